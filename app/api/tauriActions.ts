@@ -1,6 +1,31 @@
 import { invoke } from '@tauri-apps/api/core';
+import { toast } from 'vue-sonner';
 import { handleMaybeOurError, type CodeBindsForError } from '~/components/Core/Errors/errors';
-import type { ExtractIpcResponseType, RecordFromDb, Schema } from '~/types';
+import type {
+  AggregateOptions,
+  AssetSource,
+  BackupOptions,
+  BackupSchedule,
+  BatchOperation,
+  BatchTarget,
+  CalibreMapping,
+  CitationFormat,
+  CitationMapping,
+  CsvExportOptions,
+  CsvImportOptions,
+  DumpExportOptions,
+  ExtractIpcResponseType,
+  GoodreadsMapping,
+  HealthFix,
+  IcalExportOptions,
+  KindleBookTarget,
+  MovieMapping,
+  RecordFromDb,
+  Schema,
+  SiteExportOptions,
+  ThumbnailSize,
+  YearReviewOptions,
+} from '~/types';
 
 export const c_init = async () => {
   return invoke('c_init').then((v) => v as ExtractIpcResponseType<'c_init'>);
@@ -40,6 +65,51 @@ export const c_save_file = async ({
     });
 };
 
+/** Changes only passed attributes in file frontmatter, markdown body is kept as is */
+export const c_update_attributes = async ({
+  path,
+  patch,
+  hash,
+  modified,
+  forced = false,
+  errorBinds,
+}: {
+  path: string;
+  patch: RecordFromDb['attrs'];
+  /** Hash or modified from last read, used to check that file did not change on disk */
+  hash?: string;
+  modified?: number;
+  forced?: boolean;
+  errorBinds?: CodeBindsForError;
+}) => {
+  return invoke('c_update_attributes', { path, patch, hash, modified, forced })
+    .then((v) => v as ExtractIpcResponseType<'c_update_attributes'>)
+    .catch((e) => {
+      handleMaybeOurError({ e, codeBinds: errorBinds });
+      throw e;
+    });
+};
+
+/** Applies one attribute operation to many records. Per file errors are returned in result, not thrown */
+export const c_batch_edit = async ({
+  target,
+  attribute,
+  operation,
+  dryRun = false,
+}: {
+  target: BatchTarget;
+  attribute: string;
+  operation: BatchOperation;
+  dryRun?: boolean;
+}) => {
+  return invoke('c_batch_edit', { target, attribute, operation, dryRun })
+    .then((v) => v as ExtractIpcResponseType<'c_batch_edit'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
 // oxlint-disable-next-line no-unused-vars
 const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
 
@@ -155,7 +225,39 @@ export const c_resolve_schema_path = async (path: string) => {
 // Moves file to recycle bin. Works with folders too
 export const c_delete_to_trash = async (path: string) => {
   return invoke('c_delete_to_trash', { path })
-    .then((v) => v as ExtractIpcResponseType<'c_delete_to_trash'>)
+    .then((v) => {
+      const trashed = v as ExtractIpcResponseType<'c_delete_to_trash'>;
+      // Platforms where trash can't be listed return null, there is nothing to undo with
+      if (trashed) {
+        toast(`${trashed.name} moved to trash`, {
+          action: {
+            label: 'Undo',
+            // Error is already shown to user by c_restore_from_trash
+            onClick: () => c_restore_from_trash(trashed.id).catch(() => {}),
+          },
+        });
+      }
+      return trashed;
+    })
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+// Items from current root path that are still in recycle bin, most recent first
+export const c_list_trash = async () => {
+  return invoke('c_list_trash')
+    .then((v) => v as ExtractIpcResponseType<'c_list_trash'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_restore_from_trash = async (id: string) => {
+  return invoke('c_restore_from_trash', { id })
+    .then((v) => v as ExtractIpcResponseType<'c_restore_from_trash'>)
     .catch((e) => {
       handleMaybeOurError({ e });
       throw e;
@@ -170,3 +272,406 @@ export const c_create_folder_for_default_schema = async (path: string) => {
       throw e;
     });
 };
+
+// Both paths are relative to root path, rename can be undone with c_undo
+export const c_rename_path = async (from: string, to: string) => {
+  return invoke('c_rename_path', { from, to })
+    .then((v) => v as ExtractIpcResponseType<'c_rename_path'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_get_journal = async () => {
+  return invoke('c_get_journal')
+    .then((v) => v as ExtractIpcResponseType<'c_get_journal'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_undo = async () => {
+  return invoke('c_undo')
+    .then((v) => v as ExtractIpcResponseType<'c_undo'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_redo = async () => {
+  return invoke('c_redo')
+    .then((v) => v as ExtractIpcResponseType<'c_redo'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+// Returned path is the value for Image attributes
+export const c_import_image = async (source: AssetSource, name: string) => {
+  return invoke('c_import_image', { source, name })
+    .then((v) => v as ExtractIpcResponseType<'c_import_image'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+// Bytes go as raw request body, serializing them as json array is slow for big images
+export const c_import_image_bytes = async (bytes: Uint8Array, name: string) => {
+  return invoke('c_import_image_bytes', bytes, {
+    headers: { 'asom-image-name': encodeURIComponent(name) },
+  })
+    .then((v) => v as ExtractIpcResponseType<'c_import_image_bytes'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+// Absolute path to resized copy of image from assets folder, use with convertFileSrc
+export const c_get_thumbnail = async (path: string, size: ThumbnailSize) => {
+  return invoke('c_get_thumbnail', { path, size })
+    .then((v) => v as ExtractIpcResponseType<'c_get_thumbnail'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_get_asset_report = async () => {
+  return invoke('c_get_asset_report')
+    .then((v) => v as ExtractIpcResponseType<'c_get_asset_report'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_trash_orphaned_assets = async (names: string[]) => {
+  return invoke('c_trash_orphaned_assets', { names })
+    .then((v) => v as ExtractIpcResponseType<'c_trash_orphaned_assets'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_check_vault_health = async () => {
+  return invoke('c_check_vault_health')
+    .then((v) => v as ExtractIpcResponseType<'c_check_vault_health'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_apply_health_fixes = async (fixes: HealthFix[]) => {
+  return invoke('c_apply_health_fixes', { fixes })
+    .then((v) => v as ExtractIpcResponseType<'c_apply_health_fixes'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_get_unmanaged_files = async (path: string) => {
+  return invoke('c_get_unmanaged_files', { path })
+    .then((v) => v as ExtractIpcResponseType<'c_get_unmanaged_files'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_read_unmanaged_file = async (path: string) => {
+  return invoke('c_read_unmanaged_file', { path })
+    .then((v) => v as ExtractIpcResponseType<'c_read_unmanaged_file'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_create_schema_for_unmanaged = async (folder: string, name?: string) => {
+  return invoke('c_create_schema_for_unmanaged', { folder, name: name ?? null })
+    .then((v) => v as ExtractIpcResponseType<'c_create_schema_for_unmanaged'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_move_unmanaged_to_folder = async (paths: string[], folder: string) => {
+  return invoke('c_move_unmanaged_to_folder', { paths, folder })
+    .then((v) => v as ExtractIpcResponseType<'c_move_unmanaged_to_folder'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_export_csv = async (
+  target: BatchTarget,
+  output: string,
+  options: CsvExportOptions,
+) => {
+  return invoke('c_export_csv', { target, output, options })
+    .then((v) => v as ExtractIpcResponseType<'c_export_csv'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_preview_csv = async (path: string) => {
+  return invoke('c_preview_csv', { path })
+    .then((v) => v as ExtractIpcResponseType<'c_preview_csv'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_import_csv = async (path: string, folder: string, options: CsvImportOptions) => {
+  return invoke('c_import_csv', { path, folder, options })
+    .then((v) => v as ExtractIpcResponseType<'c_import_csv'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_guess_goodreads_mapping = async (folder: string) => {
+  return invoke('c_guess_goodreads_mapping', { folder })
+    .then((v) => v as ExtractIpcResponseType<'c_guess_goodreads_mapping'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_import_goodreads = async (
+  path: string,
+  folder: string,
+  mapping: GoodreadsMapping,
+) => {
+  return invoke('c_import_goodreads', { path, folder, mapping })
+    .then((v) => v as ExtractIpcResponseType<'c_import_goodreads'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_preview_movie_import = async (
+  path: string,
+  folder: string,
+  mapping?: MovieMapping,
+) => {
+  return invoke('c_preview_movie_import', { path, folder, mapping: mapping ?? null })
+    .then((v) => v as ExtractIpcResponseType<'c_preview_movie_import'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_import_movies = async (path: string, folder: string, mapping: MovieMapping) => {
+  return invoke('c_import_movies', { path, folder, mapping })
+    .then((v) => v as ExtractIpcResponseType<'c_import_movies'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_preview_kindle_import = async (path: string, folder: string) => {
+  return invoke('c_preview_kindle_import', { path, folder })
+    .then((v) => v as ExtractIpcResponseType<'c_preview_kindle_import'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_import_kindle = async (path: string, targets: KindleBookTarget[]) => {
+  return invoke('c_import_kindle', { path, targets })
+    .then((v) => v as ExtractIpcResponseType<'c_import_kindle'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_guess_calibre_mapping = async (folder: string) => {
+  return invoke('c_guess_calibre_mapping', { folder })
+    .then((v) => v as ExtractIpcResponseType<'c_guess_calibre_mapping'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_import_calibre = async (
+  library: string,
+  folder: string,
+  mapping: CalibreMapping,
+) => {
+  return invoke('c_import_calibre', { library, folder, mapping })
+    .then((v) => v as ExtractIpcResponseType<'c_import_calibre'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_export_dump = async (
+  folder: string,
+  output: string,
+  options: DumpExportOptions,
+) => {
+  return invoke('c_export_dump', { folder, output, options })
+    .then((v) => v as ExtractIpcResponseType<'c_export_dump'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_import_dump = async (path: string, folder: string) => {
+  return invoke('c_import_dump', { path, folder })
+    .then((v) => v as ExtractIpcResponseType<'c_import_dump'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_export_site = async (
+  folder: string,
+  output: string,
+  options: SiteExportOptions,
+) => {
+  return invoke('c_export_site', { folder, output, options })
+    .then((v) => v as ExtractIpcResponseType<'c_export_site'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_render_markdown = async (path: string, markdown: string | null = null) => {
+  return invoke('c_render_markdown', { path, markdown })
+    .then((v) => v as ExtractIpcResponseType<'c_render_markdown'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_create_backup = async (output: string, options: BackupOptions) => {
+  return invoke('c_create_backup', { output, options })
+    .then((v) => v as ExtractIpcResponseType<'c_create_backup'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_restore_backup = async (archive: string, target: string, switchRoot: boolean) => {
+  return invoke('c_restore_backup', { archive, target, switchRoot })
+    .then((v) => v as ExtractIpcResponseType<'c_restore_backup'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_get_backup_schedule = async () => {
+  return invoke('c_get_backup_schedule')
+    .then((v) => v as ExtractIpcResponseType<'c_get_backup_schedule'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_set_backup_schedule = async (schedule: BackupSchedule) => {
+  return invoke('c_set_backup_schedule', { schedule })
+    .then((v) => v as ExtractIpcResponseType<'c_set_backup_schedule'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_list_backups = async () => {
+  return invoke('c_list_backups')
+    .then((v) => v as ExtractIpcResponseType<'c_list_backups'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_export_ical = async (output: string, options: IcalExportOptions) => {
+  return invoke('c_export_ical', { output, options })
+    .then((v) => v as ExtractIpcResponseType<'c_export_ical'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_get_citation_mapping = async (folder: string) => {
+  return invoke('c_get_citation_mapping', { folder })
+    .then((v) => v as ExtractIpcResponseType<'c_get_citation_mapping'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_save_citation_mapping = async (folder: string, mapping: CitationMapping) => {
+  return invoke('c_save_citation_mapping', { folder, mapping })
+    .then((v) => v as ExtractIpcResponseType<'c_save_citation_mapping'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_export_citations = async (
+  folder: string,
+  output: string,
+  format: CitationFormat,
+) => {
+  return invoke('c_export_citations', { folder, output, format })
+    .then((v) => v as ExtractIpcResponseType<'c_export_citations'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_aggregate = async (options: AggregateOptions) => {
+  return invoke('c_aggregate', { options })
+    .then((v) => v as ExtractIpcResponseType<'c_aggregate'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
+
+export const c_year_review = async (options: YearReviewOptions) => {
+  return invoke('c_year_review', { options })
+    .then((v) => v as ExtractIpcResponseType<'c_year_review'>)
+    .catch((e) => {
+      handleMaybeOurError({ e });
+      throw e;
+    });
+};
@@ -127,11 +127,7 @@ export const apiValueConverters: ApiValueConverters = {
   },
   Image: {
     default: async ({ apiValue, context }) => {
-      const imageName = await saveImageFromUrl(
-        apiValue as string,
-        context.rootPath,
-        context.recordName,
-      );
+      const imageName = await saveImageFromUrl(apiValue as string, context.recordName);
 
       return { type: 'String', value: imageName };
     },
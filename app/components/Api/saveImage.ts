@@ -1,41 +1,19 @@
-import { copyFile, exists, mkdir, writeFile } from '@tauri-apps/plugin-fs';
-
-import { path } from '@tauri-apps/api';
 import { open } from '@tauri-apps/plugin-dialog';
+import { c_import_image, c_import_image_bytes } from '~/api/tauriActions';
 
-const prepareFolderFile = async (rootPath: string, desiredName: string) => {
-  const folder = await path.join(rootPath, '.assets');
-
-  const folderExists = await exists(folder);
-  if (!folderExists) {
-    await mkdir(folder, { recursive: true });
-  }
-
-  const fileName = `${desiredName}-${generateUniqId()}.jpg`;
-
-  const imagePath = await path.join(folder, fileName);
-
-  return { imagePath, fileName };
-};
-
-export const saveImageFromUrl = async (
-  imageUrl: string,
-  rootPath: string,
-  recordName: string,
-): Promise<string> => {
+export const saveImageFromUrl = async (imageUrl: string, recordName: string): Promise<string> => {
   const image = await fetch(imageUrl);
 
   const imageBlob = await image.blob();
 
   const imageBuffer = await imageBlob.arrayBuffer();
-  const { imagePath, fileName } = await prepareFolderFile(rootPath, recordName);
 
-  await writeFile(imagePath, new Uint8Array(imageBuffer));
+  const res = await c_import_image_bytes(new Uint8Array(imageBuffer), recordName);
 
-  return fileName;
+  return res.path;
 };
 
-export const saveImageFromSelection = async (rootPath: string, desiredName: string) => {
+export const saveImageFromSelection = async (desiredName: string) => {
   const result = await open({
     multiple: false,
     directory: false,
@@ -43,9 +21,7 @@ export const saveImageFromSelection = async (rootPath: string, desiredName: stri
 
   if (!result) return null;
 
-  const { imagePath, fileName } = await prepareFolderFile(rootPath, desiredName);
-
-  await copyFile(result, imagePath);
+  const res = await c_import_image({ type: 'Path', value: result }, desiredName);
 
-  return fileName;
+  return res.path;
 };
@@ -109,6 +109,18 @@ export const useFilesListV2 = ({ opened }: { opened: IOpened }) => {
         event: e.c,
       }),
   );
+  useListenToEvent('FileUpdateBatch', async (v) => {
+    for (const data of v.c) {
+      const relevant = await isExistingEventRelevant({
+        currentPath: opened._path,
+        currentSchema: files.data.value?.schema.location,
+        event: data,
+      });
+      if (relevant) {
+        onEvent({ event: 'update', data });
+      }
+    }
+  });
   useListenToEvent(
     'FileRemove',
     (v) => onEvent({ event: 'remove', data: v.c }),
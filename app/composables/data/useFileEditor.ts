@@ -1,10 +1,9 @@
 import type { UseQueryReturn } from '@pinia/colada';
 import { path } from '@tauri-apps/api';
-import { rename } from '@tauri-apps/plugin-fs';
 import { cloneDeep, throttle } from 'lodash-es';
 import type { ShallowRef } from 'vue';
 
-import { c_read_file_by_path, c_save_file } from '~/api/tauriActions';
+import { c_read_file_by_path, c_rename_path, c_save_file } from '~/api/tauriActions';
 import { useTabsStoreV2, type IOpened } from '~/composables/stores/useTabsStoreV2';
 import { useTipTap } from '~/composables/useTipTap';
 
@@ -154,6 +153,7 @@ export const useFileEditorV2 = (
           },
         },
       });
+      data.record.hash = res.hash;
       return new Date(Number(res.modified));
     },
   });
@@ -178,7 +178,7 @@ export const useFileEditorV2 = (
     ts._markPathAsIgnoredForDeletion(opened._path);
 
     await performUpdate();
-    await rename(opened._path, np);
+    await c_rename_path(opened._path, np);
 
     await listenOnce('FileAdd', (e) => {
       if (e.c.path === np) {
@@ -4,31 +4,108 @@ import type { FileEventDataRemoved } from '~~/src-tauri/bindings/FileEventDataRe
 import type { FolderEventData } from '~~/src-tauri/bindings/FolderEventData';
 import type { FolderEventDataExisting } from '~~/src-tauri/bindings/FolderEventDataExisting';
 import type { SchemaLocation } from '~~/src-tauri/bindings/SchemaLocation';
+import type { AggregateGroup } from '../../src-tauri/bindings/AggregateGroup';
+import type { AggregateOptions } from '../../src-tauri/bindings/AggregateOptions';
+import type { AggregateResult } from '../../src-tauri/bindings/AggregateResult';
+import type { AssetCleanupResult } from '../../src-tauri/bindings/AssetCleanupResult';
+import type { AssetImportResult } from '../../src-tauri/bindings/AssetImportResult';
+import type { AssetReport } from '../../src-tauri/bindings/AssetReport';
+import type { AssetSource } from '../../src-tauri/bindings/AssetSource';
 import type { AttrValue } from '../../src-tauri/bindings/AttrValue';
+import type { BackupOptions } from '../../src-tauri/bindings/BackupOptions';
+import type { BackupRestoreResult } from '../../src-tauri/bindings/BackupRestoreResult';
+import type { BackupResult } from '../../src-tauri/bindings/BackupResult';
+import type { BackupSchedule } from '../../src-tauri/bindings/BackupSchedule';
+import type { BatchEditResult } from '../../src-tauri/bindings/BatchEditResult';
+import type { BatchOperation } from '../../src-tauri/bindings/BatchOperation';
+import type { BatchTarget } from '../../src-tauri/bindings/BatchTarget';
+import type { BrokenAssetReference } from '../../src-tauri/bindings/BrokenAssetReference';
+import type { CalibreImportResult } from '../../src-tauri/bindings/CalibreImportResult';
+import type { CalibreMapping } from '../../src-tauri/bindings/CalibreMapping';
+import type { CitationExportResult } from '../../src-tauri/bindings/CitationExportResult';
+import type { CitationFormat } from '../../src-tauri/bindings/CitationFormat';
+import type { CitationMapping } from '../../src-tauri/bindings/CitationMapping';
+import type { ClippingKind } from '../../src-tauri/bindings/ClippingKind';
+import type { CsvColumnMapping } from '../../src-tauri/bindings/CsvColumnMapping';
+import type { CsvExportOptions } from '../../src-tauri/bindings/CsvExportOptions';
+import type { CsvExportResult } from '../../src-tauri/bindings/CsvExportResult';
+import type { CsvImportOptions } from '../../src-tauri/bindings/CsvImportOptions';
+import type { CsvPreview } from '../../src-tauri/bindings/CsvPreview';
 import type { DatePair } from '../../src-tauri/bindings/DatePair';
 import type { DefaultSchema } from '../../src-tauri/bindings/DefaultSchema';
+import type { Dump } from '../../src-tauri/bindings/Dump';
+import type { DumpExportOptions } from '../../src-tauri/bindings/DumpExportOptions';
+import type { DumpExportResult } from '../../src-tauri/bindings/DumpExportResult';
+import type { DumpFormat } from '../../src-tauri/bindings/DumpFormat';
+import type { DumpHeader } from '../../src-tauri/bindings/DumpHeader';
+import type { DumpRecord } from '../../src-tauri/bindings/DumpRecord';
 import type { EmptySettings } from '../../src-tauri/bindings/EmptySettings';
 import type { ErrFR } from '../../src-tauri/bindings/ErrFR';
 import type { ErrFRActionCode } from '../../src-tauri/bindings/ErrFRActionCode';
 import type { FileBreadCrumbs } from '../../src-tauri/bindings/FileBreadCrumbs';
 import type { FolderListGetResult } from '../../src-tauri/bindings/FolderListGetResult';
+import type { FrequentValue } from '../../src-tauri/bindings/FrequentValue';
+import type { GoodreadsMapping } from '../../src-tauri/bindings/GoodreadsMapping';
+import type { GroupBy } from '../../src-tauri/bindings/GroupBy';
+import type { HealthFix } from '../../src-tauri/bindings/HealthFix';
+import type { HealthFixResult } from '../../src-tauri/bindings/HealthFixResult';
+import type { HealthIssue } from '../../src-tauri/bindings/HealthIssue';
+import type { HealthIssueGroup } from '../../src-tauri/bindings/HealthIssueGroup';
+import type { HealthIssueKind } from '../../src-tauri/bindings/HealthIssueKind';
+import type { HealthReport } from '../../src-tauri/bindings/HealthReport';
+import type { HealthSeverity } from '../../src-tauri/bindings/HealthSeverity';
+import type { IcalExportOptions } from '../../src-tauri/bindings/IcalExportOptions';
+import type { IcalExportResult } from '../../src-tauri/bindings/IcalExportResult';
 import type { ImageSettings } from '../../src-tauri/bindings/ImageSettings';
+import type { ImportResult } from '../../src-tauri/bindings/ImportResult';
+import type { ImportRowError } from '../../src-tauri/bindings/ImportRowError';
 import type { InputSize } from '../../src-tauri/bindings/InputSize';
 import type { IPCEmitEvent } from '../../src-tauri/bindings/IPCEmitEvent';
 import type { IPCResponces } from '../../src-tauri/bindings/IPCResponces';
+import type { JournalEntryInfo } from '../../src-tauri/bindings/JournalEntryInfo';
+import type { JournalStatus } from '../../src-tauri/bindings/JournalStatus';
+import type { KindleBook } from '../../src-tauri/bindings/KindleBook';
+import type { KindleBookMatch } from '../../src-tauri/bindings/KindleBookMatch';
+import type { KindleBookTarget } from '../../src-tauri/bindings/KindleBookTarget';
+import type { KindleClipping } from '../../src-tauri/bindings/KindleClipping';
+import type { KindleImportResult } from '../../src-tauri/bindings/KindleImportResult';
+import type { MissingFinishedRule } from '../../src-tauri/bindings/MissingFinishedRule';
+import type { MissingStartedRule } from '../../src-tauri/bindings/MissingStartedRule';
+import type { MonthSummary } from '../../src-tauri/bindings/MonthSummary';
+import type { MovieImportPreview } from '../../src-tauri/bindings/MovieImportPreview';
+import type { MovieMapping } from '../../src-tauri/bindings/MovieMapping';
+import type { MoviePreviewEntry } from '../../src-tauri/bindings/MoviePreviewEntry';
+import type { MovieSource } from '../../src-tauri/bindings/MovieSource';
 import type { NumberSettings } from '../../src-tauri/bindings/NumberSettings';
 import type { NumberStyle } from '../../src-tauri/bindings/NumberStyle';
+import type { NumberSummary } from '../../src-tauri/bindings/NumberSummary';
 import type { RecordFromDb } from '../../src-tauri/bindings/RecordFromDb';
 import type { RecordListGetResult } from '../../src-tauri/bindings/RecordListGetResult';
+import type { RenderedMarkdown } from '../../src-tauri/bindings/RenderedMarkdown';
+import type { ReviewAttributes } from '../../src-tauri/bindings/ReviewAttributes';
+import type { ReviewItem } from '../../src-tauri/bindings/ReviewItem';
+import type { ReviewReportFormat } from '../../src-tauri/bindings/ReviewReportFormat';
+import type { ReviewReportOptions } from '../../src-tauri/bindings/ReviewReportOptions';
+import type { ScheduledBackup } from '../../src-tauri/bindings/ScheduledBackup';
 import type { Schema } from '../../src-tauri/bindings/Schema';
 import type { SchemaAttrType } from '../../src-tauri/bindings/SchemaAttrType';
 import type { SchemaItem } from '../../src-tauri/bindings/SchemaItem';
 import type { SchemaResult } from '../../src-tauri/bindings/SchemaResult';
+import type { SiteExportOptions } from '../../src-tauri/bindings/SiteExportOptions';
+import type { SiteExportResult } from '../../src-tauri/bindings/SiteExportResult';
+import type { SiteIndexLayout } from '../../src-tauri/bindings/SiteIndexLayout';
 import type { SortOrder } from '../../src-tauri/bindings/SortOrder';
 import type { TextCollectionSettings } from '../../src-tauri/bindings/TextCollectionSettings';
 import type { TextFont } from '../../src-tauri/bindings/TextFont';
 import type { TextSettings } from '../../src-tauri/bindings/TextSettings';
 import type { TextWeight } from '../../src-tauri/bindings/TextWeight';
+import type { ThumbnailSize } from '../../src-tauri/bindings/ThumbnailSize';
+import type { TrashedRecord } from '../../src-tauri/bindings/TrashedRecord';
+import type { UnmanagedMoveResult } from '../../src-tauri/bindings/UnmanagedMoveResult';
+import type { UnmanagedReadResult } from '../../src-tauri/bindings/UnmanagedReadResult';
+import type { UnmanagedRecord } from '../../src-tauri/bindings/UnmanagedRecord';
+import type { YearReview } from '../../src-tauri/bindings/YearReview';
+import type { YearReviewOptions } from '../../src-tauri/bindings/YearReviewOptions';
 
 type ExtractIPCEmitEventData<T extends IPCEmitEvent['type']> = Extract<
   IPCEmitEvent,
@@ -59,9 +136,41 @@ export const SchemaItemKeys: SchemaItem['value']['type'][] = [
 ] as const;
 
 export type {
+  AggregateGroup,
+  AggregateOptions,
+  AggregateResult,
+  AssetCleanupResult,
+  AssetImportResult,
+  AssetReport,
+  AssetSource,
   AttrValue,
+  BackupOptions,
+  BackupRestoreResult,
+  BackupResult,
+  BackupSchedule,
+  BatchEditResult,
+  BatchOperation,
+  BatchTarget,
+  BrokenAssetReference,
+  CalibreImportResult,
+  CalibreMapping,
+  CitationExportResult,
+  CitationFormat,
+  CitationMapping,
+  ClippingKind,
+  CsvColumnMapping,
+  CsvExportOptions,
+  CsvExportResult,
+  CsvImportOptions,
+  CsvPreview,
   DatePair,
   DefaultSchema,
+  Dump,
+  DumpExportOptions,
+  DumpExportResult,
+  DumpFormat,
+  DumpHeader,
+  DumpRecord,
   EmptySettings,
   ErrFR,
   ErrFRActionCode,
@@ -73,21 +182,66 @@ export type {
   FolderEventData,
   FolderEventDataExisting,
   FolderListGetResult,
+  FrequentValue,
+  GoodreadsMapping,
+  GroupBy,
+  HealthFix,
+  HealthFixResult,
+  HealthIssue,
+  HealthIssueGroup,
+  HealthIssueKind,
+  HealthReport,
+  HealthSeverity,
+  IcalExportOptions,
+  IcalExportResult,
   ImageSettings,
+  ImportResult,
+  ImportRowError,
   InputSize,
   IPCEmitEvent,
+  JournalEntryInfo,
+  JournalStatus,
+  KindleBook,
+  KindleBookMatch,
+  KindleBookTarget,
+  KindleClipping,
+  KindleImportResult,
+  MissingFinishedRule,
+  MissingStartedRule,
+  MonthSummary,
+  MovieImportPreview,
+  MovieMapping,
+  MoviePreviewEntry,
+  MovieSource,
   NumberSettings,
   NumberStyle,
+  NumberSummary,
   RecordFromDb,
   RecordListGetResult,
+  RenderedMarkdown,
+  ReviewAttributes,
+  ReviewItem,
+  ReviewReportFormat,
+  ReviewReportOptions,
+  ScheduledBackup,
   Schema,
   SchemaAttrType,
   SchemaItem,
   SchemaLocation,
   SchemaResult,
+  SiteExportOptions,
+  SiteExportResult,
+  SiteIndexLayout,
   SortOrder,
   TextCollectionSettings,
   TextFont,
   TextSettings,
   TextWeight,
+  ThumbnailSize,
+  TrashedRecord,
+  UnmanagedMoveResult,
+  UnmanagedReadResult,
+  UnmanagedRecord,
+  YearReview,
+  YearReviewOptions,
 };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GroupBy } from './GroupBy';
+
+export type AggregateOptions = { folder: string; group_by: GroupBy; numbers: Array<string> };
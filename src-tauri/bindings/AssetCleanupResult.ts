@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrFR } from './ErrFR';
+import type { TrashedRecord } from './TrashedRecord';
+
+export type AssetCleanupResult = { trashed: Array<TrashedRecord>; errors: ErrFR | null };
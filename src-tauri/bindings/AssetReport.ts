@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BrokenAssetReference } from './BrokenAssetReference';
+
+export type AssetReport = { orphaned: Array<string>; missing: Array<BrokenAssetReference> };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrFR } from './ErrFR';
+
+export type BackupRestoreResult = {
+  path: string;
+  files: number;
+  schemas: number;
+  root_switched: boolean;
+  errors: ErrFR | null;
+};
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BackupOptions } from './BackupOptions';
+
+export type BackupSchedule = {
+  enabled: boolean;
+  folder: string | null;
+  interval_hours: number;
+  keep_daily: number;
+  keep_weekly: number;
+  options: BackupOptions;
+};
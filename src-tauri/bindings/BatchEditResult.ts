@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrFR } from './ErrFR';
+
+export type BatchEditResult = {
+  dry_run: boolean;
+  changed: Array<string>;
+  unchanged: Array<string>;
+  errors: ErrFR | null;
+};
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AttrValue } from './AttrValue';
+
+export type BatchOperation =
+  | { type: 'Set'; value: AttrValue }
+  | { type: 'Clear' }
+  | { type: 'AddToCollection'; value: string }
+  | { type: 'RemoveFromCollection'; value: string }
+  | { type: 'RenameValue'; value: { from: string; to: string } };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ImportRowError } from './ImportRowError';
+
+export type CalibreImportResult = {
+  created: Array<string>;
+  updated: Array<string>;
+  unchanged: Array<string>;
+  errors: Array<ImportRowError>;
+};
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CalibreMapping = {
+  title: string | null;
+  authors: string | null;
+  series: string | null;
+  series_index: string | null;
+  tags: string | null;
+  rating: string | null;
+  publisher: string | null;
+  pubdate: string | null;
+  isbn: string | null;
+  identifiers: string | null;
+  cover: string | null;
+};
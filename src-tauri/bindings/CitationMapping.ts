@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CitationMapping = {
+  author: string | null;
+  title: string | null;
+  year: string | null;
+  publisher: string | null;
+  isbn: string | null;
+};
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CsvColumnMapping } from './CsvColumnMapping';
+
+export type CsvImportOptions = {
+  mapping: Array<CsvColumnMapping>;
+  filename_template: string;
+  collection_separator: string;
+  date_pair_separator: string;
+  decimal_comma: boolean;
+};
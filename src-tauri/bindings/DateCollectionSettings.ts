@@ -4,4 +4,5 @@ import type { SettingsTypeDateCollection } from './SettingsTypeDateCollection';
 export type DateCollectionSettings = {
   settingsType: SettingsTypeDateCollection;
   displayName?: string;
+  isPrivate?: boolean;
 };
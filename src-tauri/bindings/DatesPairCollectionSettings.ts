@@ -4,4 +4,5 @@ import type { SettingsTypeDatesPairCollection } from './SettingsTypeDatesPairCol
 export type DatesPairCollectionSettings = {
   settingsType: SettingsTypeDatesPairCollection;
   displayName?: string;
+  isPrivate?: boolean;
 };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DumpRecord } from './DumpRecord';
+import type { Schema } from './Schema';
+
+export type Dump = {
+  version: number;
+  folder: string;
+  schema: Schema;
+  records: Array<DumpRecord>;
+};
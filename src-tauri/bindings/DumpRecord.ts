@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AttrValue } from './AttrValue';
+
+export type DumpRecord = {
+  path: string;
+  attrs: { [key in string]: AttrValue };
+  markdown: string | null;
+};
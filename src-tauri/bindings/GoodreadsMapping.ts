@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GoodreadsMapping = {
+  title: string | null;
+  author: string | null;
+  isbn: string | null;
+  rating: string | null;
+  shelves: string | null;
+  read: string | null;
+  year: string | null;
+  pages: string | null;
+  review_as_markdown: boolean;
+};
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GroupBy =
+  | { type: 'All' }
+  | { type: 'Value'; attribute: string }
+  | { type: 'Year'; attribute: string }
+  | { type: 'Month'; attribute: string };
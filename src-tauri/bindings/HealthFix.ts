@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type HealthFix =
+  | { type: 'RemoveDuplicateSchemaItems'; value: { schema_path: string } }
+  | { type: 'CoerceValue'; value: { path: string; attribute: string } }
+  | { type: 'SwapDatePair'; value: { path: string; attribute: string; index: number } };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HealthFix } from './HealthFix';
+
+export type HealthIssue = {
+  path: string;
+  attribute: string | null;
+  message: string;
+  fix: HealthFix | null;
+};
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HealthIssue } from './HealthIssue';
+import type { HealthIssueKind } from './HealthIssueKind';
+import type { HealthSeverity } from './HealthSeverity';
+
+export type HealthIssueGroup = {
+  kind: HealthIssueKind;
+  severity: HealthSeverity;
+  issues: Array<HealthIssue>;
+};
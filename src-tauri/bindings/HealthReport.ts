@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HealthIssueGroup } from './HealthIssueGroup';
+
+export type HealthReport = {
+  groups: Array<HealthIssueGroup>;
+  checked_schemas: number;
+  checked_records: number;
+};
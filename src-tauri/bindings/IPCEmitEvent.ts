@@ -4,13 +4,18 @@ import type { FileEventDataExisting } from './FileEventDataExisting';
 import type { FileEventDataRemoved } from './FileEventDataRemoved';
 import type { FolderEventData } from './FolderEventData';
 import type { Schema } from './Schema';
+import type { UnmanagedRecord } from './UnmanagedRecord';
 
 export type IPCEmitEvent =
   | { type: 'FileRemove'; data: FileEventDataRemoved }
   | { type: 'FileAdd'; data: FileEventDataExisting }
   | { type: 'FileUpdate'; data: FileEventDataExisting }
+  | { type: 'FileUpdateBatch'; data: Array<FileEventDataExisting> }
   | { type: 'FolderRemove'; data: FolderEventData }
   | { type: 'FolderAdd'; data: FolderEventData }
+  | { type: 'UnmanagedFileAdd'; data: UnmanagedRecord }
+  | { type: 'UnmanagedFileUpdate'; data: UnmanagedRecord }
+  | { type: 'UnmanagedFileRemove'; data: string }
   | { type: 'ErrorHappened'; data: ErrFR }
   | { type: 'SchemasUpdated'; data: { [key in string]: Schema } }
   | { type: 'EventOverflow'; data: number };
@@ -1,11 +1,43 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AggregateResult } from './AggregateResult';
+import type { AssetCleanupResult } from './AssetCleanupResult';
+import type { AssetImportResult } from './AssetImportResult';
+import type { AssetReport } from './AssetReport';
+import type { BackupRestoreResult } from './BackupRestoreResult';
+import type { BackupResult } from './BackupResult';
+import type { BackupSchedule } from './BackupSchedule';
+import type { BatchEditResult } from './BatchEditResult';
+import type { CalibreImportResult } from './CalibreImportResult';
+import type { CalibreMapping } from './CalibreMapping';
+import type { CitationExportResult } from './CitationExportResult';
+import type { CitationMapping } from './CitationMapping';
+import type { CsvExportResult } from './CsvExportResult';
+import type { CsvPreview } from './CsvPreview';
+import type { DumpExportResult } from './DumpExportResult';
 import type { ErrFR } from './ErrFR';
 import type { FolderListGetResult } from './FolderListGetResult';
+import type { GoodreadsMapping } from './GoodreadsMapping';
+import type { HealthFixResult } from './HealthFixResult';
+import type { HealthReport } from './HealthReport';
+import type { IcalExportResult } from './IcalExportResult';
+import type { ImportResult } from './ImportResult';
 import type { IPCReadFileByPathResult } from './IPCReadFileByPathResult';
+import type { JournalStatus } from './JournalStatus';
+import type { KindleBookMatch } from './KindleBookMatch';
+import type { KindleImportResult } from './KindleImportResult';
+import type { MovieImportPreview } from './MovieImportPreview';
 import type { RecordListGetResult } from './RecordListGetResult';
 import type { RecordSaveResult } from './RecordSaveResult';
+import type { RenderedMarkdown } from './RenderedMarkdown';
+import type { ScheduledBackup } from './ScheduledBackup';
 import type { Schema } from './Schema';
 import type { SchemaResult } from './SchemaResult';
+import type { SiteExportResult } from './SiteExportResult';
+import type { TrashedRecord } from './TrashedRecord';
+import type { UnmanagedMoveResult } from './UnmanagedMoveResult';
+import type { UnmanagedReadResult } from './UnmanagedReadResult';
+import type { UnmanagedRecord } from './UnmanagedRecord';
+import type { YearReview } from './YearReview';
 
 export type IPCResponces = {
   c_init: { Ok: string | null } | { Err: ErrFR };
@@ -21,7 +53,52 @@ export type IPCResponces = {
   c_load_schema: { Ok: Schema } | { Err: ErrFR };
   c_save_schema: { Ok: Schema } | { Err: ErrFR };
   c_save_file: { Ok: RecordSaveResult } | { Err: ErrFR };
+  c_update_attributes: { Ok: RecordSaveResult } | { Err: ErrFR };
+  c_batch_edit: { Ok: BatchEditResult } | { Err: ErrFR };
   c_resolve_schema_path: { Ok: SchemaResult | null } | { Err: ErrFR };
-  c_delete_to_trash: { Ok: null } | { Err: ErrFR };
+  c_delete_to_trash: { Ok: TrashedRecord | null } | { Err: ErrFR };
+  c_list_trash: { Ok: Array<TrashedRecord> } | { Err: ErrFR };
+  c_restore_from_trash: { Ok: string } | { Err: ErrFR };
   c_create_folder_for_default_schema: { Ok: string } | { Err: ErrFR };
+  c_rename_path: { Ok: string } | { Err: ErrFR };
+  c_get_journal: { Ok: JournalStatus } | { Err: ErrFR };
+  c_undo: { Ok: JournalStatus } | { Err: ErrFR };
+  c_redo: { Ok: JournalStatus } | { Err: ErrFR };
+  c_import_image: { Ok: AssetImportResult } | { Err: ErrFR };
+  c_import_image_bytes: { Ok: AssetImportResult } | { Err: ErrFR };
+  c_get_thumbnail: { Ok: string } | { Err: ErrFR };
+  c_get_asset_report: { Ok: AssetReport } | { Err: ErrFR };
+  c_trash_orphaned_assets: { Ok: AssetCleanupResult } | { Err: ErrFR };
+  c_check_vault_health: { Ok: HealthReport } | { Err: ErrFR };
+  c_apply_health_fixes: { Ok: HealthFixResult } | { Err: ErrFR };
+  c_get_unmanaged_files: { Ok: Array<UnmanagedRecord> } | { Err: ErrFR };
+  c_read_unmanaged_file: { Ok: UnmanagedReadResult } | { Err: ErrFR };
+  c_create_schema_for_unmanaged: { Ok: Schema } | { Err: ErrFR };
+  c_move_unmanaged_to_folder: { Ok: UnmanagedMoveResult } | { Err: ErrFR };
+  c_export_csv: { Ok: CsvExportResult } | { Err: ErrFR };
+  c_preview_csv: { Ok: CsvPreview } | { Err: ErrFR };
+  c_import_csv: { Ok: ImportResult } | { Err: ErrFR };
+  c_guess_goodreads_mapping: { Ok: GoodreadsMapping } | { Err: ErrFR };
+  c_import_goodreads: { Ok: ImportResult } | { Err: ErrFR };
+  c_preview_movie_import: { Ok: MovieImportPreview } | { Err: ErrFR };
+  c_import_movies: { Ok: ImportResult } | { Err: ErrFR };
+  c_preview_kindle_import: { Ok: Array<KindleBookMatch> } | { Err: ErrFR };
+  c_import_kindle: { Ok: KindleImportResult } | { Err: ErrFR };
+  c_guess_calibre_mapping: { Ok: CalibreMapping } | { Err: ErrFR };
+  c_import_calibre: { Ok: CalibreImportResult } | { Err: ErrFR };
+  c_export_dump: { Ok: DumpExportResult } | { Err: ErrFR };
+  c_import_dump: { Ok: ImportResult } | { Err: ErrFR };
+  c_export_site: { Ok: SiteExportResult } | { Err: ErrFR };
+  c_render_markdown: { Ok: RenderedMarkdown } | { Err: ErrFR };
+  c_create_backup: { Ok: BackupResult } | { Err: ErrFR };
+  c_restore_backup: { Ok: BackupRestoreResult } | { Err: ErrFR };
+  c_get_backup_schedule: { Ok: BackupSchedule } | { Err: ErrFR };
+  c_set_backup_schedule: { Ok: BackupSchedule } | { Err: ErrFR };
+  c_list_backups: { Ok: Array<ScheduledBackup> } | { Err: ErrFR };
+  c_export_ical: { Ok: IcalExportResult } | { Err: ErrFR };
+  c_get_citation_mapping: { Ok: CitationMapping } | { Err: ErrFR };
+  c_save_citation_mapping: { Ok: CitationMapping } | { Err: ErrFR };
+  c_export_citations: { Ok: CitationExportResult } | { Err: ErrFR };
+  c_aggregate: { Ok: AggregateResult } | { Err: ErrFR };
+  c_year_review: { Ok: YearReview } | { Err: ErrFR };
 };
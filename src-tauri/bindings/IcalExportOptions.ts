@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MissingFinishedRule } from './MissingFinishedRule';
+import type { MissingStartedRule } from './MissingStartedRule';
+
+export type IcalExportOptions = {
+  folders: Array<string>;
+  from: string | null;
+  to: string | null;
+  missing_finished: MissingFinishedRule;
+  missing_started: MissingStartedRule;
+};
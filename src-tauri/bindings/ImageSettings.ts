@@ -4,5 +4,6 @@ import type { SettingsTypeImage } from './SettingsTypeImage';
 export type ImageSettings = {
   settingsType: SettingsTypeImage;
   displayName?: string;
+  isPrivate?: boolean;
   aspectRatio?: string;
 };
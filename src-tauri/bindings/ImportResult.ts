@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ImportRowError } from './ImportRowError';
+
+export type ImportResult = {
+  created: Array<string>;
+  errors: Array<ImportRowError>;
+  duplicates: Array<number>;
+};
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { KindleClipping } from './KindleClipping';
+
+export type KindleBook = {
+  title: string;
+  author: string | null;
+  clippings: Array<KindleClipping>;
+};
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type KindleBookMatch = {
+  title: string;
+  author: string | null;
+  clippings: number;
+  record: string | null;
+  score: number;
+  new_clippings: number;
+};
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ClippingKind } from './ClippingKind';
+
+export type KindleClipping = {
+  kind: ClippingKind;
+  location: string;
+  added: string | null;
+  text: string;
+};
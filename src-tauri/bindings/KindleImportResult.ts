@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrFR } from './ErrFR';
+
+export type KindleImportResult = {
+  updated: Array<string>;
+  added: number;
+  skipped: number;
+  errors: ErrFR | null;
+};
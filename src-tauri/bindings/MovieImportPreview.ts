@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MovieMapping } from './MovieMapping';
+import type { MoviePreviewEntry } from './MoviePreviewEntry';
+import type { MovieSource } from './MovieSource';
+
+export type MovieImportPreview = {
+  source: MovieSource;
+  mapping: MovieMapping;
+  entries: Array<MoviePreviewEntry>;
+};
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type MovieMapping = {
+  title: string | null;
+  year: string | null;
+  premiere: string | null;
+  director: string | null;
+  rating: string | null;
+  watched: string | null;
+  tags: string | null;
+};
@@ -6,6 +6,7 @@ import type { SettingsTypeNumber } from './SettingsTypeNumber';
 export type NumberSettings = {
   settingsType: SettingsTypeNumber;
   displayName?: string;
+  isPrivate?: boolean;
   size?: InputSize;
   min?: number;
   max?: number;
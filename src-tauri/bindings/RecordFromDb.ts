@@ -4,6 +4,7 @@ import type { AttrValue } from './AttrValue';
 export type RecordFromDb = {
   path: string | null;
   modified: number;
+  hash?: string;
   markdown: string | null;
   attrs: { [key in string]: AttrValue };
 };
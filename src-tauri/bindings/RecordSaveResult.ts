@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type RecordSaveResult = { path: string; modified: number };
+export type RecordSaveResult = { path: string; modified: number; hash: string };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ReviewAttributes = {
+  dates: string | null;
+  title: string | null;
+  rating: string | null;
+  length: string | null;
+  authors: string | null;
+  tags: string | null;
+};
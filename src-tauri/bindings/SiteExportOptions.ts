@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SiteIndexLayout } from './SiteIndexLayout';
+
+export type SiteExportOptions = {
+  title: string | null;
+  layout: SiteIndexLayout;
+  include_markdown: boolean;
+  exclude_private: boolean;
+};
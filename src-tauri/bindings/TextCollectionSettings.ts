@@ -7,6 +7,7 @@ import type { TextWeight } from './TextWeight';
 export type TextCollectionSettings = {
   settingsType: SettingsTypeTextCollection;
   displayName?: string;
+  isPrivate?: boolean;
   size?: InputSize;
   font?: TextFont;
   weight?: TextWeight;
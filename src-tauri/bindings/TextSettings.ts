@@ -7,6 +7,7 @@ import type { TextWeight } from './TextWeight';
 export type TextSettings = {
   settingsType: SettingsTypeText;
   displayName?: string;
+  isPrivate?: boolean;
   size?: InputSize;
   font?: TextFont;
   weight?: TextWeight;
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrFR } from './ErrFR';
+import type { UnmanagedRecord } from './UnmanagedRecord';
+
+export type UnmanagedReadResult = {
+  record: UnmanagedRecord;
+  front_matter: string;
+  markdown: string;
+  parsing_error: ErrFR | null;
+};
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FrequentValue } from './FrequentValue';
+import type { MonthSummary } from './MonthSummary';
+import type { ReviewAttributes } from './ReviewAttributes';
+import type { ReviewItem } from './ReviewItem';
+
+export type YearReview = {
+  year: number;
+  schema_name: string;
+  attributes: ReviewAttributes;
+  finished: number;
+  finishes: number;
+  started: number;
+  total_length: number | null;
+  average_rating: number | null;
+  months: Array<MonthSummary>;
+  top_rated: Array<ReviewItem>;
+  authors: Array<FrequentValue>;
+  tags: Array<FrequentValue>;
+  longest: ReviewItem | null;
+  shortest: ReviewItem | null;
+  rereads: Array<ReviewItem>;
+  report: string | null;
+};
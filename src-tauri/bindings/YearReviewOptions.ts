@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReviewAttributes } from './ReviewAttributes';
+import type { ReviewReportOptions } from './ReviewReportOptions';
+
+export type YearReviewOptions = {
+  folder: string;
+  year: number;
+  attributes: ReviewAttributes | null;
+  report: ReviewReportOptions | null;
+};
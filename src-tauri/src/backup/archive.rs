@@ -0,0 +1,411 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime as ChronoDateTime, Datelike, Local, Timelike};
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipArchive, ZipWriter};
+
+use crate::core::core_state::AppContext;
+use crate::export::json::to_portable_path;
+use crate::files::assets::ASSETS_FOLDER_NAME;
+use crate::schema::schema_cache::{INTERNAL_FOLDER_NAME, SCHEMA_FILE_NAME};
+use crate::schema::types::Schema;
+use crate::utils::errorhandling::ErrFR;
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct BackupOptions {
+    /* Schemas and other configs from .asom folders */
+    pub include_configs: bool,
+    pub include_assets: bool,
+    /* Skip files listed in .gitignore and .ignore files inside the vault */
+    pub exclude_ignored: bool,
+}
+
+impl Default for BackupOptions {
+    fn default() -> Self {
+        BackupOptions {
+            include_configs: true,
+            include_assets: true,
+            exclude_ignored: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct BackupResult {
+    pub path: String,
+    pub files: u32,
+    #[ts(type = "number")]
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct BackupRestoreResult {
+    /* Folder the archive was unpacked into */
+    pub path: String,
+    pub files: u32,
+    pub schemas: u32,
+    pub root_switched: bool,
+    /* Schemas that can't be read, root path is not switched when there are any */
+    pub errors: Option<ErrFR>,
+}
+
+/* Appended to archive name while it's being written */
+pub const PARTIAL_EXTENSION: &str = ".part";
+
+fn io_err(title: &str, path: &Path, e: impl ToString) -> Box<ErrFR> {
+    Box::new(ErrFR::new(title).info(&path.to_string_lossy()).raw(e))
+}
+
+/* Hidden folders like .trash or .git are not part of the vault, configs and assets are optional */
+fn is_archived_folder(name: &str, depth: usize, options: &BackupOptions) -> bool {
+    match name {
+        INTERNAL_FOLDER_NAME => options.include_configs,
+        ASSETS_FOLDER_NAME => depth == 1 && options.include_assets,
+        n => !n.starts_with('.'),
+    }
+}
+
+/* Folders and files to archive, paths are relative to root */
+fn collect_entries(
+    root: &Path,
+    options: &BackupOptions,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Box<ErrFR>> {
+    let filter_options = options.clone();
+    let walker = WalkBuilder::new(root)
+        .standard_filters(false)
+        .git_ignore(options.exclude_ignored)
+        .ignore(options.exclude_ignored)
+        .require_git(false)
+        .filter_entry(move |e| {
+            e.depth() == 0
+                || !e.file_type().is_some_and(|t| t.is_dir())
+                || is_archived_folder(&e.file_name().to_string_lossy(), e.depth(), &filter_options)
+        })
+        .build();
+
+    let mut folders: Vec<PathBuf> = vec![];
+    let mut files: Vec<PathBuf> = vec![];
+
+    for entry in walker {
+        let entry = entry.map_err(|e| Box::new(ErrFR::new("Error reading vault").raw(e)))?;
+        if entry.depth() == 0 {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+
+        match entry.file_type() {
+            Some(t) if t.is_dir() => folders.push(relative.to_path_buf()),
+            Some(t) if t.is_file() => files.push(relative.to_path_buf()),
+            // Symlinks are skipped, archive should not contain anything from outside of vault
+            _ => (),
+        }
+    }
+
+    Ok((folders, files))
+}
+
+fn modified_time(path: &Path) -> Option<DateTime> {
+    let modified: ChronoDateTime<Local> = fs::metadata(path).ok()?.modified().ok()?.into();
+    DateTime::from_date_and_time(
+        u16::try_from(modified.year()).ok()?,
+        modified.month() as u8,
+        modified.day() as u8,
+        modified.hour() as u8,
+        modified.minute() as u8,
+        modified.second() as u8,
+    )
+    .ok()
+}
+
+/*
+    Canonical form of a folder that may not exist yet: nearest existing ancestor is canonicalized
+    and the rest of the path is appended to it.
+*/
+fn resolve_folder(folder: &Path) -> PathBuf {
+    let Some((existing, canonical)) = folder
+        .ancestors()
+        .find_map(|a| fs::canonicalize(a).ok().map(|c| (a, c)))
+    else {
+        return folder.to_path_buf();
+    };
+
+    let mut result = canonical;
+    for component in folder
+        .strip_prefix(existing)
+        .unwrap_or(Path::new(""))
+        .components()
+    {
+        match component {
+            Component::ParentDir => {
+                result.pop();
+            }
+            Component::Normal(c) => result.push(c),
+            _ => (),
+        }
+    }
+    result
+}
+
+/* Archive is written next to the vault, never inside of it, so it does not end up in itself */
+pub fn check_outside_of_vault(root: &Path, folder: &Path) -> Result<(), Box<ErrFR>> {
+    let root_canonical = fs::canonicalize(root).unwrap_or(root.to_path_buf());
+    match resolve_folder(folder).starts_with(&root_canonical) {
+        true => Err(Box::new(
+            ErrFR::new("Backup can't be saved inside of the vault")
+                .info("Choose a folder outside of root path")
+                .raw(folder.to_string_lossy()),
+        )),
+        false => Ok(()),
+    }
+}
+
+/* Writes into the given path, returns number of archived files */
+fn write_archive(root: &Path, output: &Path, options: &BackupOptions) -> Result<u32, Box<ErrFR>> {
+    let (folders, files) = collect_entries(root, options)?;
+
+    let file = File::create(output).map_err(|e| io_err("Error creating archive", output, e))?;
+    let mut zip = ZipWriter::new(file);
+    let zip_options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let write_err = |e: zip::result::ZipError| io_err("Error writing archive", output, e);
+
+    for folder in folders.iter() {
+        zip.add_directory(to_portable_path(folder), zip_options)
+            .map_err(write_err)?;
+    }
+
+    for path_relative in files.iter() {
+        let path_absolute = root.join(path_relative);
+        let options = match modified_time(&path_absolute) {
+            Some(t) => zip_options.last_modified_time(t),
+            None => zip_options,
+        };
+        let mut source = File::open(&path_absolute)
+            .map_err(|e| io_err("Error reading file", &path_absolute, e))?;
+
+        zip.start_file(to_portable_path(path_relative), options)
+            .map_err(write_err)?;
+        io::copy(&mut source, &mut zip).map_err(|e| io_err("Error writing archive", output, e))?;
+    }
+
+    zip.finish().map_err(write_err)?;
+
+    Ok(files.len() as u32)
+}
+
+/* Archive that is still being written, it only gets its name when complete */
+pub fn partial_archive_path(output: &Path) -> PathBuf {
+    let mut name = output.file_name().unwrap_or_default().to_os_string();
+    name.push(PARTIAL_EXTENSION);
+    output.with_file_name(name)
+}
+
+pub async fn create_backup(
+    ctx: &AppContext,
+    output: &Path,
+    options: &BackupOptions,
+) -> Result<BackupResult, Box<ErrFR>> {
+    let root = ctx.root_path_as_buf().await?;
+    check_outside_of_vault(&root, output.parent().unwrap_or(Path::new("")))?;
+
+    let partial = partial_archive_path(output);
+    let (partial_moved, options) = (partial.clone(), options.clone());
+    let written = tauri::async_runtime::spawn_blocking(move || {
+        write_archive(&root, &partial_moved, &options)
+    })
+    .await
+    .map_err(|e| Box::new(ErrFR::new("Error writing archive").raw(e)))
+    .and_then(|r| r)
+    .and_then(|files| {
+        fs::rename(&partial, output)
+            .map(|_| files)
+            .map_err(|e| io_err("Error writing archive", output, e))
+    });
+
+    let files = match written {
+        Ok(v) => v,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+    };
+
+    Ok(BackupResult {
+        path: output.to_string_lossy().to_string(),
+        files,
+        size: fs::metadata(output).map(|m| m.len()).unwrap_or_default(),
+    })
+}
+
+fn unpack(archive: &mut ZipArchive<File>, target: &Path) -> Result<u32, Box<ErrFR>> {
+    let read_err = |e: zip::result::ZipError| Box::new(ErrFR::new("Error reading archive").raw(e));
+
+    // Every path is checked before anything is written, archive with one bad entry is rejected whole
+    let mut entries: Vec<(usize, PathBuf, bool)> = vec![];
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(read_err)?;
+        let Some(path) = entry.enclosed_name() else {
+            return Err(Box::new(
+                ErrFR::new("Archive contains a path outside of its folder").raw(entry.name()),
+            ));
+        };
+        entries.push((i, path, entry.is_dir()));
+    }
+
+    let mut files = 0;
+    for (i, path, is_dir) in entries {
+        let path_absolute = target.join(&path);
+        let folder = match is_dir {
+            true => path_absolute.as_path(),
+            false => path_absolute.parent().unwrap_or(target),
+        };
+        fs::create_dir_all(folder).map_err(|e| io_err("Error creating directory", folder, e))?;
+        if is_dir {
+            continue;
+        }
+
+        let mut entry = archive.by_index(i).map_err(read_err)?;
+        let mut file = File::create(&path_absolute)
+            .map_err(|e| io_err("Error writing file", &path_absolute, e))?;
+        io::copy(&mut entry, &mut file)
+            .map_err(|e| io_err("Error writing file", &path_absolute, e))?;
+        files += 1;
+    }
+
+    Ok(files)
+}
+
+/* Every schema in unpacked folder is parsed, app would hide records of the broken ones */
+fn validate_schemas(target: &Path) -> (u32, Vec<ErrFR>) {
+    let mut checked = 0;
+    let mut errors: Vec<ErrFR> = vec![];
+
+    for entry in WalkDir::new(target).into_iter().filter_map(Result::ok) {
+        let is_schema = entry.file_name() == SCHEMA_FILE_NAME
+            && entry.path().parent().and_then(|p| p.file_name())
+                == Some(INTERNAL_FOLDER_NAME.as_ref());
+        if !is_schema || !entry.file_type().is_file() {
+            continue;
+        }
+
+        checked += 1;
+        let parsed = fs::read_to_string(entry.path())
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_yml::from_str::<Schema>(&c).map_err(|e| e.to_string()));
+        if let Err(e) = parsed {
+            let path = entry.path().strip_prefix(target).unwrap_or(entry.path());
+            errors.push(
+                ErrFR::new("Schema can't be read")
+                    .info(&path.to_string_lossy())
+                    .raw(e),
+            );
+        }
+    }
+
+    (checked, errors)
+}
+
+/*
+    Unpacks archive into a new or empty folder, files are never overwritten.
+    Folder that was created for restore is removed when unpacking fails.
+*/
+pub async fn restore_backup(
+    archive: &Path,
+    target: &Path,
+) -> Result<BackupRestoreResult, Box<ErrFR>> {
+    let (archive, target) = (archive.to_path_buf(), target.to_path_buf());
+    tauri::async_runtime::spawn_blocking(move || restore_into(&archive, &target))
+        .await
+        .map_err(|e| Box::new(ErrFR::new("Error restoring backup").raw(e)))?
+}
+
+fn restore_into(archive: &Path, target: &Path) -> Result<BackupRestoreResult, Box<ErrFR>> {
+    let created_target = !target.exists();
+    if !created_target {
+        let is_empty = fs::read_dir(target)
+            .map_err(|e| io_err("Error reading directory", target, e))?
+            .next()
+            .is_none();
+        if !is_empty {
+            return Err(Box::new(
+                ErrFR::new("Backup can only be restored into a new or empty folder")
+                    .raw(target.to_string_lossy()),
+            ));
+        }
+    }
+
+    let file = File::open(archive).map_err(|e| io_err("Error reading archive", archive, e))?;
+    let mut zip =
+        ZipArchive::new(file).map_err(|e| io_err("File is not a zip archive", archive, e))?;
+
+    fs::create_dir_all(target).map_err(|e| io_err("Error creating directory", target, e))?;
+    let files = match unpack(&mut zip, target) {
+        Ok(v) => v,
+        Err(e) => {
+            if created_target {
+                let _ = fs::remove_dir_all(target);
+            }
+            return Err(e);
+        }
+    };
+
+    let (schemas, errors) = validate_schemas(target);
+
+    Ok(BackupRestoreResult {
+        path: target.to_string_lossy().to_string(),
+        files,
+        schemas,
+        root_switched: false,
+        errors: match errors.is_empty() {
+            true => None,
+            false => Some(ErrFR::new("Some schemas are invalid").subs(errors)),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archived_folders() {
+        let options = BackupOptions {
+            include_configs: false,
+            include_assets: true,
+            exclude_ignored: false,
+        };
+        assert!(is_archived_folder("books", 1, &options));
+        assert!(is_archived_folder(ASSETS_FOLDER_NAME, 1, &options));
+        assert!(!is_archived_folder(ASSETS_FOLDER_NAME, 2, &options));
+        assert!(!is_archived_folder(INTERNAL_FOLDER_NAME, 2, &options));
+        assert!(!is_archived_folder(".trash", 1, &options));
+    }
+
+    #[test]
+    fn folders_that_do_not_exist_yet() {
+        let root = tempfile::tempdir().unwrap();
+        let vault = root.path().join("vault");
+        fs::create_dir(&vault).unwrap();
+
+        assert!(check_outside_of_vault(&vault, &vault.join("backups").join("daily")).is_err());
+        assert!(check_outside_of_vault(&vault, &root.path().join("backups")).is_ok());
+        assert!(check_outside_of_vault(
+            &vault,
+            &root
+                .path()
+                .join("backups")
+                .join("..")
+                .join("vault")
+                .join("new")
+        )
+        .is_err());
+    }
+}
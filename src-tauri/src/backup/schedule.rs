@@ -0,0 +1,365 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Local, NaiveDateTime, TimeDelta, TimeZone};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::time::{sleep, Duration};
+use ts_rs::TS;
+
+use crate::backup::archive::{check_outside_of_vault, create_backup, BackupOptions, BackupResult};
+use crate::core::core_state::{AppContext, CoreStateManager};
+use crate::files::utils::get_content_hash;
+use crate::utils::errorhandling::{send_err_to_frontend, ErrFR};
+
+const SCHEDULE_FILE_NAME: &str = "backup_schedule.json";
+const SCHEDULE_KEY: &str = "BACKUP_SCHEDULE";
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H%M%S";
+const PATH_HASH_LENGTH: usize = 8;
+
+// First check waits for vault to be opened on start
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+// Failing backup should not show an error every few minutes
+const RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct BackupSchedule {
+    pub enabled: bool,
+    /* Absolute path, must be outside of the vault */
+    pub folder: Option<String>,
+    pub interval_hours: u32,
+    /* Newest backup of each of that many last days is kept */
+    pub keep_daily: u32,
+    /* Newest backup of each of that many last weeks is kept */
+    pub keep_weekly: u32,
+    pub options: BackupOptions,
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        BackupSchedule {
+            enabled: false,
+            folder: None,
+            interval_hours: 24,
+            keep_daily: 7,
+            keep_weekly: 4,
+            options: BackupOptions::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct ScheduledBackup {
+    pub path: String,
+    #[ts(type = "number")]
+    pub created: i64, // UNIX milliseconds
+    #[ts(type = "number")]
+    pub size: u64,
+}
+
+pub fn get_backup_schedule_from_storage<T: tauri::Runtime>(
+    app: &AppHandle<T>,
+) -> Result<BackupSchedule, Box<ErrFR>> {
+    let store = app
+        .store(SCHEDULE_FILE_NAME)
+        .map_err(|e| Box::new(ErrFR::new("Error getting store").raw(e.to_string())))?;
+
+    match store.get(SCHEDULE_KEY) {
+        Some(v) => serde_json::from_value(v).or_else(|e| {
+            log::warn!("backup: unable to parse saved schedule {}", e);
+            Ok(BackupSchedule::default())
+        }),
+        None => Ok(BackupSchedule::default()),
+    }
+}
+
+pub async fn set_backup_schedule_to_storage<T: tauri::Runtime>(
+    app: &AppHandle<T>,
+    ctx: &AppContext,
+    schedule: BackupSchedule,
+) -> Result<BackupSchedule, Box<ErrFR>> {
+    if schedule.enabled {
+        let folder = match schedule.folder.as_deref().map(|f| f.trim()) {
+            Some(f) if !f.is_empty() => PathBuf::from(f),
+            _ => return Err(Box::new(ErrFR::new("Choose a folder for backups"))),
+        };
+        if let Some(root) = ctx.root_path_option().await {
+            check_outside_of_vault(Path::new(&root), &folder)?;
+        }
+    }
+
+    let store = app
+        .store(SCHEDULE_FILE_NAME)
+        .map_err(|e| Box::new(ErrFR::new("Error getting store").raw(e.to_string())))?;
+    let value = serde_json::to_value(&schedule)
+        .map_err(|e| Box::new(ErrFR::new("Error saving backup schedule").raw(e)))?;
+
+    store.set(SCHEDULE_KEY, value);
+    store
+        .save()
+        .map_err(|e| Box::new(ErrFR::new("Error saving store").raw(e.to_string())))?;
+
+    Ok(schedule)
+}
+
+/*
+    Several vaults can share a backup folder, file names start with vault folder name
+    and a short hash of its path, so vaults with the same folder name don't remove each other's backups
+*/
+fn backup_prefix(root: &Path) -> String {
+    format!(
+        "{}_{}_",
+        root.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "vault".to_string()),
+        &get_content_hash(root.to_string_lossy().as_bytes())[..PATH_HASH_LENGTH]
+    )
+}
+
+/* Archives that are still being written have a different extension and are not matched */
+fn parse_backup_name(name: &str, prefix: &str) -> Option<NaiveDateTime> {
+    let timestamp = name.strip_prefix(prefix)?.strip_suffix(".zip")?;
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()
+}
+
+/* Backups of current vault in folder, newest first */
+fn read_backups(folder: &Path, root: &Path) -> Result<Vec<(NaiveDateTime, PathBuf)>, Box<ErrFR>> {
+    if !folder.exists() {
+        return Ok(vec![]);
+    }
+    let prefix = backup_prefix(root);
+
+    let mut backups: Vec<(NaiveDateTime, PathBuf)> = fs::read_dir(folder)
+        .map_err(|e| {
+            Box::new(
+                ErrFR::new("Error reading backups folder")
+                    .info(&folder.to_string_lossy())
+                    .raw(e),
+            )
+        })?
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|e| {
+            let created = parse_backup_name(&e.file_name().to_string_lossy(), &prefix)?;
+            Some((created, e.path()))
+        })
+        .collect();
+
+    backups.sort_by_key(|b| std::cmp::Reverse(b.0));
+    Ok(backups)
+}
+
+pub async fn list_scheduled_backups(
+    ctx: &AppContext,
+    schedule: &BackupSchedule,
+) -> Result<Vec<ScheduledBackup>, Box<ErrFR>> {
+    let (Some(folder), Some(root)) = (schedule.folder.as_deref(), ctx.root_path_option().await)
+    else {
+        return Ok(vec![]);
+    };
+
+    Ok(read_backups(Path::new(folder), Path::new(&root))?
+        .into_iter()
+        .map(|(created, path)| ScheduledBackup {
+            size: fs::metadata(&path).map(|m| m.len()).unwrap_or_default(),
+            path: path.to_string_lossy().to_string(),
+            created: Local
+                .from_local_datetime(&created)
+                .earliest()
+                .map(|d| d.timestamp_millis())
+                .unwrap_or_else(|| created.and_utc().timestamp_millis()),
+        })
+        .collect())
+}
+
+fn is_backup_due(latest: Option<&NaiveDateTime>, now: &NaiveDateTime, interval_hours: u32) -> bool {
+    match latest {
+        Some(latest) => *now - *latest >= TimeDelta::hours(interval_hours.max(1) as i64),
+        None => true,
+    }
+}
+
+/*
+    Indexes of backups to keep: newest one of each of the last days and weeks that have backups.
+    Newest backup is always kept, even when both limits are zero.
+*/
+fn retained_backups(
+    created: &[NaiveDateTime],
+    keep_daily: u32,
+    keep_weekly: u32,
+) -> HashSet<usize> {
+    let mut order: Vec<usize> = (0..created.len()).collect();
+    order.sort_by_key(|i| std::cmp::Reverse(created[*i]));
+
+    let mut keep: HashSet<usize> = order.first().copied().into_iter().collect();
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+
+    for i in order {
+        let date = created[i].date();
+        if days.len() < keep_daily as usize && days.insert(date) {
+            keep.insert(i);
+        }
+        let week = date.iso_week();
+        if weeks.len() < keep_weekly as usize && weeks.insert((week.year(), week.week())) {
+            keep.insert(i);
+        }
+    }
+
+    keep
+}
+
+/* Makes a backup when the last one is older than interval, then removes backups that are not retained */
+pub async fn run_scheduled_backup(
+    ctx: &AppContext,
+    schedule: &BackupSchedule,
+) -> Result<Option<BackupResult>, Box<ErrFR>> {
+    let (true, Some(folder), Some(root)) = (
+        schedule.enabled,
+        schedule.folder.as_deref(),
+        ctx.root_path_option().await,
+    ) else {
+        return Ok(None);
+    };
+    let folder = Path::new(folder);
+    let root = Path::new(&root);
+
+    let now = Local::now().naive_local();
+    let backups = read_backups(folder, root)?;
+    if !is_backup_due(backups.first().map(|b| &b.0), &now, schedule.interval_hours) {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(folder).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error creating backups folder")
+                .info(&folder.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+    let output = folder.join(format!(
+        "{}{}.zip",
+        backup_prefix(root),
+        now.format(TIMESTAMP_FORMAT)
+    ));
+    let result = create_backup(ctx, &output, &schedule.options).await?;
+
+    let backups = read_backups(folder, root)?;
+    let created: Vec<NaiveDateTime> = backups.iter().map(|b| b.0).collect();
+    let keep = retained_backups(&created, schedule.keep_daily, schedule.keep_weekly);
+
+    let errors: Vec<ErrFR> = backups
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !keep.contains(i))
+        .filter_map(|(_, (_, path))| {
+            fs::remove_file(path).err().map(|e| {
+                ErrFR::new("Old backup was not removed")
+                    .info(&path.to_string_lossy())
+                    .raw(e)
+            })
+        })
+        .collect();
+
+    match errors.is_empty() {
+        true => Ok(Some(result)),
+        false => Err(Box::new(
+            ErrFR::new("Backup was made, but some old backups were not removed").subs(errors),
+        )),
+    }
+}
+
+/* Runs on app runtime for the whole app lifetime, schedule is re-read on every check */
+pub fn start_backup_scheduler<T: tauri::Runtime>(app: AppHandle<T>) {
+    tauri::async_runtime::spawn(async move {
+        sleep(FIRST_CHECK_DELAY).await;
+
+        loop {
+            let result = match (
+                app.try_state::<CoreStateManager>(),
+                get_backup_schedule_from_storage(&app),
+            ) {
+                (Some(core), Ok(schedule)) => run_scheduled_backup(&core.context, &schedule).await,
+                (None, _) => Ok(None),
+                (_, Err(e)) => Err(e),
+            };
+
+            let next_check = match result {
+                Ok(Some(r)) => {
+                    log::info!("backup: created {} ({} files)", r.path, r.files);
+                    CHECK_INTERVAL
+                }
+                Ok(None) => CHECK_INTERVAL,
+                Err(e) => {
+                    log::error!("backup: scheduled backup failed {:?}", e);
+                    send_err_to_frontend(&app, &e);
+                    RETRY_INTERVAL
+                }
+            };
+
+            sleep(next_check).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::archive::partial_archive_path;
+
+    fn date(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn retention() {
+        let created = vec![
+            date("2026-03-02 09:00"), // Monday
+            date("2026-03-02 21:00"),
+            date("2026-03-01 21:00"), // Sunday, previous week
+            date("2026-02-28 21:00"),
+            date("2026-02-20 21:00"),
+            date("2026-02-10 21:00"),
+        ];
+
+        let mut daily: Vec<usize> = retained_backups(&created, 2, 0).into_iter().collect();
+        daily.sort();
+        assert_eq!(daily, vec![1, 2]);
+
+        let mut weekly: Vec<usize> = retained_backups(&created, 0, 3).into_iter().collect();
+        weekly.sort();
+        assert_eq!(weekly, vec![1, 2, 4]);
+
+        assert_eq!(retained_backups(&created, 0, 0).len(), 1);
+    }
+
+    #[test]
+    fn schedule_and_names() {
+        let now = date("2026-03-02 09:00");
+        assert!(is_backup_due(None, &now, 24));
+        assert!(!is_backup_due(Some(&date("2026-03-01 10:00")), &now, 24));
+        assert!(is_backup_due(Some(&date("2026-03-01 09:00")), &now, 24));
+
+        let prefix = backup_prefix(Path::new("/home/me/Vault"));
+        assert!(prefix.starts_with("Vault_"));
+        assert_ne!(prefix, backup_prefix(Path::new("/home/me/old/Vault")));
+
+        let name = format!("{}2026-03-02_090000.zip", prefix);
+        assert_eq!(parse_backup_name(&name, &prefix), Some(now));
+        let partial = partial_archive_path(Path::new(&name));
+        assert_eq!(parse_backup_name(&partial.to_string_lossy(), &prefix), None);
+        assert_eq!(
+            parse_backup_name("Other_2026-03-02_090000.zip", &prefix),
+            None
+        );
+        assert_eq!(
+            parse_backup_name(&format!("{}notes.zip", prefix), &prefix),
+            None
+        );
+    }
+}
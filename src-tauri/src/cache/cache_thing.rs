@@ -2,10 +2,14 @@ use std::path::Path;
 use walkdir::WalkDir;
 
 use crate::core::core_state::AppContext;
-use crate::files::read_save::{read_file_by_path, FileReadMode, RecordReadResult};
+use crate::files::read_save::{
+    read_file_by_path, read_file_from_bytes, read_file_modified_time, FileReadMode,
+    RecordReadResult,
+};
+use crate::files::unmanaged::read_unmanaged_record;
 use crate::utils::errorhandling::ErrFR;
 
-use super::query::RecordFromDb;
+use super::query::{RecordFromDb, UnmanagedRecord};
 
 pub async fn insert_file_into_cache_db(
     ctx: &AppContext,
@@ -21,15 +25,69 @@ pub async fn insert_file_into_cache_db(
     })?;
 
     sqlx::query(
-        "INSERT INTO files (path, modified, attributes) VALUES (?1, ?2, ?3) ON CONFLICT(path) DO UPDATE SET modified=excluded.modified, attributes=excluded.attributes",
+        "INSERT INTO files (path, modified, hash, attributes) VALUES (?1, ?2, ?3, ?4) ON CONFLICT(path) DO UPDATE SET modified=excluded.modified, hash=excluded.hash, attributes=excluded.attributes",
     )
     .bind(path.to_string())
     .bind(file.record.modified)
+    .bind(&file.record.hash)
     .bind(&attrs)
     .execute(&ctx.database_conn.get_conn().await)
     .await
     .map_err(|e| ErrFR::new("Error when inserting file").raw(e))?;
 
+    // File could be unmanaged before schema appeared
+    sqlx::query("DELETE FROM unmanaged_files WHERE path=?1")
+        .bind(path.to_string())
+        .execute(&ctx.database_conn.get_conn().await)
+        .await
+        .map_err(|e| ErrFR::new("Error when removing unmanaged file").raw(e))?;
+
+    Ok(())
+}
+
+pub async fn cache_unmanaged_file(
+    ctx: &AppContext,
+    path_absolute: &Path,
+) -> Result<UnmanagedRecord, Box<ErrFR>> {
+    let path_relative = ctx.absolute_path_to_relative(path_absolute).await?;
+    let record = read_unmanaged_record(path_absolute, &path_relative)?.record;
+
+    let keys = serde_json::to_string(&record.keys).map_err(|e| {
+        ErrFR::new("Error when serializing frontmatter keys. This should never happen.").raw(e)
+    })?;
+
+    sqlx::query(
+        "INSERT INTO unmanaged_files (path, modified, hash, keys) VALUES (?1, ?2, ?3, ?4) ON CONFLICT(path) DO UPDATE SET modified=excluded.modified, hash=excluded.hash, keys=excluded.keys",
+    )
+    .bind(&record.path)
+    .bind(record.modified)
+    .bind(&record.hash)
+    .bind(&keys)
+    .execute(&ctx.database_conn.get_conn().await)
+    .await
+    .map_err(|e| ErrFR::new("Error when inserting unmanaged file").raw(e))?;
+
+    // File could be managed before schema was removed
+    sqlx::query("DELETE FROM files WHERE path=?1")
+        .bind(&record.path)
+        .execute(&ctx.database_conn.get_conn().await)
+        .await
+        .map_err(|e| ErrFR::new("Error when removing file from cache").raw(e))?;
+
+    Ok(record)
+}
+
+pub async fn remove_unmanaged_files_from_cache(
+    ctx: &AppContext,
+    path_relative: &Path,
+) -> Result<(), Box<ErrFR>> {
+    sqlx::query("DELETE FROM unmanaged_files WHERE path = ?1 OR path LIKE concat(?1, ?2, '%')")
+        .bind(path_relative.to_string_lossy().to_string())
+        .bind(std::path::MAIN_SEPARATOR.to_string())
+        .execute(&ctx.database_conn.get_conn().await)
+        .await
+        .map_err(|e| ErrFR::new("Error when removing unmanaged file from cache").raw(e))?;
+
     Ok(())
 }
 
@@ -48,6 +106,21 @@ pub async fn cache_file(
     }
 }
 
+/* Same as cache_file, for content that was already read from disk */
+pub async fn cache_file_from_bytes(
+    ctx: &AppContext,
+    path_absolute: &Path,
+    bytes: &[u8],
+) -> Result<RecordFromDb, Box<ErrFR>> {
+    let path_relative = ctx.absolute_path_to_relative(path_absolute).await?;
+    let modified = read_file_modified_time(path_absolute)?;
+
+    let file =
+        read_file_from_bytes(ctx, &path_relative, bytes, modified, FileReadMode::OnlyMeta).await?;
+    insert_file_into_cache_db(ctx, &file).await?;
+    Ok(file.record)
+}
+
 pub async fn remove_file_from_cache(
     ctx: &AppContext,
     path_absolute: &Path,
@@ -63,6 +136,22 @@ pub async fn remove_file_from_cache(
     Ok(())
 }
 
+async fn is_managed(ctx: &AppContext, path_absolute: &Path) -> Result<bool, Box<ErrFR>> {
+    let path_relative = ctx.absolute_path_to_relative(path_absolute).await?;
+    Ok(ctx.schemas_cache.get_schema(&path_relative).await.is_some())
+}
+
+// Things like .trash or .obsidian are not part of the vault
+pub async fn is_in_hidden_folder(
+    ctx: &AppContext,
+    path_absolute: &Path,
+) -> Result<bool, Box<ErrFR>> {
+    let path_relative = ctx.absolute_path_to_relative(path_absolute).await?;
+    Ok(path_relative
+        .parent()
+        .is_some_and(|p| p.iter().any(|c| c.to_string_lossy().starts_with('.'))))
+}
+
 pub async fn cache_files_folders_schemas(
     ctx: &AppContext,
     path_absolute: &Path,
@@ -76,7 +165,13 @@ pub async fn cache_files_folders_schemas(
         if entry.file_type().is_file() {
             if let Some(extension) = entry.path().extension() {
                 if extension == "md" {
-                    match cache_file(ctx, entry.path()).await {
+                    let res = match is_managed(ctx, entry.path()).await? {
+                        true => cache_file(ctx, entry.path()).await.map(|_| ()),
+                        false if is_in_hidden_folder(ctx, entry.path()).await? => Ok(()),
+                        false => cache_unmanaged_file(ctx, entry.path()).await.map(|_| ()),
+                    };
+
+                    match res {
                         Ok(_) => (),
                         Err(e) => {
                             err = err.sub(e.info(&entry.file_name().to_string_lossy()));
@@ -83,13 +83,22 @@ impl DatabaseConnection {
             .execute(&conn)
             .await?;
 
-        sqlx::query("CREATE TABLE files (path TEXT PRIMARY KEY, modified INTEGER, attributes TEXT CHECK(json_valid(attributes)))")
+        sqlx::query("DROP TABLE IF EXISTS unmanaged_files;")
+            .execute(&conn)
+            .await?;
+
+        sqlx::query("CREATE TABLE files (path TEXT PRIMARY KEY, modified INTEGER, hash TEXT, attributes TEXT CHECK(json_valid(attributes)))")
         .execute(&conn)
         .await?;
 
         sqlx::query("CREATE TABLE folders (path TEXT PRIMARY KEY, name TEXT);")
             .execute(&conn)
             .await?;
+
+        // Markdown files in folders without schema, only frontmatter keys are stored
+        sqlx::query("CREATE TABLE unmanaged_files (path TEXT PRIMARY KEY, modified INTEGER, hash TEXT, keys TEXT CHECK(json_valid(keys)))")
+        .execute(&conn)
+        .await?;
         Ok(())
     }
 
@@ -98,6 +107,9 @@ impl DatabaseConnection {
         log::info!("database_conn: wiping db");
         sqlx::query("DELETE FROM files;").execute(&conn).await?;
         sqlx::query("DELETE FROM folders;").execute(&conn).await?;
+        sqlx::query("DELETE FROM unmanaged_files;")
+            .execute(&conn)
+            .await?;
         Ok(())
     }
 }
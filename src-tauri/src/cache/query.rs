@@ -16,18 +16,20 @@ pub struct RecordFromDb {
     pub path: Option<String>,
     #[ts(type = "number")]
     pub modified: Option<i64>, // UNIX milliseconds
+    /* sha256 of file content, used for conflict checks when present */
+    #[ts(optional)]
+    pub hash: Option<String>,
     pub markdown: Option<String>,
 
     pub attrs: HashMap<String, AttrValue>,
 }
 
-#[cfg_attr(not(test), allow(dead_code))]
 pub async fn get_files_abstract(
     ctx: &AppContext,
     where_clause: String,
 ) -> Result<Vec<RecordFromDb>, Box<ErrFR>> {
     let q = format!(
-        "SELECT path, modified, attributes FROM files {}",
+        "SELECT path, modified, hash, attributes FROM files {}",
         where_clause
     );
 
@@ -54,6 +56,7 @@ fn records_from_rows(rows: &[SqliteRow]) -> Vec<RecordFromDb> {
             Some(RecordFromDb {
                 path,
                 modified: r.get("modified"),
+                hash: r.get("hash"),
                 attrs: attrs.unwrap(),
                 markdown: None,
             })
@@ -75,7 +78,7 @@ pub async fn get_files_by_path(
     let schema = ctx.schemas_cache.get_schema_safe(path).await?;
     let path = path.to_string_lossy().to_string();
     let records = sqlx::query(
-        "SELECT path, modified, attributes FROM files WHERE files.path LIKE concat(?1, '%') ORDER BY path",
+        "SELECT path, modified, hash, attributes FROM files WHERE files.path LIKE concat(?1, '%') ORDER BY path",
     )
     .bind(path)
     .fetch_all(&ctx.database_conn.get_conn().await)
@@ -86,6 +89,58 @@ pub async fn get_files_by_path(
     Ok(RecordListGetResult { schema, records })
 }
 
+/* Markdown file in folder without schema */
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct UnmanagedRecord {
+    /* Relative path to root path */
+    pub path: String,
+    #[ts(type = "number")]
+    pub modified: Option<i64>, // UNIX milliseconds
+    pub hash: Option<String>,
+    /* Top level frontmatter keys in order they appear in file */
+    pub keys: Vec<String>,
+}
+
+pub async fn get_unmanaged_files_by_path(
+    ctx: &AppContext,
+    path: &Path,
+) -> Result<Vec<UnmanagedRecord>, Box<ErrFR>> {
+    let path = path.to_string_lossy().to_string();
+    // Separator keeps sibling folders with the same prefix out, "notes-old" for "notes"
+    let rows = sqlx::query(
+        "SELECT path, modified, hash, keys FROM unmanaged_files WHERE ?1 = '' OR path = ?1 OR path LIKE concat(?1, ?2, '%') ORDER BY path",
+    )
+    .bind(path)
+    .bind(std::path::MAIN_SEPARATOR.to_string())
+    .fetch_all(&ctx.database_conn.get_conn().await)
+    .await
+    .map_err(|e| ErrFR::new("Error when getting unmanaged files").raw(e))?;
+
+    Ok(rows
+        .iter()
+        .map(|r| UnmanagedRecord {
+            path: r.get("path"),
+            modified: r.get("modified"),
+            hash: r.get("hash"),
+            keys: serde_json::from_str(r.get("keys")).unwrap_or_default(),
+        })
+        .collect())
+}
+
+pub async fn get_cached_file_hash(
+    ctx: &AppContext,
+    path_relative: &Path,
+) -> Result<Option<String>, Box<ErrFR>> {
+    let res = sqlx::query("SELECT hash FROM files WHERE path = ?1")
+        .bind(path_relative.to_string_lossy().to_string())
+        .fetch_optional(&ctx.database_conn.get_conn().await)
+        .await
+        .map_err(|e| ErrFR::new("Error when getting file hash").raw(e))?;
+
+    Ok(res.and_then(|r| r.get("hash")))
+}
+
 pub async fn get_all_tags(ctx: &AppContext) -> Result<Vec<String>, sqlx::Error> {
     let res = sqlx::query("SELECT DISTINCT value FROM tags")
         .fetch_all(&ctx.database_conn.get_conn().await)
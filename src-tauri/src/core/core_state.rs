@@ -8,7 +8,11 @@ use tauri::AppHandle;
 
 use crate::{
     cache::{cache_thing::cache_files_folders_schemas, dbconn::DatabaseConnection},
-    core::root_storage::{get_root_path_from_storage, set_root_path_to_storage},
+    core::{
+        journal::OperationJournal,
+        root_storage::{get_root_path_from_storage, set_root_path_to_storage},
+    },
+    files::thumbnails::ThumbnailCache,
     schema::schema_cache::SchemasInMemoryCache,
     utils::errorhandling::{send_err_to_frontend, ErrFR},
     watcher::global_watcher::GlobalWatcher,
@@ -29,6 +33,8 @@ pub struct AppContext {
     pub root_path_cached: RwLock<Option<String>>,
     pub schemas_cache: SchemasInMemoryCache,
     pub database_conn: DatabaseConnection,
+    pub journal: OperationJournal,
+    pub thumbnails: ThumbnailCache,
 }
 
 #[derive(Debug)]
@@ -57,6 +63,15 @@ impl AppContext {
             .ok_or(Box::new(ErrFR::new("Root path is not set")))
     }
 
+    /* True while initialize_on_root_path fills cache, records in db are incomplete until it finishes */
+    pub async fn is_indexing(&self) -> bool {
+        let root = self.root_path_option().await;
+        match self.root_path_cached.try_read() {
+            Ok(cached) => *cached != root,
+            Err(_) => true,
+        }
+    }
+
     pub async fn cached_root_path(&self) -> Option<String> {
         self.root_path_cached
             .read()
@@ -93,6 +108,8 @@ impl CoreStateManager {
             root_path_cached: RwLock::new(None),
             schemas_cache: SchemasInMemoryCache::new(),
             database_conn: DatabaseConnection::new(),
+            journal: OperationJournal::new(),
+            thumbnails: ThumbnailCache::new(),
         };
 
         Self {
@@ -0,0 +1,551 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use ts_rs::TS;
+
+use crate::core::core_state::AppContext;
+use crate::files::trash::{delete_many_to_trash, delete_to_trash, restore_from_trash};
+use crate::utils::errorhandling::ErrFR;
+
+const JOURNAL_FILE_NAME: &str = "asom_journal.json";
+const MAX_JOURNAL_ENTRIES: usize = 50;
+// Saves of the same file closer than this are merged into one entry
+const MERGE_WINDOW_MS: i64 = 60_000;
+
+/*
+    Single reversible step. Executing an action returns the action that reverts it,
+    this is how undo entries become redo entries and back.
+    All paths are relative to root path.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", content = "value")]
+pub enum JournalAction {
+    // None content means file should not exist
+    WriteFile {
+        path: String,
+        content: Option<String>,
+    },
+    MoveToTrash {
+        path: String,
+    },
+    RestoreFromTrash {
+        id: String,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    CreateFolder {
+        path: String,
+    },
+    // Only removes empty folders, so we never lose something that was added after
+    RemoveFolder {
+        path: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct JournalEntry {
+    title: String,
+    timestamp: i64,
+    actions: Vec<JournalAction>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct JournalData {
+    root: Option<String>,
+    undo: Vec<JournalEntry>,
+    redo: Vec<JournalEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct JournalEntryInfo {
+    pub title: String,
+    #[ts(type = "number")]
+    pub timestamp: i64, // UNIX milliseconds
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct JournalStatus {
+    /* Most recent first */
+    pub undo: Vec<JournalEntryInfo>,
+    pub redo: Vec<JournalEntryInfo>,
+}
+
+#[derive(Debug)]
+pub struct OperationJournal {
+    data: Mutex<JournalData>,
+    // Journal is kept in memory only until folder is set (tests)
+    folder: Mutex<Option<PathBuf>>,
+}
+
+impl JournalData {
+    fn status(&self) -> JournalStatus {
+        let info = |e: &JournalEntry| JournalEntryInfo {
+            title: e.title.clone(),
+            timestamp: e.timestamp,
+        };
+        JournalStatus {
+            undo: self.undo.iter().rev().map(info).collect(),
+            redo: self.redo.iter().rev().map(info).collect(),
+        }
+    }
+
+    // Journal belongs to a single root path, switching root starts from scratch
+    fn ensure_root(&mut self, root: &str) {
+        if self.root.as_deref() != Some(root) {
+            self.root = Some(root.to_string());
+            self.undo.clear();
+            self.redo.clear();
+        }
+    }
+}
+
+fn push_bounded(stack: &mut Vec<JournalEntry>, entry: JournalEntry) {
+    stack.push(entry);
+    if stack.len() > MAX_JOURNAL_ENTRIES {
+        stack.remove(0);
+    }
+}
+
+fn read_optional(path_absolute: &Path) -> Result<Option<String>, Box<ErrFR>> {
+    match path_absolute.exists() {
+        false => Ok(None),
+        true => fs::read_to_string(path_absolute).map(Some).map_err(|e| {
+            Box::new(
+                ErrFR::new("Error reading file")
+                    .info(&path_absolute.to_string_lossy())
+                    .raw(e),
+            )
+        }),
+    }
+}
+
+/* Current content of file, to be used as WriteFile action before changing it */
+pub async fn snapshot_file(ctx: &AppContext, path_relative: &Path) -> JournalAction {
+    let content = match ctx.relative_path_to_absolute(path_relative).await {
+        Ok(p) => read_optional(&p).ok().flatten(),
+        Err(_) => None,
+    };
+    JournalAction::WriteFile {
+        path: path_relative.to_string_lossy().to_string(),
+        content,
+    }
+}
+
+async fn execute_action(
+    ctx: &AppContext,
+    action: JournalAction,
+) -> Result<Option<JournalAction>, Box<ErrFR>> {
+    let fs_err = |title: &str, path: &Path, e: std::io::Error| {
+        Box::new(ErrFR::new(title).info(&path.to_string_lossy()).raw(e))
+    };
+
+    match action {
+        JournalAction::WriteFile { path, content } => {
+            let path_absolute = ctx.relative_path_to_absolute(Path::new(&path)).await?;
+            let current = read_optional(&path_absolute)?;
+
+            match &content {
+                Some(c) => fs::write(&path_absolute, c)
+                    .map_err(|e| fs_err("Error writing to disk", &path_absolute, e))?,
+                None if current.is_some() => fs::remove_file(&path_absolute)
+                    .map_err(|e| fs_err("Error removing file", &path_absolute, e))?,
+                None => (),
+            }
+
+            Ok(Some(JournalAction::WriteFile {
+                path,
+                content: current,
+            }))
+        }
+        JournalAction::MoveToTrash { path } => Ok(delete_to_trash(ctx, Path::new(&path))
+            .await?
+            .map(|r| JournalAction::RestoreFromTrash { id: r.id })),
+        JournalAction::RestoreFromTrash { id } => {
+            let path = restore_from_trash(ctx, &id).await?;
+            Ok(Some(JournalAction::MoveToTrash {
+                path: path.to_string_lossy().to_string(),
+            }))
+        }
+        JournalAction::Rename { from, to } => {
+            let from_absolute = ctx.relative_path_to_absolute(Path::new(&from)).await?;
+            let to_absolute = ctx.relative_path_to_absolute(Path::new(&to)).await?;
+
+            if to_absolute.exists() {
+                return Err(Box::new(
+                    ErrFR::new("Unable to rename, path is already taken").info(&to),
+                ));
+            }
+
+            fs::rename(&from_absolute, &to_absolute)
+                .map_err(|e| fs_err("Error renaming", &from_absolute, e))?;
+
+            Ok(Some(JournalAction::Rename { from: to, to: from }))
+        }
+        JournalAction::CreateFolder { path } => {
+            let path_absolute = ctx.relative_path_to_absolute(Path::new(&path)).await?;
+            fs::create_dir(&path_absolute)
+                .map_err(|e| fs_err("Error creating folder", &path_absolute, e))?;
+            Ok(Some(JournalAction::RemoveFolder { path }))
+        }
+        JournalAction::RemoveFolder { path } => {
+            let path_absolute = ctx.relative_path_to_absolute(Path::new(&path)).await?;
+            fs::remove_dir(&path_absolute).map_err(|e| {
+                fs_err(
+                    "Unable to remove folder, it might be not empty",
+                    &path_absolute,
+                    e,
+                )
+            })?;
+            Ok(Some(JournalAction::CreateFolder { path }))
+        }
+    }
+}
+
+/*
+    Consecutive MoveToTrash actions (import undo) are trashed as one batch,
+    so trash is listed once and not once per file.
+    On failure returns actions that were not executed, in the order they were recorded.
+*/
+async fn trash_batch(
+    ctx: &AppContext,
+    paths: &mut Vec<String>,
+    reversed: &mut Vec<JournalAction>,
+) -> Result<(), (Vec<JournalAction>, Box<ErrFR>)> {
+    let paths_os: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    let results = delete_many_to_trash(ctx, &paths_os).await;
+
+    let mut failed: Vec<JournalAction> = vec![];
+    let mut err = ErrFR::new("Some files were not moved to trash");
+
+    for (path, result) in paths.drain(..).zip(results) {
+        match result {
+            Ok(Some(r)) => reversed.push(JournalAction::RestoreFromTrash { id: r.id }),
+            Ok(None) => (),
+            Err(e) => {
+                err = err.sub(e.info(&path));
+                failed.push(JournalAction::MoveToTrash { path });
+            }
+        }
+    }
+
+    // Paths were collected last to first
+    failed.reverse();
+
+    match failed.is_empty() {
+        true => Ok(()),
+        false => Err((failed, Box::new(err))),
+    }
+}
+
+struct Execution {
+    /* Reversals of executed actions, in the order they should be executed */
+    reversed: Vec<JournalAction>,
+    /* Actions that were not executed because of error, in the order they were recorded */
+    remaining: Vec<JournalAction>,
+    error: Option<Box<ErrFR>>,
+}
+
+/*
+    Runs actions last to first and stops on the first error.
+*/
+async fn execute_entry(ctx: &AppContext, actions: Vec<JournalAction>) -> Execution {
+    let mut pending = actions;
+    let mut reversed: Vec<JournalAction> = vec![];
+    let mut to_trash: Vec<String> = vec![];
+
+    loop {
+        let action = pending.pop();
+
+        if let Some(JournalAction::MoveToTrash { path }) = &action {
+            to_trash.push(path.clone());
+            continue;
+        }
+
+        if let Err((failed, e)) = trash_batch(ctx, &mut to_trash, &mut reversed).await {
+            pending.extend(action);
+            pending.extend(failed);
+            return Execution {
+                reversed,
+                remaining: pending,
+                error: Some(e),
+            };
+        }
+
+        let Some(action) = action else {
+            break;
+        };
+
+        match execute_action(ctx, action.clone()).await {
+            Ok(Some(v)) => reversed.push(v),
+            Ok(None) => (),
+            Err(e) => {
+                pending.push(action);
+                return Execution {
+                    reversed,
+                    remaining: pending,
+                    error: Some(e),
+                };
+            }
+        }
+    }
+
+    Execution {
+        reversed,
+        remaining: vec![],
+        error: None,
+    }
+}
+
+/*
+    Autosave records an entry for every save of the same file.
+    Saves that follow each other are kept as one entry with the oldest snapshot, so undo goes to the state before editing.
+*/
+fn merges_with_last(
+    stack: &[JournalEntry],
+    title: &str,
+    actions: &[JournalAction],
+    now: i64,
+) -> bool {
+    let Some(last) = stack.last() else {
+        return false;
+    };
+
+    match (last.actions.as_slice(), actions) {
+        (
+            [JournalAction::WriteFile {
+                path: last_path, ..
+            }],
+            [JournalAction::WriteFile { path, .. }],
+        ) => last.title == title && last_path == path && now - last.timestamp <= MERGE_WINDOW_MS,
+        _ => false,
+    }
+}
+
+impl OperationJournal {
+    pub fn new() -> Self {
+        Self {
+            data: Mutex::new(JournalData::default()),
+            folder: Mutex::new(None),
+        }
+    }
+
+    /* Loads journal saved by previous app run */
+    pub async fn init(&self, folder: PathBuf) {
+        if let Err(e) = fs::create_dir_all(&folder) {
+            log::warn!("journal: unable to create folder {}", e);
+        }
+
+        let path = folder.join(JOURNAL_FILE_NAME);
+
+        if let Ok(content) = fs::read_to_string(&path) {
+            match serde_json::from_str::<JournalData>(&content) {
+                Ok(v) => *self.data.lock().await = v,
+                Err(e) => log::warn!("journal: unable to parse saved journal {}", e),
+            }
+        }
+
+        *self.folder.lock().await = Some(folder);
+    }
+
+    async fn persist(&self, data: &JournalData) {
+        let folder = self.folder.lock().await;
+        let Some(folder) = folder.as_ref() else {
+            return;
+        };
+
+        // Journal is a convenience, failing to save it should never fail the operation itself
+        match serde_json::to_string(data) {
+            Ok(s) => {
+                if let Err(e) = fs::write(folder.join(JOURNAL_FILE_NAME), s) {
+                    log::warn!("journal: unable to save {}", e);
+                }
+            }
+            Err(e) => log::warn!("journal: unable to serialize {}", e),
+        }
+    }
+
+    /* `undo_actions` are executed last to first when user asks for undo */
+    pub async fn record(&self, ctx: &AppContext, title: &str, undo_actions: Vec<JournalAction>) {
+        if undo_actions.is_empty() {
+            return;
+        }
+
+        let Ok(root) = ctx.root_path_safe().await else {
+            return;
+        };
+
+        let mut data = self.data.lock().await;
+        data.ensure_root(&root);
+
+        let now = chrono::Utc::now().timestamp_millis();
+
+        if data.redo.is_empty() && merges_with_last(&data.undo, title, &undo_actions, now) {
+            // Saved entry stays the same, only timestamp moves, so there is no need to rewrite journal file
+            if let Some(last) = data.undo.last_mut() {
+                last.timestamp = now;
+            }
+            return;
+        }
+
+        push_bounded(
+            &mut data.undo,
+            JournalEntry {
+                title: title.to_string(),
+                timestamp: now,
+                actions: undo_actions,
+            },
+        );
+        data.redo.clear();
+        self.persist(&data).await;
+    }
+
+    pub async fn status(&self, ctx: &AppContext) -> Result<JournalStatus, Box<ErrFR>> {
+        let root = ctx.root_path_safe().await?;
+        let mut data = self.data.lock().await;
+        data.ensure_root(&root);
+        Ok(data.status())
+    }
+
+    pub async fn undo(&self, ctx: &AppContext) -> Result<JournalStatus, Box<ErrFR>> {
+        self.step(ctx, true).await
+    }
+
+    pub async fn redo(&self, ctx: &AppContext) -> Result<JournalStatus, Box<ErrFR>> {
+        self.step(ctx, false).await
+    }
+
+    async fn step(&self, ctx: &AppContext, is_undo: bool) -> Result<JournalStatus, Box<ErrFR>> {
+        let root = ctx.root_path_safe().await?;
+        let mut data = self.data.lock().await;
+        data.ensure_root(&root);
+
+        let entry = match is_undo {
+            true => data.undo.pop(),
+            false => data.redo.pop(),
+        };
+
+        let Some(entry) = entry else {
+            return Ok(data.status());
+        };
+
+        let Execution {
+            reversed,
+            remaining,
+            error,
+        } = execute_entry(ctx, entry.actions).await;
+
+        // After partial failure both parts stay reversible: what was done goes to the other stack, the rest goes back
+        if error.is_none() || !reversed.is_empty() {
+            let done = JournalEntry {
+                title: entry.title.clone(),
+                timestamp: chrono::Utc::now().timestamp_millis(),
+                actions: reversed,
+            };
+            match is_undo {
+                true => push_bounded(&mut data.redo, done),
+                false => push_bounded(&mut data.undo, done),
+            }
+        }
+        if !remaining.is_empty() {
+            let rest = JournalEntry {
+                title: entry.title.clone(),
+                timestamp: entry.timestamp,
+                actions: remaining,
+            };
+            match is_undo {
+                true => push_bounded(&mut data.undo, rest),
+                false => push_bounded(&mut data.redo, rest),
+            }
+        }
+
+        self.persist(&data).await;
+
+        match error {
+            Some(e) => Err(Box::new(
+                ErrFR::new("Operation was reverted only partially")
+                    .info(&entry.title)
+                    .sub(*e),
+            )),
+            None => Ok(data.status()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str) -> JournalEntry {
+        JournalEntry {
+            title: title.to_string(),
+            timestamp: 0,
+            actions: vec![],
+        }
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut stack = vec![];
+        for i in 0..MAX_JOURNAL_ENTRIES + 5 {
+            push_bounded(&mut stack, entry(&i.to_string()));
+        }
+        assert_eq!(stack.len(), MAX_JOURNAL_ENTRIES);
+        assert_eq!(stack[0].title, "5");
+    }
+
+    #[test]
+    fn root_change_resets_history() {
+        let mut data = JournalData::default();
+        data.ensure_root("a");
+        data.undo.push(entry("save"));
+        data.ensure_root("a");
+        assert_eq!(data.status().undo.len(), 1);
+        data.ensure_root("b");
+        assert_eq!(data.status().undo.len(), 0);
+    }
+
+    #[test]
+    fn consecutive_saves_merge() {
+        let write = |path: &str| {
+            vec![JournalAction::WriteFile {
+                path: path.to_string(),
+                content: None,
+            }]
+        };
+        let mut last = entry("Edit record");
+        last.actions = write("a.md");
+        let stack = vec![last];
+
+        assert!(merges_with_last(
+            &stack,
+            "Edit record",
+            &write("a.md"),
+            1000
+        ));
+        assert!(!merges_with_last(
+            &stack,
+            "Edit record",
+            &write("b.md"),
+            1000
+        ));
+        assert!(!merges_with_last(
+            &stack,
+            "Edit schema",
+            &write("a.md"),
+            1000
+        ));
+        assert!(!merges_with_last(
+            &stack,
+            "Edit record",
+            &write("a.md"),
+            MERGE_WINDOW_MS + 1
+        ));
+        assert!(!merges_with_last(&[], "Edit record", &write("a.md"), 1000));
+    }
+}
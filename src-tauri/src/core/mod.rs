@@ -1,2 +1,3 @@
 pub mod core_state;
+pub mod journal;
 pub mod root_storage;
@@ -8,7 +8,7 @@ use tauri::{AppHandle, Emitter, Manager};
 use ts_rs::TS;
 
 use crate::{
-    cache::query::RecordFromDb,
+    cache::query::{RecordFromDb, UnmanagedRecord},
     core::core_state::CoreStateManager,
     schema::types::{Schema, SchemaLocation},
     utils::errorhandling::ErrFR,
@@ -41,8 +41,14 @@ pub enum IPCEmitEvent {
     FileRemove(FileEventDataRemoved),
     FileAdd(FileEventDataExisting),
     FileUpdate(FileEventDataExisting),
+    // Sent instead of FileUpdate for changes made by batch operations
+    FileUpdateBatch(Vec<FileEventDataExisting>),
     FolderRemove(FolderEventData),
     FolderAdd(FolderEventData),
+    // Markdown files in folders without schema
+    UnmanagedFileAdd(UnmanagedRecord),
+    UnmanagedFileUpdate(UnmanagedRecord),
+    UnmanagedFileRemove(String),
     ErrorHappened(ErrFR),
     SchemasUpdated(HashMap<String, Schema>),
     EventOverflow(u32),
@@ -53,8 +59,12 @@ pub fn get_event_name(event: &IPCEmitEvent) -> String {
         IPCEmitEvent::FileRemove(_) => "FileRemove".to_string(),
         IPCEmitEvent::FileAdd(_) => "FileAdd".to_string(),
         IPCEmitEvent::FileUpdate(_) => "FileUpdate".to_string(),
+        IPCEmitEvent::FileUpdateBatch(_) => "FileUpdateBatch".to_string(),
         IPCEmitEvent::FolderRemove(_) => "FolderRemove".to_string(),
         IPCEmitEvent::FolderAdd(_) => "FolderAdd".to_string(),
+        IPCEmitEvent::UnmanagedFileAdd(_) => "UnmanagedFileAdd".to_string(),
+        IPCEmitEvent::UnmanagedFileUpdate(_) => "UnmanagedFileUpdate".to_string(),
+        IPCEmitEvent::UnmanagedFileRemove(_) => "UnmanagedFileRemove".to_string(),
         IPCEmitEvent::ErrorHappened(_) => "ErrorHappened".to_string(),
         IPCEmitEvent::SchemasUpdated(_) => "SchemasUpdated".to_string(),
         IPCEmitEvent::EventOverflow(_) => "EventOverflow".to_string(),
@@ -67,8 +77,14 @@ impl IPCEmitEvent {
             IPCEmitEvent::FileRemove(data) => format!("FileRemove: {}", data.path),
             IPCEmitEvent::FileAdd(data) => format!("FileAdd: {}", data.path),
             IPCEmitEvent::FileUpdate(data) => format!("FileUpdate: {}", data.path),
+            IPCEmitEvent::FileUpdateBatch(data) => format!("FileUpdateBatch: {}", data.len()),
             IPCEmitEvent::FolderRemove(data) => format!("FolderRemove: {}", data.path),
             IPCEmitEvent::FolderAdd(data) => format!("FolderAdd: {}", data.path),
+            IPCEmitEvent::UnmanagedFileAdd(data) => format!("UnmanagedFileAdd: {}", data.path),
+            IPCEmitEvent::UnmanagedFileUpdate(data) => {
+                format!("UnmanagedFileUpdate: {}", data.path)
+            }
+            IPCEmitEvent::UnmanagedFileRemove(path) => format!("UnmanagedFileRemove: {}", path),
             IPCEmitEvent::ErrorHappened(data) => format!("ErrorHappened: {}", data.title),
             IPCEmitEvent::SchemasUpdated(data) => {
                 format!("SchemasUpdated: new count {}", data.len())
@@ -0,0 +1,512 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::query::RecordFromDb;
+use crate::core::core_state::AppContext;
+use crate::core::journal::{snapshot_file, JournalAction};
+use crate::export::read_collection;
+use crate::export::record_name;
+use crate::schema::schema_cache::INTERNAL_FOLDER_NAME;
+use crate::schema::types::{
+    find_schema_item, is_date, is_number, is_text, is_text_or_collection, AttrValue, Schema,
+};
+use crate::utils::errorhandling::ErrFR;
+use crate::utils::helpers::split_names;
+
+/* Lives next to schema.yaml, so every schema has its own mapping */
+const CITATION_FILE_NAME: &str = "citation.yaml";
+const TITLE_STOP_WORDS: [&str; 9] = ["a", "an", "the", "of", "on", "in", "and", "to", "for"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, TS)]
+#[ts(export)]
+pub struct CitationMapping {
+    /* Names of schema items, None when schema has no such attribute */
+    pub author: Option<String>,
+    /* File name is used when there is no title */
+    pub title: Option<String>,
+    pub year: Option<String>,
+    pub publisher: Option<String>,
+    pub isbn: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, TS)]
+#[ts(export)]
+pub enum CitationFormat {
+    Bibtex,
+    CslJson,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct CitationExportResult {
+    pub entries: u32,
+    /* Records that failed to read, they are not in the file */
+    pub errors: Option<ErrFR>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Citation {
+    pub key: String,
+    pub authors: Vec<String>,
+    pub title: String,
+    pub year: Option<i32>,
+    pub publisher: Option<String>,
+    pub isbn: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CslName {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    given: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    literal: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CslDate {
+    #[serde(rename = "date-parts")]
+    date_parts: Vec<Vec<i32>>,
+}
+
+#[derive(Serialize)]
+struct CslItem {
+    id: String,
+    #[serde(rename = "type")]
+    item_type: &'static str,
+    title: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    author: Vec<CslName>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issued: Option<CslDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    publisher: Option<String>,
+    #[serde(rename = "ISBN", skip_serializing_if = "Option::is_none")]
+    isbn: Option<String>,
+}
+
+/* Guess based on attribute names and types, user can adjust and save it */
+pub fn guess_citation_mapping(schema: &Schema) -> CitationMapping {
+    CitationMapping {
+        author: find_schema_item(
+            schema,
+            &["authors", "author", "writer", "creator"],
+            is_text_or_collection,
+        ),
+        title: find_schema_item(schema, &["title", "name"], is_text),
+        year: find_schema_item(schema, &["year", "published", "publicationYear"], is_number).or(
+            find_schema_item(
+                schema,
+                &["published", "pubdate", "publicationDate"],
+                is_date,
+            ),
+        ),
+        publisher: find_schema_item(schema, &["publisher"], is_text),
+        isbn: find_schema_item(schema, &["isbn", "ISBN", "ISBN13", "isbn13"], is_text),
+    }
+}
+
+async fn mapping_path(ctx: &AppContext, folder_relative: &Path) -> Result<PathBuf, Box<ErrFR>> {
+    let schema = ctx.schemas_cache.get_schema_safe(folder_relative).await?;
+    Ok(schema
+        .location
+        .schema_owner_folder
+        .join(INTERNAL_FOLDER_NAME)
+        .join(CITATION_FILE_NAME))
+}
+
+/* Saved mapping of the folder's schema, or a guess when it was never saved */
+pub async fn load_citation_mapping(
+    ctx: &AppContext,
+    folder_relative: &Path,
+) -> Result<CitationMapping, Box<ErrFR>> {
+    let path = ctx
+        .relative_path_to_absolute(&mapping_path(ctx, folder_relative).await?)
+        .await?;
+
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_yml::from_str(&content).map_err(|e| {
+            Box::new(
+                ErrFR::new("Citation mapping can't be read")
+                    .info(&path.to_string_lossy())
+                    .raw(e),
+            )
+        }),
+        Err(_) => {
+            let schema = ctx.schemas_cache.get_schema_safe(folder_relative).await?;
+            Ok(guess_citation_mapping(&schema.schema))
+        }
+    }
+}
+
+pub struct CitationMappingSaveOutput {
+    pub mapping: CitationMapping,
+    pub undo: Vec<JournalAction>,
+}
+
+pub async fn save_citation_mapping(
+    ctx: &AppContext,
+    folder_relative: &Path,
+    mapping: CitationMapping,
+) -> Result<CitationMappingSaveOutput, Box<ErrFR>> {
+    let path_relative = mapping_path(ctx, folder_relative).await?;
+    let path = ctx.relative_path_to_absolute(&path_relative).await?;
+    let undo = vec![snapshot_file(ctx, &path_relative).await];
+
+    let content = serde_yml::to_string(&mapping)
+        .map_err(|e| Box::new(ErrFR::new("Error serializing citation mapping").raw(e)))?;
+    fs::write(&path, content).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error writing to disk")
+                .info(&path.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+
+    Ok(CitationMappingSaveOutput { mapping, undo })
+}
+
+/* Latin letters with diacritics are folded to ASCII, letters of other scripts are kept as is */
+fn key_part(value: &str) -> String {
+    value
+        .to_lowercase()
+        .chars()
+        .flat_map(|c| {
+            let folded = match c {
+                'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' => "a",
+                'ç' | 'č' | 'ć' => "c",
+                'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ě' => "e",
+                'ì' | 'í' | 'î' | 'ï' | 'ī' => "i",
+                'ñ' | 'ń' | 'ň' => "n",
+                'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' => "o",
+                'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' => "u",
+                'ý' | 'ÿ' => "y",
+                'š' | 'ś' => "s",
+                'ž' | 'ź' | 'ż' => "z",
+                'ř' => "r",
+                'ł' => "l",
+                'ß' => "ss",
+                'æ' => "ae",
+                'œ' => "oe",
+                c if c.is_alphanumeric() => return vec![c],
+                _ => "",
+            };
+            folded.chars().collect()
+        })
+        .collect()
+}
+
+/* "Adler, Mortimer J." and "Mortimer J. Adler" are both read as family name Adler */
+fn split_name(name: &str) -> (Option<String>, Option<String>) {
+    let name = name.trim();
+    if let Some((family, given)) = name.split_once(',') {
+        let given = given.trim();
+        return (
+            Some(family.trim().to_string()),
+            (!given.is_empty()).then(|| given.to_string()),
+        );
+    }
+    match name.rsplit_once(' ') {
+        Some((given, family)) => (
+            Some(family.trim().to_string()),
+            Some(given.trim().to_string()),
+        ),
+        None => (Some(name.to_string()).filter(|n| !n.is_empty()), None),
+    }
+}
+
+/* Family name of the first author, year and first meaningful word of title, like adler1942how */
+pub fn citation_key(authors: &[String], year: Option<i32>, title: &str) -> String {
+    let author = authors
+        .first()
+        .and_then(|a| split_name(a).0)
+        .map(|f| key_part(&f))
+        .filter(|f| !f.is_empty())
+        .unwrap_or_else(|| "anon".to_string());
+    let year = year
+        .map(|y| y.to_string())
+        .unwrap_or_else(|| "nd".to_string());
+    let word = title
+        .split(|c: char| !c.is_alphanumeric())
+        .map(key_part)
+        .find(|w| !w.is_empty() && !TITLE_STOP_WORDS.contains(&w.as_str()))
+        .unwrap_or_default();
+
+    format!("{}{}{}", author, year, word)
+}
+
+fn text_value(record: &RecordFromDb, name: Option<&String>) -> Option<String> {
+    match record.attrs.get(name?)? {
+        AttrValue::String(Some(v)) if !v.trim().is_empty() => Some(v.trim().to_string()),
+        _ => None,
+    }
+}
+
+fn authors_value(record: &RecordFromDb, name: Option<&String>) -> Vec<String> {
+    match name.and_then(|n| record.attrs.get(n)) {
+        Some(AttrValue::String(Some(v))) => split_names(v),
+        Some(AttrValue::StringVec(Some(v))) => v
+            .iter()
+            .map(|a| a.trim().to_string())
+            .filter(|a| !a.is_empty())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/* Numbers are years, dates and texts give their first four digits */
+fn year_value(record: &RecordFromDb, name: Option<&String>) -> Option<i32> {
+    match record.attrs.get(name?)? {
+        AttrValue::Integer(Some(v)) | AttrValue::Float(Some(v)) => Some(v.round() as i32),
+        AttrValue::String(Some(v)) => v.get(..4).and_then(|y| y.parse().ok()),
+        _ => None,
+    }
+}
+
+pub fn record_to_citation(record: &RecordFromDb, mapping: &CitationMapping) -> Citation {
+    let authors = authors_value(record, mapping.author.as_ref());
+    let title = text_value(record, mapping.title.as_ref()).unwrap_or_else(|| record_name(record));
+    let year = year_value(record, mapping.year.as_ref());
+
+    Citation {
+        key: citation_key(&authors, year, &title),
+        authors,
+        title,
+        year,
+        publisher: text_value(record, mapping.publisher.as_ref()),
+        isbn: text_value(record, mapping.isbn.as_ref()),
+    }
+}
+
+/* Letters for n-th duplicate like BibTeX tools do: b..z, then aa, ab, ... */
+fn key_suffix(n: usize) -> String {
+    let mut n = n + 1;
+    let mut letters: Vec<u8> = vec![];
+    while n > 0 {
+        n -= 1;
+        letters.push(b'a' + (n % 26) as u8);
+        n /= 26;
+    }
+    letters.iter().rev().map(|l| *l as char).collect()
+}
+
+/* Same key for different records gets a letter, in order of record paths */
+fn make_keys_unique(citations: &mut [Citation]) {
+    let mut used: HashSet<String> = HashSet::new();
+    for citation in citations.iter_mut() {
+        let base = citation.key.clone();
+        let mut duplicate = 0;
+        while !used.insert(citation.key.clone()) {
+            duplicate += 1;
+            citation.key = format!("{}{}", base, key_suffix(duplicate));
+        }
+    }
+}
+
+fn escape_bibtex(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\textbackslash{}"),
+            '~' => result.push_str("\\textasciitilde{}"),
+            '^' => result.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                result.push('\\');
+                result.push(c);
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+pub fn citations_to_bibtex(citations: &[Citation]) -> String {
+    citations
+        .iter()
+        .map(|c| {
+            let mut fields: Vec<(&str, String)> = vec![];
+            if !c.authors.is_empty() {
+                fields.push(("author", c.authors.join(" and ")));
+            }
+            fields.push(("title", c.title.clone()));
+            if let Some(year) = c.year {
+                fields.push(("year", year.to_string()));
+            }
+            if let Some(publisher) = &c.publisher {
+                fields.push(("publisher", publisher.clone()));
+            }
+            if let Some(isbn) = &c.isbn {
+                fields.push(("isbn", isbn.clone()));
+            }
+
+            format!(
+                "@book{{{},\n{}}}\n",
+                c.key,
+                fields
+                    .iter()
+                    .map(|(name, value)| format!("  {} = {{{}}},\n", name, escape_bibtex(value)))
+                    .collect::<String>()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn citations_to_csl_json(citations: &[Citation]) -> Result<String, Box<ErrFR>> {
+    let items: Vec<CslItem> = citations
+        .iter()
+        .map(|c| CslItem {
+            id: c.key.clone(),
+            item_type: "book",
+            title: c.title.clone(),
+            author: c
+                .authors
+                .iter()
+                .map(|a| match split_name(a) {
+                    (family, Some(given)) => CslName {
+                        family,
+                        given: Some(given),
+                        literal: None,
+                    },
+                    (_, None) => CslName {
+                        family: None,
+                        given: None,
+                        literal: Some(a.clone()),
+                    },
+                })
+                .collect(),
+            issued: c.year.map(|y| CslDate {
+                date_parts: vec![vec![y]],
+            }),
+            publisher: c.publisher.clone(),
+            isbn: c.isbn.clone(),
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&items)
+        .map_err(|e| Box::new(ErrFR::new("Error serializing citations").raw(e)))
+}
+
+/* Records of folder's collection, mapped with the saved mapping of its schema */
+pub async fn export_citations(
+    ctx: &AppContext,
+    folder_relative: &Path,
+    output: &Path,
+    format: CitationFormat,
+) -> Result<CitationExportResult, Box<ErrFR>> {
+    let mapping = load_citation_mapping(ctx, folder_relative).await?;
+    let collection = read_collection(ctx, folder_relative, false).await?;
+
+    let mut citations: Vec<Citation> = collection
+        .records
+        .iter()
+        .map(|r| record_to_citation(r, &mapping))
+        .collect();
+    make_keys_unique(&mut citations);
+
+    let content = match format {
+        CitationFormat::Bibtex => citations_to_bibtex(&citations),
+        CitationFormat::CslJson => citations_to_csl_json(&citations)?,
+    };
+    fs::write(output, content).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error writing to disk")
+                .info(&output.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+
+    Ok(CitationExportResult {
+        entries: citations.len() as u32,
+        errors: match collection.errors.is_empty() {
+            true => None,
+            false => Some(ErrFR::new("Some records were not exported").subs(collection.errors)),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn citation(authors: &[&str], year: Option<i32>, title: &str) -> Citation {
+        let authors: Vec<String> = authors.iter().map(|a| a.to_string()).collect();
+        Citation {
+            key: citation_key(&authors, year, title),
+            authors,
+            title: title.to_string(),
+            year,
+            ..Citation::default()
+        }
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(
+            citation(&["Adler, Mortimer J."], Some(1940), "How to Read a Book").key,
+            "adler1940how"
+        );
+        assert_eq!(
+            citation(&["Sönke Ahrens"], Some(2017), "The Smart Notes").key,
+            "ahrens2017smart"
+        );
+        assert_eq!(citation(&[], None, "A").key, "anonnd");
+        assert_eq!(
+            citation(&["Лев Толстой"], Some(1869), "Война и мир").key,
+            "толстой1869война"
+        );
+
+        let mut citations = vec![
+            citation(
+                &["Le Guin, Ursula"],
+                Some(1969),
+                "The Left Hand of Darkness",
+            ),
+            citation(&["Le Guin, Ursula"], Some(1969), "Left"),
+            citation(&["Le Guin, Ursula"], Some(1969), "Left"),
+        ];
+        make_keys_unique(&mut citations);
+        let keys: Vec<&str> = citations.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec!["leguin1969left", "leguin1969leftb", "leguin1969leftc"]
+        );
+    }
+
+    #[test]
+    fn many_identical_keys() {
+        let mut citations = vec![citation(&["Anon"], None, "Untitled"); 60];
+        make_keys_unique(&mut citations);
+        let keys: Vec<&str> = citations.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(keys[1], "anonnduntitledb");
+        assert_eq!(keys[25], "anonnduntitledz");
+        assert_eq!(keys[26], "anonnduntitledaa");
+        assert_eq!(keys[52], "anonnduntitledba");
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 60);
+    }
+
+    #[test]
+    fn formats() {
+        let mut dune = citation(&["Frank Herbert", "Plato"], Some(1965), "Dune & {Sons}");
+        dune.isbn = Some("9780441013593".to_string());
+
+        let bibtex = citations_to_bibtex(std::slice::from_ref(&dune));
+        assert_eq!(
+            bibtex,
+            "@book{herbert1965dune,\n  author = {Frank Herbert and Plato},\n  title = {Dune \\& \\{Sons\\}},\n  year = {1965},\n  isbn = {9780441013593},\n}\n"
+        );
+
+        let csl: serde_json::Value =
+            serde_json::from_str(&citations_to_csl_json(&[dune]).unwrap()).unwrap();
+        assert_eq!(csl[0]["id"], "herbert1965dune");
+        assert_eq!(csl[0]["author"][0]["family"], "Herbert");
+        assert_eq!(csl[0]["author"][1]["literal"], "Plato");
+        assert_eq!(csl[0]["issued"]["date-parts"][0][0], 1965);
+        assert_eq!(csl[0]["ISBN"], "9780441013593");
+    }
+}
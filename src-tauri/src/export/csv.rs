@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::core::core_state::AppContext;
+use crate::export::read_collection;
+use crate::files::batch::{resolve_target, BatchTarget};
+use crate::files::read_save::{read_file_by_path, FileReadMode};
+use crate::schema::types::{AttrValue, DatePair, Schema};
+use crate::utils::errorhandling::ErrFR;
+use crate::utils::helpers::normalize_path_to_os;
+
+const UTF8_BOM: &str = "\u{FEFF}";
+pub const PATH_COLUMN: &str = "path";
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct CsvExportOptions {
+    /* Joins items of collections and date pairs, "; " by default */
+    pub collection_separator: String,
+    /* Goes between start and finish of a date pair, " - " by default */
+    pub date_pair_separator: String,
+    /* Excel does not detect UTF-8 without it */
+    pub bom: bool,
+}
+
+impl Default for CsvExportOptions {
+    fn default() -> Self {
+        Self {
+            collection_separator: "; ".to_string(),
+            date_pair_separator: " - ".to_string(),
+            bom: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct CsvExportResult {
+    pub rows: u32,
+    /* Records that failed to read, they are not in the file */
+    pub errors: Option<ErrFR>,
+}
+
+fn format_number(v: f64, is_integer: bool) -> String {
+    match is_integer {
+        true => (v.round() as i64).to_string(),
+        false => v.to_string(),
+    }
+}
+
+fn format_date_pair(pair: &DatePair, options: &CsvExportOptions) -> String {
+    format!(
+        "{}{}{}",
+        pair.started.as_deref().unwrap_or(""),
+        options.date_pair_separator,
+        pair.finished.as_deref().unwrap_or("")
+    )
+}
+
+/* Flat text representation of a value, empty string for missing values */
+pub fn attr_value_to_cell(value: Option<&AttrValue>, options: &CsvExportOptions) -> String {
+    match value {
+        Some(AttrValue::String(Some(v))) => v.clone(),
+        Some(AttrValue::StringVec(Some(v))) => v.join(&options.collection_separator),
+        Some(AttrValue::DatePairVec(Some(v))) => v
+            .iter()
+            .map(|p| format_date_pair(p, options))
+            .collect::<Vec<_>>()
+            .join(&options.collection_separator),
+        Some(AttrValue::Integer(Some(v))) => format_number(*v, true),
+        Some(AttrValue::Float(Some(v))) => format_number(*v, false),
+        _ => String::new(),
+    }
+}
+
+/* Columns are path followed by schema items in schema order */
+pub fn records_to_csv(
+    schema: &Schema,
+    records: &[(String, HashMap<String, AttrValue>)],
+    options: &CsvExportOptions,
+) -> Result<String, Box<ErrFR>> {
+    let mut writer = ::csv::Writer::from_writer(vec![]);
+    let csv_err = |e: ::csv::Error| Box::new(ErrFR::new("Error when writing csv").raw(e));
+
+    let mut header = vec![PATH_COLUMN];
+    header.extend(schema.items.iter().map(|i| i.label()));
+    writer.write_record(&header).map_err(csv_err)?;
+
+    for (path, attrs) in records {
+        let mut row = vec![path.clone()];
+        row.extend(
+            schema
+                .items
+                .iter()
+                .map(|i| attr_value_to_cell(attrs.get(&i.name), options)),
+        );
+        writer.write_record(&row).map_err(csv_err)?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| Box::new(ErrFR::new("Error when writing csv").raw(e)))?;
+    let content = String::from_utf8(bytes)
+        .map_err(|e| Box::new(ErrFR::new("Error when writing csv").raw(e)))?;
+
+    Ok(match options.bom {
+        true => format!("{}{}", UTF8_BOM, content),
+        false => content,
+    })
+}
+
+struct CsvSource {
+    schema: Option<Schema>,
+    records: Vec<(String, HashMap<String, AttrValue>)>,
+    /* One per record that is not exported */
+    errors: Vec<ErrFR>,
+}
+
+/* Records of any schema, schema of the first one defines columns */
+async fn read_paths(ctx: &AppContext, paths: Vec<PathBuf>) -> CsvSource {
+    let mut source = CsvSource {
+        schema: None,
+        records: vec![],
+        errors: vec![],
+    };
+
+    for path in paths {
+        let path_string = path.to_string_lossy().to_string();
+        let file = match read_file_by_path(ctx, &path, FileReadMode::OnlyMeta).await {
+            Ok(v) => v,
+            Err(e) => {
+                source.errors.push(e.info(&path_string));
+                continue;
+            }
+        };
+
+        // Broken frontmatter would be exported as empty cells
+        if let Some(e) = file.parsing_error {
+            source.errors.push(e.info(&path_string));
+            continue;
+        }
+
+        if source.schema.is_none() {
+            source.schema = Some(file.schema.schema);
+        }
+        source.records.push((path_string, file.record.attrs));
+    }
+
+    source
+}
+
+/*
+    Folder target is exported as a collection, same as in other exports.
+    For other targets schema of the first record defines columns, records of other schemas only fill matching attributes.
+    `output` is an absolute path chosen by user, it's overwritten.
+*/
+pub async fn export_csv(
+    ctx: &AppContext,
+    target: BatchTarget,
+    output: &Path,
+    options: &CsvExportOptions,
+) -> Result<CsvExportResult, Box<ErrFR>> {
+    let source = match target {
+        BatchTarget::Folder(folder) => {
+            let collection = read_collection(ctx, &normalize_path_to_os(&folder), false).await?;
+            CsvSource {
+                schema: Some(collection.schema),
+                records: collection
+                    .records
+                    .into_iter()
+                    .filter_map(|r| r.path.map(|p| (p, r.attrs)))
+                    .collect(),
+                errors: collection.errors,
+            }
+        }
+        target => read_paths(ctx, resolve_target(ctx, target).await?).await,
+    };
+
+    let Some(schema) = source.schema else {
+        return Err(Box::new(
+            ErrFR::new("Nothing to export").subs(source.errors),
+        ));
+    };
+
+    let content = records_to_csv(&schema, &source.records, options)?;
+
+    fs::write(output, content).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error writing to disk")
+                .info(&output.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+
+    Ok(CsvExportResult {
+        rows: source.records.len() as u32,
+        errors: match source.errors.is_empty() {
+            true => None,
+            false => Some(ErrFR::new("Some records were not exported").subs(source.errors)),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::types::{
+        DatesPairCollectionSettings, NumberSettings, SchemaAttrType, SchemaItem,
+        TextCollectionSettings, TextSettings,
+    };
+
+    fn schema() -> Schema {
+        Schema {
+            name: "Books".to_string(),
+            version: "1.0".to_string(),
+            fill_from_filename: None,
+            fill_api_search_from: None,
+            items: vec![
+                SchemaItem {
+                    name: "title".to_string(),
+                    value: SchemaAttrType::Text(TextSettings {
+                        display_name: Some("Title".to_string()),
+                        ..TextSettings::default()
+                    }),
+                },
+                SchemaItem {
+                    name: "year".to_string(),
+                    value: SchemaAttrType::Number(NumberSettings::default()),
+                },
+                SchemaItem {
+                    name: "tags".to_string(),
+                    value: SchemaAttrType::TextCollection(TextCollectionSettings::default()),
+                },
+                SchemaItem {
+                    name: "read".to_string(),
+                    value: SchemaAttrType::DatesPairCollection(
+                        DatesPairCollectionSettings::default(),
+                    ),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn flattens_values_in_schema_order() {
+        let attrs = HashMap::from([
+            (
+                "read".to_string(),
+                AttrValue::DatePairVec(Some(vec![
+                    DatePair {
+                        started: Some("2024-01-01".to_string()),
+                        finished: Some("2024-01-20".to_string()),
+                    },
+                    DatePair {
+                        started: Some("2025-03-01".to_string()),
+                        finished: None,
+                    },
+                ])),
+            ),
+            ("year".to_string(), AttrValue::Integer(Some(1965.0))),
+            (
+                "tags".to_string(),
+                AttrValue::StringVec(Some(vec!["sci-fi".to_string(), "classic".to_string()])),
+            ),
+            (
+                "title".to_string(),
+                AttrValue::String(Some("Dune, Part \"One\"".to_string())),
+            ),
+        ]);
+
+        let options = CsvExportOptions {
+            bom: false,
+            ..CsvExportOptions::default()
+        };
+        let csv =
+            records_to_csv(&schema(), &[("books/Dune.md".to_string(), attrs)], &options).unwrap();
+
+        assert_eq!(
+            csv,
+            "path,Title,year,tags,read\n\
+             books/Dune.md,\"Dune, Part \"\"One\"\"\",1965,sci-fi; classic,2024-01-01 - 2024-01-20; 2025-03-01 - \n"
+        );
+    }
+
+    #[test]
+    fn bom_and_empty_values() {
+        let csv = records_to_csv(
+            &schema(),
+            &[("a.md".to_string(), HashMap::new())],
+            &CsvExportOptions::default(),
+        )
+        .unwrap();
+
+        assert!(csv.starts_with(UTF8_BOM));
+        assert!(csv.ends_with("a.md,,,,\n"));
+    }
+}
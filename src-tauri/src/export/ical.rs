@@ -0,0 +1,365 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{Local, NaiveDate, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ts_rs::TS;
+
+use crate::core::core_state::AppContext;
+use crate::export::read_collection;
+use crate::export::record_name;
+use crate::schema::types::{AttrValue, DatePair, SchemaAttrType};
+use crate::utils::errorhandling::ErrFR;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const MAX_LINE_OCTETS: usize = 75;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, TS)]
+#[ts(export)]
+pub enum MissingFinishedRule {
+    Skip,
+    /* Event on the start day only */
+    SingleDay,
+    /* Event lasts until the day of export, for things that are still in progress */
+    UntilToday,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, TS)]
+#[ts(export)]
+pub enum MissingStartedRule {
+    Skip,
+    /* Event on the finish day only */
+    SingleDay,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct IcalExportOptions {
+    /* Schema owner folders relative to root path, every schema is exported when empty */
+    pub folders: Vec<String>,
+    /* YYYY-MM-DD, events that overlap the range are exported */
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub missing_finished: MissingFinishedRule,
+    pub missing_started: MissingStartedRule,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct IcalExportResult {
+    pub events: u32,
+    /* Records that failed to read and pairs with dates that can't be read */
+    pub errors: Option<ErrFR>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: String,
+    pub start: NaiveDate,
+    /* Inclusive, unlike DTEND */
+    pub end: NaiveDate,
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, Box<ErrFR>> {
+    value
+        .get(..10)
+        .and_then(|d| NaiveDate::parse_from_str(d, DATE_FORMAT).ok())
+        .ok_or_else(|| Box::new(ErrFR::new("Date can't be read").raw(value)))
+}
+
+fn parse_optional_date(value: Option<&str>) -> Result<Option<NaiveDate>, Box<ErrFR>> {
+    match value.map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        Some(v) => parse_date(v).map(Some),
+        None => Ok(None),
+    }
+}
+
+/* Inclusive range of days for a pair, None when rules say it's skipped */
+pub fn pair_to_range(
+    pair: &DatePair,
+    options: &IcalExportOptions,
+    today: NaiveDate,
+) -> Result<Option<(NaiveDate, NaiveDate)>, Box<ErrFR>> {
+    let started = parse_optional_date(pair.started.as_deref())?;
+    let finished = parse_optional_date(pair.finished.as_deref())?;
+
+    let range = match (started, finished) {
+        (Some(s), Some(f)) if f < s => {
+            return Err(Box::new(
+                ErrFR::new("Finish date is before start date").raw(format!("{} - {}", s, f)),
+            ))
+        }
+        (Some(s), Some(f)) => Some((s, f)),
+        (Some(s), None) => match options.missing_finished {
+            MissingFinishedRule::Skip => None,
+            MissingFinishedRule::SingleDay => Some((s, s)),
+            MissingFinishedRule::UntilToday => Some((s, today.max(s))),
+        },
+        (None, Some(f)) => match options.missing_started {
+            MissingStartedRule::Skip => None,
+            MissingStartedRule::SingleDay => Some((f, f)),
+        },
+        (None, None) => None,
+    };
+
+    Ok(range)
+}
+
+/*
+    Pair is identified by record, attribute and its position in the list, not by its dates,
+    so calendars update the event on reimport when a date is corrected or finish date is added
+*/
+fn event_uid(path: &str, attr: &str, pair_index: usize) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}\n{}\n{}", path, attr, pair_index).as_bytes());
+    let hash = hasher.finalize();
+    format!(
+        "{}@asom",
+        hash[..16]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/* Lines longer than 75 octets are split, continuation lines start with a space */
+fn fold_line(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut octets = 0;
+
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            result.push_str("\r\n ");
+            octets = 1;
+        }
+        result.push(c);
+        octets += c.len_utf8();
+    }
+    result
+}
+
+pub fn render_calendar(name: &str, events: &[CalendarEvent], stamp: &str) -> String {
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//asom//Export//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+    ];
+
+    for event in events {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", event.uid),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", event.start.format("%Y%m%d")),
+            // All-day events end on the next day
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                (event.end + TimeDelta::days(1)).format("%Y%m%d")
+            ),
+            format!("SUMMARY:{}", escape_text(&event.summary)),
+            format!("DESCRIPTION:{}", escape_text(&event.description)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|l| fold_line(l) + "\r\n")
+        .collect::<String>()
+}
+
+/* Every started/finished pair of DatesPairCollection attributes becomes an all-day event */
+pub async fn export_ical(
+    ctx: &AppContext,
+    output: &Path,
+    options: &IcalExportOptions,
+) -> Result<IcalExportResult, Box<ErrFR>> {
+    let from = parse_optional_date(options.from.as_deref())?;
+    let to = parse_optional_date(options.to.as_deref())?;
+    let today = Local::now().date_naive();
+
+    let mut folders: Vec<String> = match options.folders.is_empty() {
+        true => ctx
+            .schemas_cache
+            .get_schemas_list()
+            .await
+            .into_keys()
+            .collect(),
+        false => options.folders.clone(),
+    };
+    folders.sort();
+
+    let mut events: Vec<CalendarEvent> = vec![];
+    let mut errors: Vec<ErrFR> = vec![];
+
+    for folder in folders.iter() {
+        let collection = read_collection(ctx, Path::new(folder), false).await?;
+        errors.extend(collection.errors);
+
+        let items: Vec<(&String, &str)> = collection
+            .schema
+            .items
+            .iter()
+            .filter(|i| matches!(i.value, SchemaAttrType::DatesPairCollection(_)))
+            .map(|i| (&i.name, i.label()))
+            .collect();
+
+        for record in collection.records.iter() {
+            let path = record.path.clone().unwrap_or_default();
+
+            for (name, label) in items.iter() {
+                let Some(AttrValue::DatePairVec(Some(pairs))) = record.attrs.get(*name) else {
+                    continue;
+                };
+
+                for (i, pair) in pairs.iter().enumerate() {
+                    let (start, end) = match pair_to_range(pair, options, today) {
+                        Ok(Some(range)) => range,
+                        Ok(None) => continue,
+                        Err(e) => {
+                            errors.push(e.info(&path));
+                            continue;
+                        }
+                    };
+                    if from.is_some_and(|f| end < f) || to.is_some_and(|t| start > t) {
+                        continue;
+                    }
+
+                    events.push(CalendarEvent {
+                        uid: event_uid(&path, name, i),
+                        summary: record_name(record),
+                        description: format!("{} · {}", collection.schema.name, label),
+                        start,
+                        end,
+                    });
+                }
+            }
+        }
+    }
+
+    events.sort_by(|a, b| a.start.cmp(&b.start).then(a.summary.cmp(&b.summary)));
+
+    let content = render_calendar(
+        "asom",
+        &events,
+        &Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+    );
+    fs::write(output, content).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error writing to disk")
+                .info(&output.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+
+    Ok(IcalExportResult {
+        events: events.len() as u32,
+        errors: match errors.is_empty() {
+            true => None,
+            false => Some(ErrFR::new("Some dates were not exported").subs(errors)),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(missing_finished: MissingFinishedRule) -> IcalExportOptions {
+        IcalExportOptions {
+            folders: vec![],
+            from: None,
+            to: None,
+            missing_finished,
+            missing_started: MissingStartedRule::Skip,
+        }
+    }
+
+    fn pair(started: Option<&str>, finished: Option<&str>) -> DatePair {
+        DatePair {
+            started: started.map(|s| s.to_string()),
+            finished: finished.map(|s| s.to_string()),
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn open_ended_pairs() {
+        let today = date("2026-03-10");
+        let open = pair(Some("2026-03-01"), None);
+
+        assert_eq!(
+            pair_to_range(&open, &options(MissingFinishedRule::Skip), today).unwrap(),
+            None
+        );
+        assert_eq!(
+            pair_to_range(&open, &options(MissingFinishedRule::SingleDay), today).unwrap(),
+            Some((date("2026-03-01"), date("2026-03-01")))
+        );
+        assert_eq!(
+            pair_to_range(&open, &options(MissingFinishedRule::UntilToday), today).unwrap(),
+            Some((date("2026-03-01"), today))
+        );
+        assert_eq!(
+            pair_to_range(
+                &pair(None, Some("2026-03-05")),
+                &options(MissingFinishedRule::Skip),
+                today
+            )
+            .unwrap(),
+            None
+        );
+        assert!(pair_to_range(
+            &pair(Some("2026-03-05"), Some("2026-03-01")),
+            &options(MissingFinishedRule::Skip),
+            today
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn uid_does_not_depend_on_dates() {
+        let uid = event_uid("books/Dune.md", "read", 0);
+        assert_eq!(uid, event_uid("books/Dune.md", "read", 0));
+        assert_ne!(uid, event_uid("books/Dune.md", "read", 1));
+        assert_ne!(uid, event_uid("books/Dune.md", "listened", 0));
+    }
+
+    #[test]
+    fn calendar_text() {
+        let events = vec![CalendarEvent {
+            uid: "1@asom".to_string(),
+            summary: "Dune, Part One; a long title that has to be folded because it is too long"
+                .to_string(),
+            description: "Books · Read".to_string(),
+            start: date("2026-03-01"),
+            end: date("2026-03-05"),
+        }];
+        let ics = render_calendar("asom", &events, "20260310T120000Z");
+
+        assert!(ics.contains("DTSTART;VALUE=DATE:20260301\r\n"));
+        assert!(ics.contains("DTEND;VALUE=DATE:20260306\r\n"));
+        assert!(ics.contains("SUMMARY:Dune\\, Part One\\; a long title"));
+        assert!(ics.lines().all(|l| l.len() <= MAX_LINE_OCTETS));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+}
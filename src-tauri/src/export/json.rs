@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::core::core_state::AppContext;
+use crate::export::read_collection;
+use crate::schema::types::{AttrValue, Schema};
+use crate::utils::errorhandling::ErrFR;
+
+/* Bumped on changes that older app versions can't read */
+pub const DUMP_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, TS)]
+#[ts(export)]
+pub enum DumpFormat {
+    /* Single object with records array */
+    Json,
+    /* Header on the first line, then one record per line */
+    Ndjson,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct DumpExportOptions {
+    pub format: DumpFormat,
+    pub include_markdown: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct DumpHeader {
+    pub version: u32,
+    /* Exported folder relative to root path, for information only */
+    pub folder: String,
+    pub schema: Schema,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+pub struct DumpRecord {
+    /* Relative to exported folder, always with forward slashes */
+    pub path: String,
+    /* Sorted by name so dumps of the same folder can be diffed */
+    pub attrs: BTreeMap<String, AttrValue>,
+    #[serde(default)]
+    pub markdown: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct Dump {
+    pub version: u32,
+    pub folder: String,
+    pub schema: Schema,
+    pub records: Vec<DumpRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct DumpExportResult {
+    pub records: u32,
+    /* Records that failed to read, they are not in the file */
+    pub errors: Option<ErrFR>,
+}
+
+/* Same path on every platform, so dumps made on Windows restore on Unix */
+pub fn to_portable_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(v) => Some(v.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn serialize_err(e: serde_json::Error) -> Box<ErrFR> {
+    Box::new(ErrFR::new("Error serializing records").raw(e))
+}
+
+pub fn dump_to_string(dump: Dump, format: DumpFormat) -> Result<String, Box<ErrFR>> {
+    match format {
+        DumpFormat::Json => serde_json::to_string_pretty(&dump).map_err(serialize_err),
+        DumpFormat::Ndjson => {
+            let header = DumpHeader {
+                version: dump.version,
+                folder: dump.folder,
+                schema: dump.schema,
+            };
+            let mut lines = vec![serde_json::to_string(&header).map_err(serialize_err)?];
+            for record in dump.records.iter() {
+                lines.push(serde_json::to_string(record).map_err(serialize_err)?);
+            }
+            Ok(lines.join("\n") + "\n")
+        }
+    }
+}
+
+pub async fn export_dump(
+    ctx: &AppContext,
+    folder_relative: &Path,
+    output: &Path,
+    options: &DumpExportOptions,
+) -> Result<DumpExportResult, Box<ErrFR>> {
+    let collection = read_collection(ctx, folder_relative, options.include_markdown).await?;
+
+    let records: Vec<DumpRecord> = collection
+        .records
+        .into_iter()
+        .map(|r| {
+            let path = r.path.unwrap_or_default();
+            let path = Path::new(&path);
+            DumpRecord {
+                path: to_portable_path(path.strip_prefix(folder_relative).unwrap_or(path)),
+                attrs: r.attrs.into_iter().collect(),
+                markdown: r.markdown.filter(|_| options.include_markdown),
+            }
+        })
+        .collect();
+
+    let count = records.len() as u32;
+    let content = dump_to_string(
+        Dump {
+            version: DUMP_FORMAT_VERSION,
+            folder: to_portable_path(folder_relative),
+            schema: collection.schema,
+            records,
+        },
+        options.format,
+    )?;
+
+    fs::write(output, content).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error writing to disk")
+                .info(&output.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+
+    Ok(DumpExportResult {
+        records: count,
+        errors: match collection.errors.is_empty() {
+            true => None,
+            false => Some(ErrFR::new("Some records were not exported").subs(collection.errors)),
+        },
+    })
+}
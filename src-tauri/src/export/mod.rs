@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use crate::cache::query::{get_files_by_path, RecordFromDb};
+use crate::core::core_state::AppContext;
+use crate::files::read_save::{read_file_by_path, FileReadMode};
+use crate::schema::types::Schema;
+use crate::utils::errorhandling::ErrFR;
+
+pub mod citation;
+pub mod csv;
+pub mod ical;
+pub mod json;
+pub mod site;
+
+pub struct Collection {
+    pub schema: Schema,
+    /* Paths are relative to root path, ordered by path */
+    pub records: Vec<RecordFromDb>,
+    /* One per record that failed to read */
+    pub errors: Vec<ErrFR>,
+}
+
+/*
+    Records of folder and its subfolders that use the same schema,
+    subfolders with their own schema are separate collections.
+*/
+pub async fn read_collection(
+    ctx: &AppContext,
+    folder_relative: &Path,
+    include_markdown: bool,
+) -> Result<Collection, Box<ErrFR>> {
+    let list = get_files_by_path(ctx, folder_relative).await?;
+    let schema_owner = list.schema.location.schema_owner_folder;
+
+    let mut records: Vec<RecordFromDb> = vec![];
+    let mut errors: Vec<ErrFR> = vec![];
+
+    for path in list.records.into_iter().filter_map(|r| r.path) {
+        let path = Path::new(&path);
+        let read_mode = match include_markdown {
+            true => FileReadMode::FullFile,
+            false => FileReadMode::OnlyMeta,
+        };
+        let file = match read_file_by_path(ctx, path, read_mode).await {
+            Ok(v) => v,
+            Err(e) => {
+                errors.push(e.info(&path.to_string_lossy()));
+                continue;
+            }
+        };
+
+        // Exports would silently lose values of broken frontmatter
+        if let Some(e) = file.parsing_error {
+            errors.push(e.info(&path.to_string_lossy()));
+            continue;
+        }
+
+        if file.schema.location.schema_owner_folder == schema_owner {
+            records.push(file.record);
+        }
+    }
+
+    Ok(Collection {
+        schema: list.schema.schema,
+        records,
+        errors,
+    })
+}
+
+/* File name without extension, what user sees as record title */
+pub fn record_name(record: &RecordFromDb) -> String {
+    record
+        .path
+        .as_deref()
+        .and_then(|p| Path::new(p).file_stem())
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+pub fn escape_html(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
@@ -0,0 +1,602 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::query::RecordFromDb;
+use crate::core::core_state::AppContext;
+use crate::export::{escape_html, read_collection, record_name};
+use crate::files::assets::get_assets_folder;
+use crate::files::markdown::{
+    encode_uri_component, render_markdown, MarkdownRenderOptions, RenderedMarkdown,
+};
+use crate::schema::types::{AttrValue, NumberSettings, NumberStyle, SchemaAttrType, SchemaItem};
+use crate::utils::errorhandling::ErrFR;
+
+const ASSETS_DIR: &str = "assets";
+const RECORDS_DIR: &str = "records";
+const STYLE_FILE: &str = "style.css";
+const DEFAULT_STARS_COUNT: u8 = 5;
+
+const STYLE: &str = r#"body { font-family: system-ui, sans-serif; margin: 0 auto; max-width: 960px; padding: 2rem 1rem; color: #1c1c1c; background: #fff; }
+a { color: inherit; }
+h1 { font-family: Georgia, serif; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.5rem; border-bottom: 1px solid #e5e5e5; vertical-align: top; }
+th[data-column] { cursor: pointer; user-select: none; }
+th[data-dir="asc"]::after { content: " ▲"; }
+th[data-dir="desc"]::after { content: " ▼"; }
+.grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(160px, 1fr)); gap: 1.5rem; }
+.grid a { text-decoration: none; }
+.grid img, .cover-placeholder { width: 100%; aspect-ratio: 2 / 3; object-fit: cover; border-radius: 4px; background: #eee; }
+.record img { max-width: 100%; }
+dl { display: grid; grid-template-columns: max-content 1fr; gap: 0.5rem 1.5rem; }
+dt { color: #777; }
+dd { margin: 0; }
+.tags { display: flex; flex-wrap: wrap; gap: 0.25rem; list-style: none; margin: 0; padding: 0; }
+.tags li { background: #f0f0f0; border-radius: 4px; padding: 0 0.4rem; }
+.stars { color: #d4a017; letter-spacing: 0.1em; }
+.markdown { margin-top: 2rem; line-height: 1.6; }
+"#;
+
+const SORT_SCRIPT: &str = r#"document.querySelectorAll('th[data-column]').forEach((th) => {
+  th.addEventListener('click', () => {
+    const body = th.closest('table').tBodies[0];
+    const dir = th.dataset.dir === 'asc' ? 'desc' : 'asc';
+    th.closest('tr').querySelectorAll('th').forEach((h) => delete h.dataset.dir);
+    th.dataset.dir = dir;
+    const numeric = th.dataset.numeric === 'true';
+    const key = (row) => row.cells[th.cellIndex].dataset.sort ?? '';
+    const rows = [...body.rows].sort((a, b) => {
+      const r = numeric
+        ? (parseFloat(key(a)) || 0) - (parseFloat(key(b)) || 0)
+        : key(a).localeCompare(key(b));
+      return dir === 'asc' ? r : -r;
+    });
+    rows.forEach((row) => body.appendChild(row));
+  });
+});"#;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, TS)]
+#[ts(export)]
+pub enum SiteIndexLayout {
+    /* Sortable table with every attribute */
+    Table,
+    /* Covers with names, first Image attribute is used */
+    Grid,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct SiteExportOptions {
+    /* Schema name is used when empty */
+    pub title: Option<String>,
+    pub layout: SiteIndexLayout,
+    pub include_markdown: bool,
+    /* Leaves out attributes marked as private in schema */
+    pub exclude_private: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct SiteExportResult {
+    /* Record pages, index is not counted */
+    pub pages: u32,
+    pub assets: u32,
+    /* Records that failed to read and assets that were not copied */
+    pub errors: Option<ErrFR>,
+}
+
+/* Images from assets folder in body point to copies next to record pages */
+fn render_body(record: &RecordFromDb) -> Option<RenderedMarkdown> {
+    let markdown = record
+        .markdown
+        .as_deref()
+        .filter(|m| !m.trim().is_empty())?;
+    let path = Path::new(record.path.as_deref().unwrap_or_default());
+
+    Some(render_markdown(
+        markdown,
+        &MarkdownRenderOptions {
+            record_folder: path.parent().unwrap_or(Path::new("")),
+            asset_url: &|name| format!("../{}/{}", ASSETS_DIR, encode_uri_component(name)),
+        },
+    ))
+}
+
+fn format_number(value: f64, settings: &NumberSettings) -> String {
+    match settings.decimal_places {
+        Some(places) if places > 0 => format!("{:.*}", places as usize, value),
+        _ => format!("{}", value.round() as i64),
+    }
+}
+
+fn render_stars(value: f64, settings: &NumberSettings) -> String {
+    let count = settings.stars_count.unwrap_or(DEFAULT_STARS_COUNT) as usize;
+    let max = settings.max.unwrap_or(count as f64);
+    let filled = match max > 0.0 {
+        true => ((value / max * count as f64).round() as usize).min(count),
+        false => 0,
+    };
+
+    format!(
+        r#"<span class="stars" title="{}">{}{}</span>"#,
+        format_number(value, settings),
+        "★".repeat(filled),
+        "☆".repeat(count - filled)
+    )
+}
+
+fn render_dates<'a>(dates: impl IntoIterator<Item = &'a String>) -> String {
+    dates
+        .into_iter()
+        .map(|d| format!(r#"<time datetime="{0}">{0}</time>"#, escape_html(d)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/* Inner HTML for value of attribute, None when there is nothing to show */
+fn render_value(value: &AttrValue, item: &SchemaItem, assets_prefix: &str) -> Option<String> {
+    let html = match (&item.value, value) {
+        (SchemaAttrType::Image(_), AttrValue::String(Some(v))) => format!(
+            r#"<img src="{}/{}" alt="{}" loading="lazy">"#,
+            assets_prefix,
+            encode_uri_component(v),
+            escape_html(item.label())
+        ),
+        (SchemaAttrType::Date(_), AttrValue::String(Some(v))) => render_dates([v]),
+        (SchemaAttrType::Text(s), AttrValue::String(Some(v))) => match s.is_multiline {
+            Some(true) => escape_html(v).replace('\n', "<br>"),
+            _ => escape_html(v),
+        },
+        (SchemaAttrType::TextCollection(s), AttrValue::StringVec(Some(v))) => format!(
+            r#"<ul class="tags">{}</ul>"#,
+            v.iter()
+                .map(|t| format!(
+                    "<li>{}{}</li>",
+                    escape_html(s.prefix.as_deref().unwrap_or("")),
+                    escape_html(t)
+                ))
+                .collect::<String>()
+        ),
+        (SchemaAttrType::DateCollection(_), AttrValue::StringVec(Some(v))) => render_dates(v),
+        (SchemaAttrType::DatesPairCollection(_), AttrValue::DatePairVec(Some(v))) => v
+            .iter()
+            .map(|p| {
+                format!(
+                    "{} – {}",
+                    render_dates(&p.started),
+                    render_dates(&p.finished)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("<br>"),
+        (SchemaAttrType::Number(s), AttrValue::Integer(Some(v)) | AttrValue::Float(Some(v))) => {
+            match s.style {
+                Some(NumberStyle::Stars) => render_stars(*v, s),
+                _ => format_number(*v, s),
+            }
+        }
+        _ => return None,
+    };
+
+    match html.trim().is_empty() {
+        true => None,
+        false => Some(html),
+    }
+}
+
+/* Value that index table is sorted by */
+fn sort_key(value: Option<&AttrValue>) -> String {
+    match value {
+        Some(AttrValue::String(Some(v))) => v.to_lowercase(),
+        Some(AttrValue::StringVec(Some(v))) => v.join(", ").to_lowercase(),
+        Some(AttrValue::Integer(Some(v)) | AttrValue::Float(Some(v))) => v.to_string(),
+        Some(AttrValue::DatePairVec(Some(v))) => v
+            .iter()
+            .filter_map(|p| p.finished.clone().or(p.started.clone()))
+            .max()
+            .unwrap_or_default(),
+        _ => "".to_string(),
+    }
+}
+
+fn page(title: &str, root_prefix: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{}</title>
+<link rel="stylesheet" href="{}{}">
+</head>
+<body>
+{}
+</body>
+</html>
+"#,
+        escape_html(title),
+        root_prefix,
+        STYLE_FILE,
+        body
+    )
+}
+
+/* Lowercase latin letters and digits, other letters are kept as is */
+fn slug(name: &str) -> String {
+    let slug = name
+        .chars()
+        .flat_map(|c| c.to_lowercase())
+        .map(|c| match c.is_alphanumeric() {
+            true => c,
+            false => '-',
+        })
+        .collect::<String>()
+        .split('-')
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    match slug.is_empty() {
+        true => "record".to_string(),
+        false => slug,
+    }
+}
+
+struct SitePage<'a> {
+    name: String,
+    file_name: String,
+    record: &'a RecordFromDb,
+    body: Option<RenderedMarkdown>,
+}
+
+fn render_record_page(page_data: &SitePage, items: &[&SchemaItem], site_title: &str) -> String {
+    let attributes: String = items
+        .iter()
+        .filter_map(|item| {
+            let value = page_data.record.attrs.get(&item.name)?;
+            render_value(value, item, &format!("../{}", ASSETS_DIR))
+                .map(|html| format!("<dt>{}</dt><dd>{}</dd>\n", escape_html(item.label()), html))
+        })
+        .collect();
+
+    let markdown = page_data
+        .body
+        .as_ref()
+        .map(|b| format!(r#"<div class="markdown">{}</div>"#, b.html))
+        .unwrap_or_default();
+
+    page(
+        &format!("{} – {}", page_data.name, site_title),
+        "../",
+        &format!(
+            r#"<nav><a href="../index.html">← {}</a></nav>
+<article class="record">
+<h1>{}</h1>
+<dl>
+{}</dl>
+{}
+</article>"#,
+            escape_html(site_title),
+            escape_html(&page_data.name),
+            attributes,
+            markdown
+        ),
+    )
+}
+
+fn render_index(
+    pages: &[SitePage],
+    items: &[&SchemaItem],
+    title: &str,
+    layout: SiteIndexLayout,
+) -> String {
+    let link = |p: &SitePage| format!("{}/{}", RECORDS_DIR, p.file_name);
+
+    let body = match layout {
+        SiteIndexLayout::Table => {
+            let columns: Vec<&&SchemaItem> = items
+                .iter()
+                .filter(|i| !matches!(i.value, SchemaAttrType::Image(_)))
+                .collect();
+
+            let header: String = columns
+                .iter()
+                .map(|i| {
+                    format!(
+                        r#"<th data-column data-numeric="{}">{}</th>"#,
+                        matches!(i.value, SchemaAttrType::Number(_)),
+                        escape_html(i.label())
+                    )
+                })
+                .collect();
+
+            let rows: String = pages
+                .iter()
+                .map(|p| {
+                    let cells: String = columns
+                        .iter()
+                        .map(|i| {
+                            let value = p.record.attrs.get(&i.name);
+                            format!(
+                                r#"<td data-sort="{}">{}</td>"#,
+                                escape_html(&sort_key(value)),
+                                value
+                                    .and_then(|v| render_value(v, i, ASSETS_DIR))
+                                    .unwrap_or_default()
+                            )
+                        })
+                        .collect();
+                    format!(
+                        "<tr><td data-sort=\"{}\"><a href=\"{}\">{}</a></td>{}</tr>\n",
+                        escape_html(&p.name.to_lowercase()),
+                        escape_html(&link(p)),
+                        escape_html(&p.name),
+                        cells
+                    )
+                })
+                .collect();
+
+            format!(
+                "<table>\n<thead><tr><th data-column>Name</th>{}</tr></thead>\n<tbody>\n{}</tbody>\n</table>\n<script>\n{}\n</script>",
+                header, rows, SORT_SCRIPT
+            )
+        }
+        SiteIndexLayout::Grid => {
+            let cover = items
+                .iter()
+                .find(|i| matches!(i.value, SchemaAttrType::Image(_)));
+
+            let cards: String = pages
+                .iter()
+                .map(|p| {
+                    let image = cover
+                        .and_then(|i| {
+                            p.record
+                                .attrs
+                                .get(&i.name)
+                                .and_then(|v| render_value(v, i, ASSETS_DIR))
+                        })
+                        .unwrap_or(r#"<div class="cover-placeholder"></div>"#.to_string());
+                    format!(
+                        "<a href=\"{}\">{}<div>{}</div></a>\n",
+                        escape_html(&link(p)),
+                        image,
+                        escape_html(&p.name)
+                    )
+                })
+                .collect();
+
+            format!("<div class=\"grid\">\n{}</div>", cards)
+        }
+    };
+
+    page(
+        title,
+        "",
+        &format!("<h1>{}</h1>\n{}", escape_html(title), body),
+    )
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), Box<ErrFR>> {
+    fs::write(path, content).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error writing to disk")
+                .info(&path.to_string_lossy())
+                .raw(e),
+        )
+    })
+}
+
+fn create_dir(path: &Path) -> Result<(), Box<ErrFR>> {
+    fs::create_dir_all(path).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error creating directory")
+                .info(&path.to_string_lossy())
+                .raw(e),
+        )
+    })
+}
+
+/*
+    Writes index.html, one page per record and images that are used by visible attributes and bodies.
+    Files from previous export into the same folder are overwritten.
+*/
+pub async fn export_site(
+    ctx: &AppContext,
+    folder_relative: &Path,
+    output: &Path,
+    options: &SiteExportOptions,
+) -> Result<SiteExportResult, Box<ErrFR>> {
+    let collection = read_collection(ctx, folder_relative, options.include_markdown).await?;
+    let mut errors = collection.errors;
+
+    let items: Vec<&SchemaItem> = collection
+        .schema
+        .items
+        .iter()
+        .filter(|i| !(options.exclude_private && i.is_private()))
+        .collect();
+
+    let title = match options.title.as_deref().map(|t| t.trim()) {
+        Some(t) if !t.is_empty() => t.to_string(),
+        _ => collection.schema.name.clone(),
+    };
+
+    let mut used_names: HashSet<String> = HashSet::new();
+    let pages: Vec<SitePage> = collection
+        .records
+        .iter()
+        .map(|record| {
+            let name = record_name(record);
+            let base = slug(&name);
+            let mut file_name = format!("{}.html", base);
+            let mut counter = 1;
+            while !used_names.insert(file_name.clone()) {
+                counter += 1;
+                file_name = format!("{}-{}.html", base, counter);
+            }
+            SitePage {
+                name,
+                file_name,
+                record,
+                body: render_body(record),
+            }
+        })
+        .collect();
+
+    create_dir(&output.join(RECORDS_DIR))?;
+    write_file(&output.join(STYLE_FILE), STYLE)?;
+    write_file(
+        &output.join("index.html"),
+        &render_index(&pages, &items, &title, options.layout),
+    )?;
+
+    for p in pages.iter() {
+        write_file(
+            &output.join(RECORDS_DIR).join(&p.file_name),
+            &render_record_page(p, &items, &title),
+        )?;
+    }
+
+    let assets: BTreeSet<&String> = pages
+        .iter()
+        .flat_map(|p| {
+            items
+                .iter()
+                .filter_map(|i| match (&i.value, p.record.attrs.get(&i.name)) {
+                    (SchemaAttrType::Image(_), Some(AttrValue::String(Some(v))))
+                        if !v.is_empty() =>
+                    {
+                        Some(v)
+                    }
+                    _ => None,
+                })
+                .chain(p.body.iter().flat_map(|b| b.assets.iter()))
+        })
+        .collect();
+
+    let assets_folder = get_assets_folder(ctx).await?;
+    let mut copied = 0;
+    if !assets.is_empty() {
+        create_dir(&output.join(ASSETS_DIR))?;
+    }
+
+    for asset in assets {
+        // Values are file names in assets folder, anything else could point outside of it
+        let is_file_name = Path::new(asset)
+            .file_name()
+            .is_some_and(|n| n == asset.as_str());
+        let result = match is_file_name {
+            true => fs::copy(
+                assets_folder.join(asset),
+                output.join(ASSETS_DIR).join(asset),
+            )
+            .map_err(|e| ErrFR::new("Image was not copied").info(asset).raw(e)),
+            false => Err(ErrFR::new("Invalid image name").info(asset)),
+        };
+        match result {
+            Ok(_) => copied += 1,
+            Err(e) => errors.push(e),
+        }
+    }
+
+    Ok(SiteExportResult {
+        pages: pages.len() as u32,
+        assets: copied,
+        errors: match errors.is_empty() {
+            true => None,
+            false => Some(ErrFR::new("Some records or images were not exported").subs(errors)),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::types::{ImageSettings, TextCollectionSettings, TextSettings};
+
+    fn item(name: &str, value: SchemaAttrType) -> SchemaItem {
+        SchemaItem {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn renders_values_by_settings() {
+        let stars = item(
+            "rating",
+            SchemaAttrType::Number(NumberSettings {
+                max: Some(10.0),
+                stars_count: Some(5),
+                style: Some(NumberStyle::Stars),
+                ..NumberSettings::default()
+            }),
+        );
+        assert_eq!(
+            render_value(&AttrValue::Float(Some(7.0)), &stars, ASSETS_DIR),
+            Some(r#"<span class="stars" title="7">★★★★☆</span>"#.to_string())
+        );
+
+        let tags = item(
+            "tags",
+            SchemaAttrType::TextCollection(TextCollectionSettings {
+                prefix: Some("#".to_string()),
+                ..TextCollectionSettings::default()
+            }),
+        );
+        assert_eq!(
+            render_value(
+                &AttrValue::StringVec(Some(vec!["<b>".to_string()])),
+                &tags,
+                ASSETS_DIR
+            ),
+            Some(r#"<ul class="tags"><li>#&lt;b&gt;</li></ul>"#.to_string())
+        );
+
+        let cover = item("cover", SchemaAttrType::Image(ImageSettings::default()));
+        assert_eq!(
+            render_value(
+                &AttrValue::String(Some("a \"b\".png".to_string())),
+                &cover,
+                ASSETS_DIR
+            ),
+            Some(r#"<img src="assets/a%20%22b%22.png" alt="cover" loading="lazy">"#.to_string())
+        );
+
+        let text = item("title", SchemaAttrType::Text(TextSettings::default()));
+        assert_eq!(
+            render_value(&AttrValue::String(None), &text, ASSETS_DIR),
+            None
+        );
+    }
+
+    #[test]
+    fn markdown_does_not_pass_raw_html() {
+        let record = RecordFromDb {
+            path: Some("books/Dune.md".to_string()),
+            markdown: Some(
+                "**bold** <script>alert(1)</script> ![](../.assets/a%20b.png)".to_string(),
+            ),
+            ..RecordFromDb::default()
+        };
+        let body = render_body(&record).unwrap();
+        assert!(body.html.contains("<strong>bold</strong>"));
+        assert!(!body.html.contains("<script>"));
+        assert!(body.html.contains(r#"src="../assets/a%20b.png""#));
+        assert_eq!(body.assets, vec!["a b.png"]);
+    }
+
+    #[test]
+    fn slugs() {
+        assert_eq!(
+            slug("How to Read a Book: Guide"),
+            "how-to-read-a-book-guide"
+        );
+        assert_eq!(slug("???"), "record");
+    }
+}
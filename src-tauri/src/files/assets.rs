@@ -0,0 +1,366 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::query::{get_files_abstract, get_unmanaged_files_by_path};
+use crate::core::core_state::AppContext;
+use crate::files::markdown::encode_uri_component;
+use crate::files::trash::{delete_many_to_trash, TrashedRecord};
+use crate::files::utils::get_content_hash;
+use crate::schema::types::{AttrValue, SchemaAttrType};
+use crate::utils::errorhandling::ErrFR;
+
+/*
+    Folder in root path where all images are stored.
+    Image attribute values are file names inside of it.
+*/
+pub const ASSETS_FOLDER_NAME: &str = ".assets";
+
+// Part of hash that goes into file name, enough to not collide in a single vault
+const HASH_IN_NAME_LENGTH: usize = 12;
+const MAX_NAME_LENGTH: usize = 48;
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+#[serde(tag = "type", content = "value")]
+pub enum AssetSource {
+    // Absolute path to file anywhere on disk
+    Path(String),
+    // Frontend sends bytes as raw request body with c_import_image_bytes, json array is too slow for images
+    Bytes(Vec<u8>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct AssetImportResult {
+    /* Relative to assets folder, this is the value for Image attributes */
+    pub path: String,
+    /* Same image was already imported before, nothing was written */
+    pub existing: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct BrokenAssetReference {
+    /* Record path relative to root path */
+    pub path: String,
+    pub attribute: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct AssetReport {
+    /* Files in assets folder that no record uses */
+    pub orphaned: Vec<String>,
+    /* Image values that point to files that do not exist */
+    pub missing: Vec<BrokenAssetReference>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct AssetCleanupResult {
+    pub trashed: Vec<TrashedRecord>,
+    /* One sub error per asset that was not trashed */
+    pub errors: Option<ErrFR>,
+}
+
+/* Returns extension for supported image formats, based on content and not on file name */
+pub fn detect_image_extension(bytes: &[u8]) -> Option<&'static str> {
+    infer::get(bytes)
+        .filter(|t| t.matcher_type() == infer::MatcherType::Image)
+        .map(|t| t.extension())
+}
+
+/* Makes file system and url friendly name out of record name */
+pub fn asset_name_base(desired_name: &str) -> String {
+    let mut result = String::new();
+
+    for c in desired_name.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            result.push(c);
+        } else if !result.is_empty() && !result.ends_with('-') {
+            result.push('-');
+        }
+
+        if result.chars().count() >= MAX_NAME_LENGTH {
+            break;
+        }
+    }
+
+    let trimmed = result.trim_end_matches('-');
+    match trimmed.is_empty() {
+        true => "image".to_string(),
+        false => trimmed.to_string(),
+    }
+}
+
+pub async fn get_assets_folder(ctx: &AppContext) -> Result<PathBuf, Box<ErrFR>> {
+    Ok(ctx.root_path_as_buf().await?.join(ASSETS_FOLDER_NAME))
+}
+
+/*
+    Name ends with part of content hash, so looking for a file with the same ending is enough to find duplicates.
+*/
+fn find_existing(folder: &Path, hash: &str, extension: &str) -> Option<String> {
+    let ending = format!("-{}.{}", &hash[..HASH_IN_NAME_LENGTH], extension);
+
+    fs::read_dir(folder)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.file_name()
+                    .is_some_and(|n| n.to_string_lossy().ends_with(&ending))
+        })
+        .find(|p| fs::read(p).is_ok_and(|b| get_content_hash(&b) == hash))
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+}
+
+pub async fn import_asset(
+    ctx: &AppContext,
+    source: AssetSource,
+    desired_name: &str,
+) -> Result<AssetImportResult, Box<ErrFR>> {
+    let bytes = match source {
+        AssetSource::Bytes(b) => b,
+        AssetSource::Path(p) => fs::read(&p)
+            .map_err(|e| Box::new(ErrFR::new("Unable to read image").info(&p).raw(e)))?,
+    };
+
+    let extension = detect_image_extension(&bytes).ok_or(Box::new(
+        ErrFR::new("Unsupported image").info("File is not an image or format is not recognized"),
+    ))?;
+
+    let hash = get_content_hash(&bytes);
+    let folder = get_assets_folder(ctx).await?;
+
+    if let Some(existing) = find_existing(&folder, &hash, extension) {
+        return Ok(AssetImportResult {
+            path: existing,
+            existing: true,
+        });
+    }
+
+    fs::create_dir_all(&folder).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error creating directory")
+                .info(&folder.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+
+    let file_name = format!(
+        "{}-{}.{}",
+        asset_name_base(desired_name),
+        &hash[..HASH_IN_NAME_LENGTH],
+        extension
+    );
+
+    fs::write(folder.join(&file_name), bytes).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error writing to disk")
+                .info("Image was not saved")
+                .raw(e),
+        )
+    })?;
+
+    Ok(AssetImportResult {
+        path: file_name,
+        existing: false,
+    })
+}
+
+/* Every Image attribute value in cached records, grouped with record and attribute */
+async fn get_asset_references(ctx: &AppContext) -> Result<Vec<BrokenAssetReference>, Box<ErrFR>> {
+    let records = get_files_abstract(ctx, "".to_string()).await?;
+    let schemas = ctx.schemas_cache.get_read_lock().await;
+
+    let mut result: Vec<BrokenAssetReference> = vec![];
+
+    for record in records {
+        let Some(path) = record.path else {
+            continue;
+        };
+        let Some(schema) = ctx
+            .schemas_cache
+            .get_schema_by_lock(&schemas, Path::new(&path))
+        else {
+            continue;
+        };
+
+        for item in schema.schema.items {
+            if !matches!(item.value, SchemaAttrType::Image(_)) {
+                continue;
+            }
+            if let Some(AttrValue::String(Some(value))) = record.attrs.get(&item.name) {
+                if !value.is_empty() {
+                    result.push(BrokenAssetReference {
+                        path: path.clone(),
+                        attribute: item.name.clone(),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/*
+    Cache only has attributes of records that were parsed. Assets can also be linked from markdown body,
+    from files without schema or from records with broken metadata, so raw text of every file is checked too.
+*/
+async fn remove_mentioned(
+    ctx: &AppContext,
+    candidates: Vec<String>,
+) -> Result<Vec<String>, Box<ErrFR>> {
+    if candidates.is_empty() {
+        return Ok(candidates);
+    }
+
+    let mut paths: Vec<String> = get_files_abstract(ctx, "".to_string())
+        .await?
+        .into_iter()
+        .filter_map(|r| r.path)
+        .collect();
+    paths.extend(
+        get_unmanaged_files_by_path(ctx, Path::new(""))
+            .await?
+            .into_iter()
+            .map(|r| r.path),
+    );
+
+    let mut remaining = candidates;
+    for path in paths {
+        let Ok(content) =
+            fs::read_to_string(ctx.relative_path_to_absolute(Path::new(&path)).await?)
+        else {
+            continue;
+        };
+        remaining.retain(|name| {
+            !content.contains(name.as_str()) && !content.contains(&encode_uri_component(name))
+        });
+        if remaining.is_empty() {
+            break;
+        }
+    }
+
+    Ok(remaining)
+}
+
+pub async fn get_asset_report(ctx: &AppContext) -> Result<AssetReport, Box<ErrFR>> {
+    if ctx.is_indexing().await {
+        return Err(Box::new(
+            ErrFR::new("Files are still being indexed").info("Try again when indexing is finished"),
+        ));
+    }
+
+    let folder = get_assets_folder(ctx).await?;
+    let references = get_asset_references(ctx).await?;
+
+    let used: HashSet<&str> = references.iter().map(|r| r.value.as_str()).collect();
+
+    let candidates: Vec<String> = match fs::read_dir(&folder) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|name| !name.starts_with('.') && !used.contains(name.as_str()))
+            .collect(),
+        Err(_) => vec![],
+    };
+    let mut orphaned = remove_mentioned(ctx, candidates).await?;
+    orphaned.sort();
+
+    let missing = references
+        .iter()
+        .filter(|r| !folder.join(&r.value).is_file())
+        .cloned()
+        .collect();
+
+    Ok(AssetReport { orphaned, missing })
+}
+
+/*
+    Only files that are still orphaned at the moment of the call are trashed,
+    in case something started using them after report was made.
+*/
+pub async fn trash_orphaned_assets(
+    ctx: &AppContext,
+    names: Vec<String>,
+) -> Result<AssetCleanupResult, Box<ErrFR>> {
+    let report = get_asset_report(ctx).await?;
+    let orphaned: HashSet<&String> = report.orphaned.iter().collect();
+
+    let mut trashed: Vec<TrashedRecord> = vec![];
+    let mut err = ErrFR::new("Some assets were not moved to trash");
+
+    let mut to_trash: Vec<&String> = vec![];
+    for name in names.iter() {
+        match orphaned.contains(name) {
+            true => to_trash.push(name),
+            false => err = err.sub(ErrFR::new("Asset is in use or does not exist").info(name)),
+        }
+    }
+
+    let paths: Vec<PathBuf> = to_trash
+        .iter()
+        .map(|name| Path::new(ASSETS_FOLDER_NAME).join(name))
+        .collect();
+
+    for (name, result) in to_trash.iter().zip(delete_many_to_trash(ctx, &paths).await) {
+        match result {
+            Ok(Some(r)) => trashed.push(r),
+            Ok(None) => (),
+            Err(e) => err = err.sub(e.info(name)),
+        }
+    }
+
+    Ok(AssetCleanupResult {
+        trashed,
+        errors: match err.sub_errors.is_empty() {
+            true => None,
+            false => Some(err),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_HEADER: [u8; 16] = [
+        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44,
+        0x52,
+    ];
+
+    #[test]
+    fn name_base() {
+        assert_eq!(
+            asset_name_base("The Name of the Wind"),
+            "the-name-of-the-wind"
+        );
+        assert_eq!(asset_name_base("  Dune: Part 2!  "), "dune-part-2");
+        assert_eq!(asset_name_base("???"), "image");
+        assert_eq!(asset_name_base(&"a".repeat(100)).len(), MAX_NAME_LENGTH);
+    }
+
+    #[test]
+    fn detects_format_by_content() {
+        assert_eq!(detect_image_extension(&PNG_HEADER), Some("png"));
+        assert_eq!(
+            detect_image_extension(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some("jpg")
+        );
+        assert_eq!(
+            detect_image_extension(b"---\ntitle: not an image\n---"),
+            None
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use std::mem::discriminant;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::cache_thing::cache_file;
+use crate::cache::query::get_files_by_path;
+use crate::core::core_state::AppContext;
+use crate::core::journal::{snapshot_file, JournalAction};
+use crate::emitter::FileEventDataExisting;
+use crate::files::metadata::get_default_metadata;
+use crate::files::read_save::{read_file_by_path, update_attributes, FileReadMode};
+use crate::schema::types::{AttrValue, SchemaAttrType};
+use crate::utils::errorhandling::ErrFR;
+use crate::utils::helpers::normalize_path_to_os;
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+#[serde(tag = "type", content = "value")]
+pub enum BatchOperation {
+    Set(AttrValue),
+    // Resets to empty value of attribute type
+    Clear,
+    AddToCollection(String),
+    RemoveFromCollection(String),
+    // Works for both single text values and collections
+    RenameValue { from: String, to: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+#[serde(tag = "type", content = "value")]
+pub enum BatchTarget {
+    Paths(Vec<String>),
+    // Every record returned by get_files_by_path for this folder
+    Folder(String),
+    // Records of folder where attribute equals value, or collection contains it. Empty value matches empty attributes
+    Query {
+        folder: String,
+        attribute: String,
+        value: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct BatchEditResult {
+    pub dry_run: bool,
+    /* Paths that were changed, or would be changed on dry run */
+    pub changed: Vec<String>,
+    pub unchanged: Vec<String>,
+    /* One sub error per failed file */
+    pub errors: Option<ErrFR>,
+}
+
+pub struct BatchEditOutput {
+    pub result: BatchEditResult,
+    pub events: Vec<FileEventDataExisting>,
+    /* Content of changed files before edit */
+    pub undo: Vec<JournalAction>,
+}
+
+/*
+   Returns None when operation does not change value.
+*/
+fn apply_operation(
+    current: &AttrValue,
+    schema_type: &SchemaAttrType,
+    operation: &BatchOperation,
+) -> Result<Option<AttrValue>, Box<ErrFR>> {
+    let new_value = match (operation, current) {
+        (BatchOperation::Set(v), _) => {
+            if discriminant(v) != discriminant(&get_default_metadata(schema_type.clone())) {
+                return Err(Box::new(ErrFR::new(
+                    "Value type does not match attribute type",
+                )));
+            }
+            v.clone()
+        }
+        (BatchOperation::Clear, _) => get_default_metadata(schema_type.clone()),
+        (BatchOperation::AddToCollection(item), AttrValue::StringVec(v)) => {
+            let mut items = v.clone().unwrap_or_default();
+            if !items.contains(item) {
+                items.push(item.clone());
+            }
+            AttrValue::StringVec(Some(items))
+        }
+        (BatchOperation::RemoveFromCollection(item), AttrValue::StringVec(v)) => {
+            let items: Vec<String> = v
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|i| i != item)
+                .collect();
+            AttrValue::StringVec(match items.len() {
+                0 => None,
+                _ => Some(items),
+            })
+        }
+        (BatchOperation::RenameValue { from, to }, AttrValue::String(Some(v))) => {
+            AttrValue::String(Some(match v == from {
+                true => to.clone(),
+                false => v.clone(),
+            }))
+        }
+        (BatchOperation::RenameValue { from, to }, AttrValue::StringVec(Some(v))) => {
+            let mut items: Vec<String> = vec![];
+            for i in v {
+                let renamed = match i == from {
+                    true => to.clone(),
+                    false => i.clone(),
+                };
+                if !items.contains(&renamed) {
+                    items.push(renamed);
+                }
+            }
+            AttrValue::StringVec(Some(items))
+        }
+        (BatchOperation::RenameValue { .. }, AttrValue::String(None))
+        | (BatchOperation::RenameValue { .. }, AttrValue::StringVec(None)) => return Ok(None),
+        (_, _) => {
+            return Err(Box::new(ErrFR::new(
+                "Operation is not supported for this attribute type",
+            )));
+        }
+    };
+
+    match &new_value == current {
+        true => Ok(None),
+        false => Ok(Some(new_value)),
+    }
+}
+
+pub async fn resolve_target(
+    ctx: &AppContext,
+    target: BatchTarget,
+) -> Result<Vec<PathBuf>, Box<ErrFR>> {
+    match target {
+        BatchTarget::Paths(paths) => Ok(paths.iter().map(|p| normalize_path_to_os(p)).collect()),
+        BatchTarget::Folder(folder) => Ok(get_files_by_path(ctx, &normalize_path_to_os(&folder))
+            .await?
+            .records
+            .into_iter()
+            .filter_map(|r| r.path.map(PathBuf::from))
+            .collect()),
+        BatchTarget::Query {
+            folder,
+            attribute,
+            value,
+        } => Ok(get_files_by_path(ctx, &normalize_path_to_os(&folder))
+            .await?
+            .records
+            .into_iter()
+            .filter(|r| matches_query(r.attrs.get(&attribute), &value))
+            .filter_map(|r| r.path.map(PathBuf::from))
+            .collect()),
+    }
+}
+
+fn matches_query(current: Option<&AttrValue>, value: &str) -> bool {
+    let value = value.trim();
+    let values: Vec<String> = match current {
+        Some(AttrValue::String(Some(v))) => vec![v.clone()],
+        Some(AttrValue::StringVec(Some(v))) => v.clone(),
+        Some(AttrValue::Integer(Some(v))) | Some(AttrValue::Float(Some(v))) => vec![v.to_string()],
+        _ => vec![],
+    };
+    let values: Vec<&str> = values
+        .iter()
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .collect();
+
+    match value.is_empty() {
+        true => values.is_empty(),
+        false => values.contains(&value),
+    }
+}
+
+async fn batch_edit_file(
+    ctx: &AppContext,
+    path_relative: &Path,
+    attribute: &str,
+    operation: &BatchOperation,
+    dry_run: bool,
+    undo: &mut Vec<JournalAction>,
+) -> Result<Option<FileEventDataExisting>, Box<ErrFR>> {
+    let file = read_file_by_path(ctx, path_relative, FileReadMode::OnlyMeta).await?;
+
+    let schema_item = file
+        .schema
+        .schema
+        .items
+        .iter()
+        .find(|i| i.name == attribute)
+        .ok_or(Box::new(
+            ErrFR::new("Attribute is not in schema").raw(attribute),
+        ))?;
+
+    let current = file
+        .record
+        .attrs
+        .get(attribute)
+        .cloned()
+        .unwrap_or(get_default_metadata(schema_item.value.clone()));
+
+    let new_value = match apply_operation(&current, &schema_item.value, operation)? {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let path_string = path_relative.to_string_lossy().to_string();
+
+    if dry_run {
+        let mut record = file.record;
+        record.attrs.insert(attribute.to_string(), new_value);
+        return Ok(Some(FileEventDataExisting {
+            path: path_string,
+            record,
+            schema: file.schema.location,
+        }));
+    }
+
+    let snapshot = snapshot_file(ctx, path_relative).await;
+
+    update_attributes(
+        ctx,
+        path_relative,
+        HashMap::from([(attribute.to_string(), new_value)]),
+        file.record.hash,
+        file.record.modified,
+        false,
+    )
+    .await?;
+
+    undo.push(snapshot);
+
+    // Caching right away means watcher will see the same hash and won't send event for every file
+    let record = cache_file(ctx, &ctx.relative_path_to_absolute(path_relative).await?).await?;
+
+    Ok(Some(FileEventDataExisting {
+        path: path_string,
+        record,
+        schema: file.schema.location,
+    }))
+}
+
+pub async fn batch_edit(
+    ctx: &AppContext,
+    target: BatchTarget,
+    attribute: &str,
+    operation: &BatchOperation,
+    dry_run: bool,
+) -> Result<BatchEditOutput, Box<ErrFR>> {
+    let paths = resolve_target(ctx, target).await?;
+
+    let mut changed: Vec<String> = vec![];
+    let mut unchanged: Vec<String> = vec![];
+    let mut events: Vec<FileEventDataExisting> = vec![];
+    let mut undo: Vec<JournalAction> = vec![];
+    let mut err = ErrFR::new("Some files were not updated");
+
+    for path in paths {
+        let path_string = path.to_string_lossy().to_string();
+        match batch_edit_file(ctx, &path, attribute, operation, dry_run, &mut undo).await {
+            Ok(Some(event)) => {
+                changed.push(path_string);
+                events.push(event);
+            }
+            Ok(None) => unchanged.push(path_string),
+            Err(e) => err = err.sub(e.info(&path_string)),
+        }
+    }
+
+    Ok(BatchEditOutput {
+        result: BatchEditResult {
+            dry_run,
+            changed,
+            unchanged,
+            errors: match err.sub_errors.is_empty() {
+                true => None,
+                false => Some(err),
+            },
+        },
+        events: match dry_run {
+            true => vec![],
+            false => events,
+        },
+        undo,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::types::{TextCollectionSettings, TextSettings};
+
+    use super::*;
+
+    fn tags() -> SchemaAttrType {
+        SchemaAttrType::TextCollection(TextCollectionSettings::default())
+    }
+
+    #[test]
+    fn add_and_remove_from_collection() {
+        let current = AttrValue::StringVec(Some(vec!["a".into()]));
+
+        let added = apply_operation(
+            &current,
+            &tags(),
+            &BatchOperation::AddToCollection("b".into()),
+        );
+        assert_eq!(
+            added.unwrap(),
+            Some(AttrValue::StringVec(Some(vec!["a".into(), "b".into()])))
+        );
+
+        let existing = apply_operation(
+            &current,
+            &tags(),
+            &BatchOperation::AddToCollection("a".into()),
+        );
+        assert_eq!(existing.unwrap(), None);
+
+        let removed = apply_operation(
+            &current,
+            &tags(),
+            &BatchOperation::RemoveFromCollection("a".into()),
+        );
+        assert_eq!(removed.unwrap(), Some(AttrValue::StringVec(None)));
+    }
+
+    #[test]
+    fn rename_value() {
+        let op = BatchOperation::RenameValue {
+            from: "scifi".into(),
+            to: "sci-fi".into(),
+        };
+
+        let vec = AttrValue::StringVec(Some(vec!["scifi".into(), "sci-fi".into()]));
+        assert_eq!(
+            apply_operation(&vec, &tags(), &op).unwrap(),
+            Some(AttrValue::StringVec(Some(vec!["sci-fi".into()])))
+        );
+
+        let text = SchemaAttrType::Text(TextSettings::default());
+        let single = AttrValue::String(Some("other".into()));
+        assert_eq!(apply_operation(&single, &text, &op).unwrap(), None);
+    }
+
+    #[test]
+    fn query_matching() {
+        let tags = AttrValue::StringVec(Some(vec!["a".into(), " b ".into()]));
+        assert!(matches_query(Some(&tags), "b"));
+        assert!(!matches_query(Some(&tags), "c"));
+        assert!(!matches_query(Some(&tags), ""));
+
+        assert!(matches_query(Some(&AttrValue::Integer(Some(3.0))), "3"));
+        assert!(matches_query(
+            Some(&AttrValue::String(Some(" ".into()))),
+            ""
+        ));
+        assert!(matches_query(None, ""));
+    }
+
+    #[test]
+    fn set_checks_type() {
+        let text = SchemaAttrType::Text(TextSettings::default());
+        let current = AttrValue::String(None);
+
+        assert!(apply_operation(
+            &current,
+            &text,
+            &BatchOperation::Set(AttrValue::Integer(Some(1.0)))
+        )
+        .is_err());
+
+        assert_eq!(
+            apply_operation(&current, &text, &BatchOperation::Clear).unwrap(),
+            None
+        );
+    }
+}
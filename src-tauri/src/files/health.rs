@@ -0,0 +1,864 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_yml::Value;
+use ts_rs::TS;
+use walkdir::WalkDir;
+
+use crate::core::core_state::AppContext;
+use crate::core::journal::{snapshot_file, JournalAction};
+use crate::files::assets::get_assets_folder;
+use crate::files::read_save::{update_attributes, update_front_matter};
+use crate::files::utils::{get_content_hash, split_front_matter};
+use crate::schema::schema_cache::locate_schema_and_folder;
+use crate::schema::types::{AttrValue, Schema, SchemaAttrType, SchemaItem};
+use crate::utils::errorhandling::ErrFR;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, TS, PartialEq, Eq, PartialOrd, Ord)]
+#[ts(export)]
+pub enum HealthSeverity {
+    // Data can be lost or is not visible in app
+    Error,
+    Warning,
+    Info,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, TS, PartialEq, Eq, PartialOrd, Ord)]
+#[ts(export)]
+pub enum HealthIssueKind {
+    InvalidSchema,
+    YamlParseError,
+    TypeMismatch,
+    DuplicateSchemaItem,
+    DatePairOrder,
+    BrokenImage,
+    UnknownKey,
+    RecordWithoutSchema,
+}
+
+impl HealthIssueKind {
+    fn severity(&self) -> HealthSeverity {
+        match self {
+            HealthIssueKind::InvalidSchema | HealthIssueKind::YamlParseError => {
+                HealthSeverity::Error
+            }
+            // Mismatched values are replaced with empty ones next time record is saved
+            HealthIssueKind::TypeMismatch => HealthSeverity::Error,
+            HealthIssueKind::DuplicateSchemaItem
+            | HealthIssueKind::DatePairOrder
+            | HealthIssueKind::BrokenImage => HealthSeverity::Warning,
+            HealthIssueKind::UnknownKey | HealthIssueKind::RecordWithoutSchema => {
+                HealthSeverity::Info
+            }
+        }
+    }
+}
+
+/*
+    Only fixes that can't lose data are offered. Fixes are recomputed from disk when applied.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+#[serde(tag = "type", content = "value")]
+pub enum HealthFix {
+    // Saves schema through regular path which drops empty and duplicate items, offered only when duplicates are copies of the first item
+    RemoveDuplicateSchemaItems {
+        schema_path: String,
+    },
+    // Converts value to attribute type when conversion is lossless, e.g. 2024 -> "2024"
+    CoerceValue {
+        path: String,
+        attribute: String,
+    },
+    SwapDatePair {
+        path: String,
+        attribute: String,
+        index: usize,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct HealthIssue {
+    /* Relative to root path, record or schema file */
+    pub path: String,
+    pub attribute: Option<String>,
+    pub message: String,
+    pub fix: Option<HealthFix>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct HealthIssueGroup {
+    pub kind: HealthIssueKind,
+    pub severity: HealthSeverity,
+    pub issues: Vec<HealthIssue>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct HealthReport {
+    /* Most severe first */
+    pub groups: Vec<HealthIssueGroup>,
+    pub checked_schemas: u32,
+    pub checked_records: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct HealthFixResult {
+    /* Paths of changed files */
+    pub fixed: Vec<String>,
+    /* One sub error per fix that was not applied */
+    pub errors: Option<ErrFR>,
+}
+
+pub struct HealthFixOutput {
+    pub result: HealthFixResult,
+    /* Content of changed files before fixes */
+    pub undo: Vec<JournalAction>,
+}
+
+#[derive(Default)]
+struct IssueCollector {
+    issues: BTreeMap<HealthIssueKind, Vec<HealthIssue>>,
+}
+
+impl IssueCollector {
+    fn add(
+        &mut self,
+        kind: HealthIssueKind,
+        path: &str,
+        attribute: Option<&str>,
+        message: &str,
+        fix: Option<HealthFix>,
+    ) {
+        self.issues.entry(kind).or_default().push(HealthIssue {
+            path: path.to_string(),
+            attribute: attribute.map(|a| a.to_string()),
+            message: message.to_string(),
+            fix,
+        });
+    }
+
+    fn into_groups(self) -> Vec<HealthIssueGroup> {
+        let mut groups: Vec<HealthIssueGroup> = self
+            .issues
+            .into_iter()
+            .map(|(kind, issues)| HealthIssueGroup {
+                kind,
+                severity: kind.severity(),
+                issues,
+            })
+            .collect();
+        groups.sort_by_key(|g| (g.severity, g.kind));
+        groups
+    }
+}
+
+fn type_name(schema_type: &SchemaAttrType) -> &'static str {
+    match schema_type {
+        SchemaAttrType::Text(_) => "text",
+        SchemaAttrType::Image(_) => "image",
+        SchemaAttrType::Date(_) => "date",
+        SchemaAttrType::Number(_) => "number",
+        SchemaAttrType::TextCollection(_) => "list of text",
+        SchemaAttrType::DateCollection(_) => "list of dates",
+        SchemaAttrType::DatesPairCollection(_) => "list of date ranges",
+    }
+}
+
+/* Mirrors what parse_metadata accepts, anything else is replaced with empty value */
+fn value_matches_type(value: &Value, schema_type: &SchemaAttrType) -> bool {
+    match (value, schema_type) {
+        (Value::Null, _) => true,
+        (
+            Value::String(_),
+            SchemaAttrType::Text(_) | SchemaAttrType::Image(_) | SchemaAttrType::Date(_),
+        ) => true,
+        (Value::Number(_), SchemaAttrType::Number(_)) => true,
+        (
+            Value::Sequence(s),
+            SchemaAttrType::TextCollection(_) | SchemaAttrType::DateCollection(_),
+        ) => s.iter().all(|v| v.is_string()),
+        (Value::Sequence(s), SchemaAttrType::DatesPairCollection(_)) => {
+            s.iter().all(|v| v.is_mapping())
+        }
+        _ => false,
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/* Returns None when there is no conversion that keeps the value as is */
+pub fn coerce_value(value: &Value, schema_type: &SchemaAttrType) -> Option<AttrValue> {
+    match (value, schema_type) {
+        (Value::Number(_) | Value::Bool(_), SchemaAttrType::Text(_)) => {
+            scalar_to_string(value).map(|s| AttrValue::String(Some(s)))
+        }
+        (Value::String(s), SchemaAttrType::Number(settings)) => {
+            let n = s.trim().parse::<f64>().ok()?;
+            match settings.decimal_places.is_some_and(|v| v > 0) {
+                true => Some(AttrValue::Float(Some(n))),
+                // Fraction would be rounded away by integer attribute
+                false if n.fract() != 0.0 => None,
+                false => Some(AttrValue::Integer(Some(n))),
+            }
+        }
+        (
+            Value::String(s),
+            SchemaAttrType::TextCollection(_) | SchemaAttrType::DateCollection(_),
+        ) => Some(AttrValue::StringVec(Some(vec![s.clone()]))),
+        (Value::Sequence(items), SchemaAttrType::TextCollection(_)) => items
+            .iter()
+            .map(scalar_to_string)
+            .collect::<Option<Vec<String>>>()
+            .map(|v| AttrValue::StringVec(Some(v))),
+        _ => None,
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/* Indexes of pairs where finished is before started */
+fn reversed_date_pairs(value: &Value) -> Vec<usize> {
+    let Some(pairs) = value.as_sequence() else {
+        return vec![];
+    };
+
+    pairs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, pair)| {
+            let started = pair.get("started")?.as_str().and_then(parse_date)?;
+            let finished = pair.get("finished")?.as_str().and_then(parse_date)?;
+            (finished < started).then_some(i)
+        })
+        .collect()
+}
+
+/*
+    Swaps dates of one range inside raw sequence. Index is counted over raw entries, like in
+    reversed_date_pairs, entries that app doesn't show and unknown keys are written back as they are.
+*/
+fn swap_date_pair(value: &mut Value, index: usize) -> Result<(), Box<ErrFR>> {
+    if !reversed_date_pairs(value).contains(&index) {
+        return Err(Box::new(ErrFR::new(
+            "Date range not found or is already in order",
+        )));
+    }
+
+    let pair = value
+        .as_sequence_mut()
+        .and_then(|s| s.get_mut(index))
+        .and_then(|p| p.as_mapping_mut())
+        .ok_or(Box::new(ErrFR::new("Date range not found")))?;
+    let started = pair.get("started").cloned().unwrap_or_default();
+    let finished = pair.get("finished").cloned().unwrap_or_default();
+    // Insert into existing key keeps position of the key
+    pair.insert(Value::from("started"), finished);
+    pair.insert(Value::from("finished"), started);
+    Ok(())
+}
+
+fn same_item(a: &SchemaItem, b: &SchemaItem) -> bool {
+    serde_yml::to_value(a).ok() == serde_yml::to_value(b).ok()
+}
+
+/* Removing duplicates keeps the first item, it only loses nothing when the rest are copies of it */
+fn duplicates_are_identical(schema: &Schema) -> bool {
+    let mut seen: HashMap<&str, &SchemaItem> = HashMap::new();
+    schema
+        .items
+        .iter()
+        .all(|item| match seen.get(item.name.as_str()) {
+            Some(first) => same_item(first, item),
+            None => {
+                seen.insert(&item.name, item);
+                true
+            }
+        })
+}
+
+fn check_schema_items(schema: &Schema, schema_path: &str, collector: &mut IssueCollector) {
+    let mut seen: HashMap<&str, &SchemaItem> = HashMap::new();
+    let mut has_problems = false;
+
+    for item in schema.items.iter() {
+        let name = item.name.trim();
+        if name.is_empty() {
+            has_problems = true;
+            collector.add(
+                HealthIssueKind::DuplicateSchemaItem,
+                schema_path,
+                None,
+                "Schema has an item with empty name",
+                None,
+            );
+        } else if let Some(first) = seen.get(item.name.as_str()) {
+            has_problems = true;
+            let message = match same_item(first, item) {
+                true => "Schema has more than one item with this name, only first one is used",
+                false => "Schema has more than one item with this name and different settings, only first one is used. Remove the one you don't need in schema editor",
+            };
+            collector.add(
+                HealthIssueKind::DuplicateSchemaItem,
+                schema_path,
+                Some(&item.name),
+                message,
+                None,
+            );
+        } else {
+            seen.insert(&item.name, item);
+        }
+    }
+
+    // Single fix for all problems in a schema, so it's attached to the last one
+    if has_problems && duplicates_are_identical(schema) {
+        if let Some(issues) = collector
+            .issues
+            .get_mut(&HealthIssueKind::DuplicateSchemaItem)
+        {
+            if let Some(last) = issues.last_mut() {
+                last.fix = Some(HealthFix::RemoveDuplicateSchemaItems {
+                    schema_path: schema_path.to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn check_record(
+    path: &str,
+    raw: &str,
+    schema: &Schema,
+    assets_folder: &Path,
+    collector: &mut IssueCollector,
+) {
+    let front_matter = match split_front_matter(raw) {
+        Some(v) => v.front_matter,
+        None => return,
+    };
+
+    if front_matter.trim().is_empty() {
+        return;
+    }
+
+    let meta: serde_yml::Mapping = match serde_yml::from_str(front_matter) {
+        Ok(v) => v,
+        Err(e) => {
+            collector.add(
+                HealthIssueKind::YamlParseError,
+                path,
+                None,
+                &format!(
+                    "Metadata can't be parsed and will be overridden on save: {}",
+                    e
+                ),
+                None,
+            );
+            return;
+        }
+    };
+
+    let schema_items: HashMap<&str, &SchemaAttrType> = schema
+        .items
+        .iter()
+        .map(|i| (i.name.as_str(), &i.value))
+        .collect();
+
+    for (key, value) in meta.iter() {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+
+        let Some(schema_type) = schema_items.get(key) else {
+            collector.add(
+                HealthIssueKind::UnknownKey,
+                path,
+                Some(key),
+                "Key is not in schema, it is kept in file but not shown in app",
+                None,
+            );
+            continue;
+        };
+
+        if !value_matches_type(value, schema_type) {
+            let fix = coerce_value(value, schema_type).map(|_| HealthFix::CoerceValue {
+                path: path.to_string(),
+                attribute: key.to_string(),
+            });
+            collector.add(
+                HealthIssueKind::TypeMismatch,
+                path,
+                Some(key),
+                &format!("Expected {}", type_name(schema_type)),
+                fix,
+            );
+            continue;
+        }
+
+        match schema_type {
+            SchemaAttrType::DatesPairCollection(_) => {
+                for index in reversed_date_pairs(value) {
+                    collector.add(
+                        HealthIssueKind::DatePairOrder,
+                        path,
+                        Some(key),
+                        &format!("Range {} finishes before it starts", index + 1),
+                        Some(HealthFix::SwapDatePair {
+                            path: path.to_string(),
+                            attribute: key.to_string(),
+                            index,
+                        }),
+                    );
+                }
+            }
+            SchemaAttrType::Image(_) => {
+                if let Some(image) = value.as_str().filter(|v| !v.is_empty()) {
+                    if !assets_folder.join(image).is_file() {
+                        collector.add(
+                            HealthIssueKind::BrokenImage,
+                            path,
+                            Some(key),
+                            &format!("Image {} does not exist", image),
+                            None,
+                        );
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn is_hidden(name: &str) -> bool {
+    name.starts_with('.')
+}
+
+pub async fn check_vault_health(ctx: &AppContext) -> Result<HealthReport, Box<ErrFR>> {
+    let root = ctx.root_path_as_buf().await?;
+    let assets_folder = get_assets_folder(ctx).await?;
+
+    let mut collector = IssueCollector::default();
+    let mut checked_schemas: u32 = 0;
+    let mut checked_records: u32 = 0;
+
+    let mut schema_files: Vec<PathBuf> = vec![];
+    let mut record_files: Vec<PathBuf> = vec![];
+
+    for entry in WalkDir::new(&root)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !is_hidden(&e.file_name().to_string_lossy())
+                || e.file_type().is_dir() && e.file_name() == ".asom"
+        })
+        .filter_map(Result::ok)
+    {
+        if !entry.file_type().is_dir()
+            && entry.path().parent().and_then(|p| p.file_name()) == Some(".asom".as_ref())
+        {
+            if entry.file_name() == "schema.yaml" {
+                schema_files.push(entry.into_path());
+            }
+        } else if entry.file_type().is_file() && entry.path().extension().is_some_and(|e| e == "md")
+        {
+            record_files.push(entry.into_path());
+        }
+    }
+
+    for schema_file in schema_files {
+        checked_schemas += 1;
+        let path = ctx
+            .absolute_path_to_relative(&schema_file)
+            .await?
+            .to_string_lossy()
+            .to_string();
+
+        let parsed = fs::read_to_string(&schema_file)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_yml::from_str::<Schema>(&c).map_err(|e| e.to_string()));
+
+        match parsed {
+            Ok(schema) => check_schema_items(&schema, &path, &mut collector),
+            Err(e) => collector.add(
+                HealthIssueKind::InvalidSchema,
+                &path,
+                None,
+                &format!(
+                    "Schema can't be read, records in this folder are hidden: {}",
+                    e
+                ),
+                None,
+            ),
+        }
+    }
+
+    for record_file in record_files {
+        checked_records += 1;
+        let path_relative = ctx.absolute_path_to_relative(&record_file).await?;
+        let path = path_relative.to_string_lossy().to_string();
+
+        let Some(schema) = ctx.schemas_cache.get_schema(&path_relative).await else {
+            collector.add(
+                HealthIssueKind::RecordWithoutSchema,
+                &path,
+                None,
+                "Folder has no schema, record is not shown in app",
+                None,
+            );
+            continue;
+        };
+
+        match fs::read_to_string(&record_file) {
+            Ok(raw) => check_record(&path, &raw, &schema.schema, &assets_folder, &mut collector),
+            Err(e) => collector.add(
+                HealthIssueKind::YamlParseError,
+                &path,
+                None,
+                &format!("File can't be read: {}", e),
+                None,
+            ),
+        }
+    }
+
+    Ok(HealthReport {
+        groups: collector.into_groups(),
+        checked_schemas,
+        checked_records,
+    })
+}
+
+/* Value with hash of the file it was read from, fix is written only if file is still the same */
+async fn read_raw_value(
+    ctx: &AppContext,
+    path_relative: &Path,
+    attribute: &str,
+) -> Result<(Value, String), Box<ErrFR>> {
+    let path_absolute = ctx.relative_path_to_absolute(path_relative).await?;
+    let raw = fs::read_to_string(&path_absolute)
+        .map_err(|e| Box::new(ErrFR::new("Error reading file").raw(e)))?;
+    let hash = get_content_hash(raw.as_bytes());
+
+    let front_matter = split_front_matter(&raw)
+        .map(|v| v.front_matter)
+        .unwrap_or("");
+    let meta: serde_yml::Mapping = serde_yml::from_str(front_matter)
+        .map_err(|e| Box::new(ErrFR::new("Unable to parse metadata").raw(e)))?;
+
+    meta.get(attribute)
+        .cloned()
+        .map(|v| (v, hash))
+        .ok_or(Box::new(ErrFR::new("Attribute not found").info(attribute)))
+}
+
+/* Returns changed file */
+async fn apply_fix(ctx: &AppContext, fix: &HealthFix) -> Result<PathBuf, Box<ErrFR>> {
+    match fix {
+        HealthFix::RemoveDuplicateSchemaItems { schema_path } => {
+            let path_relative = PathBuf::from(schema_path);
+            let (schema_file, _) =
+                locate_schema_and_folder(&ctx.relative_path_to_absolute(&path_relative).await?)?;
+            let content = fs::read_to_string(&schema_file)
+                .map_err(|e| Box::new(ErrFR::new("Error reading schema").raw(e)))?;
+            let schema: Schema = serde_yml::from_str(&content)
+                .map_err(|e| Box::new(ErrFR::new("Error parsing schema").raw(e)))?;
+            if !duplicates_are_identical(&schema) {
+                return Err(Box::new(
+                    ErrFR::new("Items with the same name have different settings")
+                        .info("Remove the one you don't need in schema editor"),
+                ));
+            }
+
+            ctx.schemas_cache
+                .save_schema(ctx, &path_relative, schema)
+                .await?;
+            Ok(path_relative)
+        }
+        HealthFix::CoerceValue { path, attribute } => {
+            let path_relative = PathBuf::from(path);
+            let schema = ctx.schemas_cache.get_schema_safe(&path_relative).await?;
+            let schema_type = schema
+                .schema
+                .items
+                .iter()
+                .find(|i| &i.name == attribute)
+                .map(|i| i.value.clone())
+                .ok_or(Box::new(
+                    ErrFR::new("Attribute is not in schema").info(attribute),
+                ))?;
+
+            let (value, hash) = read_raw_value(ctx, &path_relative, attribute).await?;
+            let coerced = coerce_value(&value, &schema_type).ok_or(Box::new(
+                ErrFR::new("Value can't be converted without losing data").info(attribute),
+            ))?;
+
+            update_attributes(
+                ctx,
+                &path_relative,
+                HashMap::from([(attribute.clone(), coerced)]),
+                Some(hash),
+                None,
+                false,
+            )
+            .await?;
+            Ok(path_relative)
+        }
+        HealthFix::SwapDatePair {
+            path,
+            attribute,
+            index,
+        } => {
+            let path_relative = PathBuf::from(path);
+            let (mut value, hash) = read_raw_value(ctx, &path_relative, attribute).await?;
+            swap_date_pair(&mut value, *index).map_err(|e| e.info(attribute))?;
+
+            update_front_matter(
+                ctx,
+                &path_relative,
+                vec![(attribute.clone(), value)],
+                Some(hash),
+                None,
+                false,
+            )
+            .await?;
+            Ok(path_relative)
+        }
+    }
+}
+
+fn fix_target(fix: &HealthFix) -> &str {
+    match fix {
+        HealthFix::RemoveDuplicateSchemaItems { schema_path } => schema_path,
+        HealthFix::CoerceValue { path, .. } | HealthFix::SwapDatePair { path, .. } => path,
+    }
+}
+
+pub async fn apply_health_fixes(
+    ctx: &AppContext,
+    fixes: Vec<HealthFix>,
+) -> Result<HealthFixOutput, Box<ErrFR>> {
+    let mut fixed: Vec<String> = vec![];
+    let mut undo: Vec<JournalAction> = vec![];
+    let mut snapshotted: HashSet<String> = HashSet::new();
+    let mut err = ErrFR::new("Some fixes were not applied");
+
+    for fix in fixes.iter() {
+        let target = fix_target(fix).to_string();
+
+        // Schema fix can point to folder, snapshot has to be of the file itself
+        let snapshot_path = match fix {
+            HealthFix::RemoveDuplicateSchemaItems { .. } => {
+                let absolute = ctx.relative_path_to_absolute(Path::new(&target)).await?;
+                ctx.absolute_path_to_relative(&locate_schema_and_folder(&absolute)?.0)
+                    .await?
+            }
+            _ => PathBuf::from(&target),
+        };
+
+        let snapshot = match snapshotted.contains(&target) {
+            true => None,
+            false => Some(snapshot_file(ctx, &snapshot_path).await),
+        };
+
+        match apply_fix(ctx, fix).await {
+            Ok(_) => {
+                if let Some(s) = snapshot {
+                    snapshotted.insert(target.clone());
+                    undo.push(s);
+                }
+                if !fixed.contains(&target) {
+                    fixed.push(target);
+                }
+            }
+            Err(e) => err = err.sub(e.info(&target)),
+        }
+    }
+
+    Ok(HealthFixOutput {
+        result: HealthFixResult {
+            fixed,
+            errors: match err.sub_errors.is_empty() {
+                true => None,
+                false => Some(err),
+            },
+        },
+        undo,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::types::{
+        DatesPairCollectionSettings, NumberSettings, SchemaItem, TextCollectionSettings,
+        TextSettings,
+    };
+
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema {
+            name: "test".to_owned(),
+            version: "1".to_owned(),
+            fill_api_search_from: None,
+            fill_from_filename: None,
+            items: vec![
+                SchemaItem {
+                    name: "title".to_owned(),
+                    value: SchemaAttrType::Text(TextSettings::default()),
+                },
+                SchemaItem {
+                    name: "year".to_owned(),
+                    value: SchemaAttrType::Number(NumberSettings::default()),
+                },
+                SchemaItem {
+                    name: "tags".to_owned(),
+                    value: SchemaAttrType::TextCollection(TextCollectionSettings::default()),
+                },
+                SchemaItem {
+                    name: "read".to_owned(),
+                    value: SchemaAttrType::DatesPairCollection(
+                        DatesPairCollectionSettings::default(),
+                    ),
+                },
+            ],
+        }
+    }
+
+    fn check(raw: &str) -> Vec<HealthIssueGroup> {
+        let mut collector = IssueCollector::default();
+        check_record(
+            "a.md",
+            raw,
+            &schema(),
+            Path::new("/nowhere"),
+            &mut collector,
+        );
+        collector.into_groups()
+    }
+
+    #[test]
+    fn valid_record_has_no_issues() {
+        let raw = "---\ntitle: Dune\nyear: 1965\ntags:\n  - scifi\nread:\n  - started: 2024-01-01\n    finished: 2024-02-01\n---\nbody";
+        assert!(check(raw).is_empty());
+    }
+
+    #[test]
+    fn reports_grouped_by_severity() {
+        let raw = "---\ntitle: 1984\nyear: soon\nisbn: '123'\nread:\n  - started: 2024-02-01\n    finished: 2024-01-01\n---\n";
+        let groups = check(raw);
+
+        let kinds: Vec<HealthIssueKind> = groups.iter().map(|g| g.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                HealthIssueKind::TypeMismatch,
+                HealthIssueKind::DatePairOrder,
+                HealthIssueKind::UnknownKey
+            ]
+        );
+
+        let mismatches = &groups[0].issues;
+        assert_eq!(mismatches.len(), 2);
+        // Number can be safely turned into text, but "soon" can't be a number
+        let title = mismatches
+            .iter()
+            .find(|i| i.attribute.as_deref() == Some("title"))
+            .unwrap();
+        assert!(title.fix.is_some());
+        let year = mismatches
+            .iter()
+            .find(|i| i.attribute.as_deref() == Some("year"))
+            .unwrap();
+        assert!(year.fix.is_none());
+    }
+
+    #[test]
+    fn coerce_keeps_fraction() {
+        let integer = SchemaAttrType::Number(NumberSettings::default());
+        let float = SchemaAttrType::Number(NumberSettings {
+            decimal_places: Some(1),
+            ..NumberSettings::default()
+        });
+        let value = Value::String("4.5".to_string());
+
+        assert_eq!(coerce_value(&value, &integer), None);
+        assert_eq!(
+            coerce_value(&value, &float),
+            Some(AttrValue::Float(Some(4.5)))
+        );
+        assert_eq!(
+            coerce_value(&Value::String(" 1942 ".to_string()), &integer),
+            Some(AttrValue::Integer(Some(1942.0)))
+        );
+    }
+
+    #[test]
+    fn yaml_error_stops_other_checks() {
+        let groups = check("---\ntitle: [unclosed\n---\n");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].kind, HealthIssueKind::YamlParseError);
+        assert_eq!(groups[0].severity, HealthSeverity::Error);
+    }
+
+    #[test]
+    fn swap_keeps_entries_app_does_not_show() {
+        let mut value: Value = serde_yml::from_str(
+            "- note\n- {}\n- started: 2024-02-01\n  finished: 2024-01-01\n  place: home\n",
+        )
+        .unwrap();
+        assert_eq!(reversed_date_pairs(&value), vec![2]);
+
+        swap_date_pair(&mut value, 2).unwrap();
+        let expected: Value = serde_yml::from_str(
+            "- note\n- {}\n- started: 2024-01-01\n  finished: 2024-02-01\n  place: home\n",
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+
+        // Already in order, nothing to swap
+        assert!(swap_date_pair(&mut value, 2).is_err());
+    }
+
+    #[test]
+    fn duplicate_schema_items_get_single_fix() {
+        let mut s = schema();
+        s.items.push(s.items[0].clone());
+        s.items.push(s.items[1].clone());
+
+        let mut collector = IssueCollector::default();
+        check_schema_items(&s, "books", &mut collector);
+        let groups = collector.into_groups();
+
+        assert_eq!(groups[0].issues.len(), 2);
+        assert_eq!(
+            groups[0].issues.iter().filter(|i| i.fix.is_some()).count(),
+            1
+        );
+
+        // Removing a duplicate with other settings would lose them
+        let mut s = schema();
+        s.items.push(SchemaItem {
+            name: "title".to_owned(),
+            value: SchemaAttrType::TextCollection(TextCollectionSettings::default()),
+        });
+        let mut collector = IssueCollector::default();
+        check_schema_items(&s, "books", &mut collector);
+        let groups = collector.into_groups();
+        assert_eq!(groups[0].issues.len(), 1);
+        assert!(groups[0].issues[0].fix.is_none());
+    }
+}
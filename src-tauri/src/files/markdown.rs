@@ -0,0 +1,334 @@
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::core::core_state::AppContext;
+use crate::files::assets::{get_assets_folder, ASSETS_FOLDER_NAME};
+use crate::files::read_save::{read_file_by_path, FileReadMode};
+use crate::utils::errorhandling::ErrFR;
+
+const SNIPPET_LENGTH: usize = 200;
+const LINK_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+const IMAGE_SCHEMES: [&str; 2] = ["http", "https"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+pub struct RenderedMarkdown {
+    pub html: String,
+    /* Plain text from the start of body, for lists and search results */
+    pub snippet: String,
+    /* File names in assets folder that body refers to */
+    pub assets: Vec<String>,
+}
+
+pub struct MarkdownRenderOptions<'a> {
+    /* Folder of the record relative to root path, relative links are resolved from it */
+    pub record_folder: &'a Path,
+    /* Makes URL that rendered page can load asset file from */
+    pub asset_url: &'a dyn Fn(&str) -> String,
+}
+
+fn parser_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+}
+
+/* Same escaping as encodeURIComponent */
+pub fn encode_uri_component(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'!'
+            | b'~'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => result.push(b as char),
+            b => result.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    result
+}
+
+pub fn decode_percent(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut result: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = match bytes[i] == b'%' {
+            true => value
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok()),
+            false => None,
+        };
+        match hex {
+            Some(b) => {
+                result.push(b);
+                i += 3;
+            }
+            None => {
+                result.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&result).to_string()
+}
+
+/* Same URL as convertFileSrc gives on frontend, for files that webview loads through asset protocol */
+pub fn asset_protocol_url(path_absolute: &Path) -> String {
+    let encoded = encode_uri_component(&path_absolute.to_string_lossy());
+    match cfg!(any(windows, target_os = "android")) {
+        true => format!("http://asset.localhost/{}", encoded),
+        false => format!("asset://localhost/{}", encoded),
+    }
+}
+
+/* None for relative URLs */
+fn url_scheme(url: &str) -> Option<String> {
+    let end = url.find([':', '/', '?', '#'])?;
+    match url[end..].starts_with(':') {
+        true => Some(url[..end].trim().to_lowercase()),
+        false => None,
+    }
+}
+
+/* Links to javascript: and similar are dropped, text of link stays */
+fn is_allowed_url(url: &str, schemes: &[&str]) -> bool {
+    match url_scheme(url) {
+        Some(scheme) => schemes.contains(&scheme.as_str()),
+        None => true,
+    }
+}
+
+/* Relative link from record folder, normalized. None when it leaves root path */
+fn resolve_relative(record_folder: &Path, url: &str) -> Option<PathBuf> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let joined = match path.starts_with('/') {
+        true => PathBuf::from(decode_percent(path.trim_start_matches('/'))),
+        false => record_folder.join(decode_percent(path)),
+    };
+
+    let mut result = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::Normal(v) => result.push(v),
+            Component::ParentDir => {
+                if !result.pop() {
+                    return None;
+                }
+            }
+            Component::CurDir => (),
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(result)
+}
+
+/* File name in assets folder when link points there */
+fn asset_name(record_folder: &Path, url: &str) -> Option<String> {
+    if url_scheme(url).is_some() {
+        return None;
+    }
+    let resolved = resolve_relative(record_folder, url)?;
+    let mut components = resolved.components();
+
+    match (components.next(), components.next(), components.next()) {
+        (Some(Component::Normal(folder)), Some(Component::Normal(name)), None)
+            if folder == ASSETS_FOLDER_NAME =>
+        {
+            Some(name.to_string_lossy().to_string())
+        }
+        _ => None,
+    }
+}
+
+/* Whitespace is collapsed, long text is cut on a char boundary */
+fn make_snippet(text: &str, max_chars: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match collapsed.chars().count() > max_chars {
+        true => format!(
+            "{}…",
+            collapsed
+                .chars()
+                .take(max_chars)
+                .collect::<String>()
+                .trim_end()
+        ),
+        false => collapsed,
+    }
+}
+
+/*
+    Renders body with GFM tables, task lists, strikethrough and footnotes.
+    Raw HTML is escaped rather than filtered, so nothing from notes can run in the page.
+*/
+pub fn render_markdown(markdown: &str, options: &MarkdownRenderOptions) -> RenderedMarkdown {
+    let mut assets: BTreeSet<String> = BTreeSet::new();
+    let mut text = String::new();
+
+    let events: Vec<Event> = Parser::new_ext(markdown, parser_options())
+        .map(|event| match event {
+            Event::Html(v) | Event::InlineHtml(v) => Event::Text(v),
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Link {
+                link_type,
+                dest_url: match is_allowed_url(&dest_url, &LINK_SCHEMES) {
+                    true => dest_url,
+                    false => CowStr::Borrowed(""),
+                },
+                title,
+                id,
+            }),
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => {
+                let dest_url = match asset_name(options.record_folder, &dest_url) {
+                    Some(name) => {
+                        let url = (options.asset_url)(&name);
+                        assets.insert(name);
+                        CowStr::from(url)
+                    }
+                    None if is_allowed_url(&dest_url, &IMAGE_SCHEMES) => dest_url,
+                    None => CowStr::Borrowed(""),
+                };
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                })
+            }
+            e => e,
+        })
+        .inspect(|event| match event {
+            Event::Text(v) | Event::Code(v) => text.push_str(v),
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item)
+            | Event::End(TagEnd::TableCell) => text.push(' '),
+            _ => (),
+        })
+        .collect();
+
+    let mut html = String::new();
+    html::push_html(&mut html, events.into_iter());
+
+    RenderedMarkdown {
+        html,
+        snippet: make_snippet(&text, SNIPPET_LENGTH),
+        assets: assets.into_iter().collect(),
+    }
+}
+
+/*
+    Preview of record body, images from assets folder are loaded by webview through asset protocol.
+    Unsaved body from editor can be passed instead of the one on disk.
+*/
+pub async fn render_record_markdown(
+    ctx: &AppContext,
+    path_relative: &Path,
+    markdown: Option<String>,
+) -> Result<RenderedMarkdown, Box<ErrFR>> {
+    let markdown = match markdown {
+        Some(v) => v,
+        None => read_file_by_path(ctx, path_relative, FileReadMode::FullFile)
+            .await?
+            .record
+            .markdown
+            .unwrap_or_default(),
+    };
+    let assets_folder = get_assets_folder(ctx).await?;
+
+    Ok(render_markdown(
+        &markdown,
+        &MarkdownRenderOptions {
+            record_folder: path_relative.parent().unwrap_or(Path::new("")),
+            asset_url: &|name| asset_protocol_url(&assets_folder.join(name)),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn render(markdown: &str) -> RenderedMarkdown {
+        render_markdown(
+            markdown,
+            &MarkdownRenderOptions {
+                record_folder: Path::new("books"),
+                asset_url: &|name| format!("assets/{}", encode_uri_component(name)),
+            },
+        )
+    }
+
+    /*
+        Every case is a pair of files, name.md is rendered and compared with name.html.
+        Run with UPDATE_GOLDEN=1 to write current output as expected.
+    */
+    #[test]
+    fn golden() {
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/golden/markdown");
+        let update = std::env::var("UPDATE_GOLDEN").is_ok();
+        let mut cases = 0;
+
+        for entry in fs::read_dir(&folder).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|e| e != "md") {
+                continue;
+            }
+
+            let rendered = render(&fs::read_to_string(&path).unwrap()).html;
+            let expected_path = path.with_extension("html");
+
+            if update {
+                fs::write(&expected_path, &rendered).unwrap();
+            }
+            let expected = fs::read_to_string(&expected_path).unwrap();
+            pretty_assertions::assert_eq!(expected, rendered, "{}", path.display());
+            cases += 1;
+        }
+
+        assert!(cases > 0);
+    }
+
+    #[test]
+    fn collects_assets_and_snippet() {
+        let rendered = render("# Dune\n\n![cover](../.assets/dune%20cover.png) Spice  must\nflow");
+        assert_eq!(rendered.assets, vec!["dune cover.png"]);
+        assert_eq!(rendered.snippet, "Dune cover Spice must flow");
+        assert!(rendered.html.contains(r#"src="assets/dune%20cover.png""#));
+    }
+
+    #[test]
+    fn resolves_relative_paths() {
+        assert_eq!(
+            asset_name(Path::new("books/sub"), "../../.assets/a.png"),
+            Some("a.png".to_string())
+        );
+        assert_eq!(asset_name(Path::new("books"), "../../.assets/a.png"), None);
+        assert_eq!(asset_name(Path::new("books"), "https://x.com/a.png"), None);
+        assert_eq!(make_snippet("aaaa bbbb", 6), "aaaa b…");
+    }
+}
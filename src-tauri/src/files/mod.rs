@@ -1,3 +1,10 @@
+pub mod assets;
+pub mod batch;
+pub mod health;
+pub mod markdown;
 pub mod metadata;
 pub mod read_save;
+pub mod thumbnails;
+pub mod trash;
+pub mod unmanaged;
 pub mod utils;
@@ -12,7 +12,10 @@ use crate::schema::types::{AttrValue, AttrValueOnDisk};
 use crate::utils::errorhandling::{ErrFR, ErrFRActionCode};
 
 use super::metadata::parse_metadata;
-use super::utils::{get_file_content, get_file_modified_time};
+use super::utils::{
+    get_content_hash, get_file_content_hash, get_file_modified_time, parse_file_content,
+    split_front_matter,
+};
 
 pub enum FileReadMode {
     OnlyMeta,
@@ -34,13 +37,40 @@ pub async fn read_file_by_path(
 ) -> Result<RecordReadResult, Box<ErrFR>> {
     let absolute_path = ctx.relative_path_to_absolute(path_relative).await?;
 
-    let file_modified = get_file_modified_time(&absolute_path).map_err(|e| {
-        ErrFR::new("Error reading get file modified time")
+    let file_modified = read_file_modified_time(&absolute_path)?;
+
+    // Hash is taken from the same bytes that are parsed, a write in between can't make them differ
+    let bytes = fs::read(&absolute_path).map_err(|e| {
+        ErrFR::new("Error reading file")
             .info(absolute_path.to_string_lossy().as_ref())
             .raw(e)
             .action_c(ErrFRActionCode::FileReadRetry, "Retry")
     })?;
 
+    read_file_from_bytes(ctx, path_relative, &bytes, file_modified, read_mode).await
+}
+
+pub fn read_file_modified_time(absolute_path: &Path) -> Result<i64, Box<ErrFR>> {
+    get_file_modified_time(absolute_path).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error reading get file modified time")
+                .info(absolute_path.to_string_lossy().as_ref())
+                .raw(e)
+                .action_c(ErrFRActionCode::FileReadRetry, "Retry"),
+        )
+    })
+}
+
+/* For callers that already have file content, record hash is computed from these bytes */
+pub async fn read_file_from_bytes(
+    ctx: &AppContext,
+    path_relative: &Path,
+    bytes: &[u8],
+    file_modified: i64,
+    read_mode: FileReadMode,
+) -> Result<RecordReadResult, Box<ErrFR>> {
+    let file_hash = get_content_hash(bytes);
+
     let files_schema = match ctx.schemas_cache.get_schema(path_relative).await {
         Some(v) => v,
         None => {
@@ -50,7 +80,7 @@ pub async fn read_file_by_path(
         }
     };
 
-    let content = get_file_content(&absolute_path, &read_mode);
+    let content = parse_file_content(bytes, &read_mode);
 
     match content {
         Ok(c) => {
@@ -64,6 +94,7 @@ pub async fn read_file_by_path(
                         FileReadMode::FullFile => Some(c.content),
                     },
                     modified: Some(file_modified),
+                    hash: Some(file_hash),
                     attrs: parsed_meta.metadata,
                 },
                 parsing_error: parsed_meta.parsing_error,
@@ -84,6 +115,43 @@ pub struct RecordSaveResult {
     pub path: String,
     #[ts(type = "number")]
     pub modified: i64, // UNIX milliseconds
+    pub hash: String,
+}
+
+/*
+    Compares record we got from frontend with file on disk.
+    Content hash is preferred, modified time is a fallback for records that were created without one.
+*/
+pub fn check_save_conflict(
+    path_absolute: &Path,
+    hash: Option<&String>,
+    modified: Option<i64>,
+) -> Result<(), Box<ErrFR>> {
+    let unable_to_check = |e: String| {
+        Box::new(
+            ErrFR::new("Unable to check file on disk for changes")
+                .info("Retry only if you are sure there is no important data in file on disk")
+                .action_c(ErrFRActionCode::FileSaveRetryForced, "Save anyway")
+                .raw(e),
+        )
+    };
+
+    let changed = match (hash, modified) {
+        (Some(hash), _) => get_file_content_hash(path_absolute).map_err(unable_to_check)? != *hash,
+        (None, Some(modified)) => {
+            get_file_modified_time(path_absolute).map_err(unable_to_check)? != modified
+        }
+        (None, None) => false,
+    };
+
+    if changed {
+        return Err(Box::new(
+            ErrFR::new("File was modified by something else")
+                .action_c(ErrFRActionCode::FileSaveRetryForced, "Overwrite"),
+        ));
+    }
+
+    Ok(())
 }
 
 pub async fn save_file(
@@ -92,8 +160,8 @@ pub async fn save_file(
     forced: bool,
     create_new: bool,
 ) -> Result<RecordSaveResult, Box<ErrFR>> {
-    let mut path = match record.path {
-        Some(v) => v,
+    let mut path = match &record.path {
+        Some(v) => v.clone(),
         None => {
             return Err(Box::new(ErrFR::new("No path in record").info(
                 "This is likely a frontend bug. Copy unsaved content and restart the app",
@@ -111,26 +179,7 @@ pub async fn save_file(
             .to_string_lossy()
             .to_string();
     } else if !forced {
-        if let Some(v) = record.modified {
-            let modified_before = match get_file_modified_time(&path_absolute) {
-                Ok(v) => v,
-                Err(e) => return Err(Box::new(
-                    ErrFR::new("Unable to get modified date from file on disk")
-                        .info(
-                            "Retry only if you are sure there is no important data in file on disk",
-                        )
-                        .action_c(ErrFRActionCode::FileSaveRetryForced, "Save anyway")
-                        .raw(e),
-                )),
-            };
-
-            if v != modified_before {
-                return Err(Box::new(
-                    ErrFR::new("File was modified by something else")
-                        .action_c(ErrFRActionCode::FileSaveRetryForced, "Overwrite"),
-                ));
-            }
-        }
+        check_save_conflict(&path_absolute, record.hash.as_ref(), record.modified)?;
     }
 
     let markdown = record.markdown.unwrap_or("".to_string());
@@ -143,6 +192,98 @@ pub async fn save_file(
         })?;
 
     let file = format!("---\n{yaml}---\n{markdown}");
+    let hash = get_content_hash(file.as_bytes());
+
+    fs::write(&path_absolute, file).map_err(|e| {
+        ErrFR::new("Error writing to disk")
+            .info("File was not saved")
+            .raw(e)
+            .action_c(ErrFRActionCode::FileSaveRetry, "Retry")
+    })?;
+
+    match get_file_modified_time(&path_absolute) {
+        Ok(v) => Ok(RecordSaveResult {
+            path,
+            modified: v,
+            hash,
+        }),
+        Err(e) => Err(Box::new(
+            ErrFR::new("Error getting update file modification date")
+                .info("File should be saved. Expect to get a warning next time you save this file")
+                .raw(e),
+        )),
+    }
+}
+
+/*
+    Writes only passed attributes into front matter of existing file.
+    Keys that are not in patch (including ones unknown to schema) and markdown body are left as is.
+*/
+pub async fn update_attributes(
+    ctx: &AppContext,
+    path_relative: &Path,
+    patch: HashMap<String, AttrValue>,
+    hash: Option<String>,
+    modified: Option<i64>,
+    forced: bool,
+) -> Result<RecordSaveResult, Box<ErrFR>> {
+    let patch = transform_attr_values_to_on_disk(patch)
+        .into_iter()
+        .map(|(key, value)| serde_yml::to_value(value).map(|v| (key, v)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ErrFR::new("Error serializing record metadata").raw(e))?;
+
+    update_front_matter(ctx, path_relative, patch, hash, modified, forced).await
+}
+
+/* Same as update_attributes for values that are already in their on disk form */
+pub async fn update_front_matter(
+    ctx: &AppContext,
+    path_relative: &Path,
+    patch: Vec<(String, serde_yml::Value)>,
+    hash: Option<String>,
+    modified: Option<i64>,
+    forced: bool,
+) -> Result<RecordSaveResult, Box<ErrFR>> {
+    let path_absolute = ctx.relative_path_to_absolute(path_relative).await?;
+
+    if !forced {
+        check_save_conflict(&path_absolute, hash.as_ref(), modified)?;
+    }
+
+    let raw = fs::read_to_string(&path_absolute).map_err(|e| {
+        ErrFR::new("Error reading file")
+            .info(&path_absolute.to_string_lossy())
+            .raw(e)
+            .action_c(ErrFRActionCode::FileReadRetry, "Retry")
+    })?;
+
+    let (before, front_matter, after) = match split_front_matter(&raw) {
+        Some(v) => (v.before, v.front_matter, v.after),
+        None => ("", "", raw.as_str()),
+    };
+
+    let mut meta: serde_yml::Mapping = match front_matter.trim().is_empty() {
+        true => serde_yml::Mapping::new(),
+        false => serde_yml::from_str(front_matter).map_err(|e| {
+            ErrFR::new("Unable to parse metadata")
+                .info("Attributes were not updated to avoid losing data in file")
+                .raw(e)
+        })?,
+    };
+
+    for (key, value) in patch {
+        meta.insert(serde_yml::Value::String(key), value);
+    }
+
+    let yaml = serde_yml::to_string(&meta).map_err(|e| {
+        ErrFR::new("Error serializing record metadata")
+            .info("File was not saved")
+            .raw(e)
+    })?;
+
+    let file = format!("{before}---\n{yaml}---\n{after}");
+    let new_hash = get_content_hash(file.as_bytes());
 
     fs::write(&path_absolute, file).map_err(|e| {
         ErrFR::new("Error writing to disk")
@@ -152,7 +293,11 @@ pub async fn save_file(
     })?;
 
     match get_file_modified_time(&path_absolute) {
-        Ok(v) => Ok(RecordSaveResult { path, modified: v }),
+        Ok(v) => Ok(RecordSaveResult {
+            path: path_relative.to_string_lossy().to_string(),
+            modified: v,
+            hash: new_hash,
+        }),
         Err(e) => Err(Box::new(
             ErrFR::new("Error getting update file modification date")
                 .info("File should be saved. Expect to get a warning next time you save this file")
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use ts_rs::TS;
+
+use crate::core::core_state::AppContext;
+use crate::files::assets::get_assets_folder;
+use crate::files::utils::get_content_hash;
+use crate::utils::errorhandling::ErrFR;
+
+pub const THUMBNAILS_FOLDER_NAME: &str = "thumbnails";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, TS, PartialEq)]
+#[ts(export)]
+pub enum ThumbnailSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl ThumbnailSize {
+    /* Longest side in pixels */
+    fn max_side(&self) -> u32 {
+        match self {
+            ThumbnailSize::Small => 128,
+            ThumbnailSize::Medium => 256,
+            ThumbnailSize::Large => 512,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ThumbnailSize::Small => "small",
+            ThumbnailSize::Medium => "medium",
+            ThumbnailSize::Large => "large",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SourceState {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: String,
+}
+
+/*
+    Thumbnails live in app cache directory and are named by hash of source image.
+    To avoid hashing source on every request we remember hash along with modification time and size.
+*/
+#[derive(Debug)]
+pub struct ThumbnailCache {
+    folder: Mutex<Option<PathBuf>>,
+    sources: Mutex<HashMap<PathBuf, SourceState>>,
+}
+
+fn thumbnail_candidates(folder: &Path, hash: &str, size: ThumbnailSize) -> [PathBuf; 2] {
+    let base = format!("{}-{}", hash, size.name());
+    [
+        folder.join(format!("{}.jpg", base)),
+        folder.join(format!("{}.png", base)),
+    ]
+}
+
+fn remove_thumbnails_for_hash(folder: &Path, hash: &str) {
+    for size in [
+        ThumbnailSize::Small,
+        ThumbnailSize::Medium,
+        ThumbnailSize::Large,
+    ] {
+        for p in thumbnail_candidates(folder, hash, size) {
+            if p.exists() {
+                if let Err(e) = fs::remove_file(&p) {
+                    log::warn!("thumbnails: unable to remove stale thumbnail {}", e);
+                }
+            }
+        }
+    }
+}
+
+/* Image values can only point inside of assets folder */
+fn is_safe_asset_path(asset_path: &str) -> bool {
+    let path = Path::new(asset_path);
+    !asset_path.is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/*
+    Images that have transparency are saved as png, everything else as jpg to keep thumbnails small.
+*/
+pub fn render_thumbnail(
+    bytes: &[u8],
+    size: ThumbnailSize,
+) -> Result<(Vec<u8>, &'static str), Box<ErrFR>> {
+    let img = image::load_from_memory(bytes)
+        .map_err(|e| Box::new(ErrFR::new("Unable to decode image").raw(e)))?;
+
+    let max = size.max_side();
+    let resized = match img.width() > max || img.height() > max {
+        true => img.thumbnail(max, max),
+        false => img,
+    };
+
+    let (encoded, format, extension) = match resized.color().has_alpha() {
+        true => (resized, ImageFormat::Png, "png"),
+        false => (
+            DynamicImage::ImageRgb8(resized.to_rgb8()),
+            ImageFormat::Jpeg,
+            "jpg",
+        ),
+    };
+
+    let mut result = std::io::Cursor::new(Vec::new());
+    encoded
+        .write_to(&mut result, format)
+        .map_err(|e| Box::new(ErrFR::new("Unable to encode thumbnail").raw(e)))?;
+
+    Ok((result.into_inner(), extension))
+}
+
+impl ThumbnailCache {
+    pub fn new() -> Self {
+        Self {
+            folder: Mutex::new(None),
+            sources: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn init(&self, cache_dir: &Path) {
+        *self.folder.lock().await = Some(cache_dir.join(THUMBNAILS_FOLDER_NAME));
+    }
+
+    /* Returns up to date hash of source, removes thumbnails of previous version when source changed */
+    async fn source_hash(&self, folder: &Path, source: &Path) -> Result<String, Box<ErrFR>> {
+        let meta = fs::metadata(source).map_err(|e| {
+            Box::new(
+                ErrFR::new("Image not found")
+                    .info(&source.to_string_lossy())
+                    .raw(e),
+            )
+        })?;
+
+        let modified = meta.modified().ok();
+        let len = meta.len();
+
+        let previous = self.sources.lock().await.get(source).cloned();
+
+        if let Some(state) = &previous {
+            if state.modified.is_some() && state.modified == modified && state.len == len {
+                return Ok(state.hash.clone());
+            }
+        }
+
+        // Lock is not held while reading, hashing a big image should not block other thumbnails
+        let bytes = fs::read(source).map_err(|e| {
+            Box::new(
+                ErrFR::new("Unable to read image")
+                    .info(&source.to_string_lossy())
+                    .raw(e),
+            )
+        })?;
+        let hash = get_content_hash(&bytes);
+
+        let mut sources = self.sources.lock().await;
+
+        if let Some(previous) = sources.get(source) {
+            if previous.hash != hash {
+                remove_thumbnails_for_hash(folder, &previous.hash);
+            }
+        }
+
+        sources.insert(
+            source.to_path_buf(),
+            SourceState {
+                modified,
+                len,
+                hash: hash.clone(),
+            },
+        );
+
+        Ok(hash)
+    }
+
+    /* `asset_path` is an Image attribute value. Returns absolute path to thumbnail. */
+    pub async fn get_thumbnail(
+        &self,
+        ctx: &AppContext,
+        asset_path: &str,
+        size: ThumbnailSize,
+    ) -> Result<PathBuf, Box<ErrFR>> {
+        let folder = self
+            .folder
+            .lock()
+            .await
+            .clone()
+            .ok_or(Box::new(ErrFR::new("Thumbnail cache is not initialized")))?;
+
+        if !is_safe_asset_path(asset_path) {
+            return Err(Box::new(ErrFR::new("Invalid image path").info(asset_path)));
+        }
+
+        let source = get_assets_folder(ctx).await?.join(asset_path);
+        let hash = self.source_hash(&folder, &source).await?;
+
+        if let Some(existing) = thumbnail_candidates(&folder, &hash, size)
+            .into_iter()
+            .find(|p| p.exists())
+        {
+            return Ok(existing);
+        }
+
+        let bytes = fs::read(&source).map_err(|e| {
+            Box::new(
+                ErrFR::new("Unable to read image")
+                    .info(&source.to_string_lossy())
+                    .raw(e),
+            )
+        })?;
+
+        let (thumbnail, extension) =
+            tauri::async_runtime::spawn_blocking(move || render_thumbnail(&bytes, size))
+                .await
+                .map_err(|e| Box::new(ErrFR::new("Thumbnail generation failed").raw(e)))??;
+
+        fs::create_dir_all(&folder).map_err(|e| {
+            Box::new(
+                ErrFR::new("Error creating directory")
+                    .info(&folder.to_string_lossy())
+                    .raw(e),
+            )
+        })?;
+
+        let target = folder.join(format!("{}-{}.{}", hash, size.name(), extension));
+        fs::write(&target, thumbnail)
+            .map_err(|e| Box::new(ErrFR::new("Error writing thumbnail").raw(e)))?;
+
+        Ok(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    use super::*;
+
+    fn encode(img: DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut result = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut result, format).unwrap();
+        result.into_inner()
+    }
+
+    #[test]
+    fn thumbnail_keeps_aspect_ratio() {
+        let source = encode(
+            DynamicImage::ImageRgb8(RgbImage::from_pixel(1000, 500, Rgb([10, 20, 30]))),
+            ImageFormat::Png,
+        );
+
+        let (bytes, extension) = render_thumbnail(&source, ThumbnailSize::Small).unwrap();
+        assert_eq!(extension, "jpg");
+
+        let thumb = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (128, 64));
+    }
+
+    #[test]
+    fn thumbnail_keeps_transparency_and_small_images() {
+        let source = encode(
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(50, 80, Rgba([0, 0, 0, 0]))),
+            ImageFormat::Png,
+        );
+
+        let (bytes, extension) = render_thumbnail(&source, ThumbnailSize::Medium).unwrap();
+        assert_eq!(extension, "png");
+
+        let thumb = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (50, 80));
+    }
+
+    #[test]
+    fn asset_path_stays_in_assets() {
+        assert!(is_safe_asset_path("cover-abc.jpg"));
+        assert!(!is_safe_asset_path("../secret.jpg"));
+        assert!(!is_safe_asset_path("/etc/passwd"));
+        assert!(!is_safe_asset_path(""));
+    }
+}
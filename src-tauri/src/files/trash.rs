@@ -0,0 +1,249 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::cache_thing::{cache_file, cache_files_folders_schemas};
+use crate::core::core_state::AppContext;
+use crate::utils::errorhandling::ErrFR;
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct TrashedRecord {
+    /* System specific id, pass it back to restore */
+    pub id: String,
+    /* Relative to root path, where item was before deletion */
+    pub path: String,
+    pub name: String,
+    #[ts(type = "number")]
+    pub deleted: i64, // UNIX seconds
+}
+
+pub async fn delete_to_trash(
+    ctx: &AppContext,
+    path_relative: &Path,
+) -> Result<Option<TrashedRecord>, Box<ErrFR>> {
+    delete_many_to_trash(ctx, &[path_relative.to_path_buf()])
+        .await
+        .pop()
+        .unwrap_or(Ok(None))
+}
+
+/*
+    Trash is listed once for the whole batch, items are found by original path and deletion time.
+    Result has one entry per path, in the same order.
+*/
+pub async fn delete_many_to_trash(
+    ctx: &AppContext,
+    paths_relative: &[PathBuf],
+) -> Vec<Result<Option<TrashedRecord>, Box<ErrFR>>> {
+    // Deletion time is stored in seconds, step back one so item deleted on the edge still matches
+    let since = chrono::Utc::now().timestamp() - 1;
+
+    let mut deleted: Vec<Result<(), Box<ErrFR>>> = vec![];
+    for path_relative in paths_relative {
+        deleted.push(match ctx.relative_path_to_absolute(path_relative).await {
+            Ok(absolute_path) => trash::delete(&absolute_path)
+                .map_err(|e| Box::new(ErrFR::new("Failed to delete file").raw(e))),
+            Err(e) => Err(e),
+        });
+    }
+
+    // Deletion itself already succeeded, so listing problems only mean there will be no undo
+    let mut found = match deleted.iter().any(|d| d.is_ok()) {
+        true => match list_trashed_records(ctx).await {
+            Ok(list) => match_trashed(list, since),
+            Err(e) => {
+                log::warn!("Unable to find trashed items after deletion: {:?}", e.title);
+                HashMap::new()
+            }
+        },
+        false => HashMap::new(),
+    };
+
+    paths_relative
+        .iter()
+        .zip(deleted)
+        .map(|(path, d)| d.map(|_| found.remove(path)))
+        .collect()
+}
+
+/* Most recent item for every original path, deleted not earlier than since */
+fn match_trashed(records: Vec<TrashedRecord>, since: i64) -> HashMap<PathBuf, TrashedRecord> {
+    let mut result: HashMap<PathBuf, TrashedRecord> = HashMap::new();
+
+    for record in records.into_iter().filter(|r| r.deleted >= since) {
+        let path = PathBuf::from(&record.path);
+        match result.get(&path) {
+            Some(existing) if existing.deleted >= record.deleted => (),
+            _ => {
+                result.insert(path, record);
+            }
+        }
+    }
+
+    result
+}
+
+/*
+    Listing and restoring is only implemented by trash crate on Windows and freedesktop platforms
+*/
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+mod platform {
+    use std::path::Path;
+
+    use trash::os_limited::{list, restore_all};
+    use trash::TrashItem;
+
+    use crate::utils::errorhandling::ErrFR;
+
+    pub fn list_items_in(root: &Path) -> Result<Vec<TrashItem>, Box<ErrFR>> {
+        let items = list().map_err(|e| Box::new(ErrFR::new("Unable to read trash").raw(e)))?;
+
+        Ok(items
+            .into_iter()
+            .filter(|i| i.original_path().starts_with(root))
+            .collect())
+    }
+
+    pub fn restore_item(item: TrashItem) -> Result<(), Box<ErrFR>> {
+        restore_all(vec![item]).map_err(|e| match e {
+            trash::Error::RestoreCollision { path, .. } => Box::new(
+                ErrFR::new("Unable to restore, file with the same name already exists")
+                    .info(&path.to_string_lossy()),
+            ),
+            e => Box::new(ErrFR::new("Unable to restore from trash").raw(e)),
+        })
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+mod platform {
+    use std::path::{Path, PathBuf};
+
+    use crate::utils::errorhandling::ErrFR;
+
+    pub struct TrashItem {
+        pub id: std::ffi::OsString,
+        pub name: std::ffi::OsString,
+        pub time_deleted: i64,
+    }
+
+    impl TrashItem {
+        pub fn original_path(&self) -> PathBuf {
+            PathBuf::new()
+        }
+    }
+
+    pub fn list_items_in(_root: &Path) -> Result<Vec<TrashItem>, Box<ErrFR>> {
+        Err(Box::new(ErrFR::new(
+            "Browsing trash is not supported on this platform",
+        )))
+    }
+
+    pub fn restore_item(_item: TrashItem) -> Result<(), Box<ErrFR>> {
+        Err(Box::new(ErrFR::new(
+            "Restoring from trash is not supported on this platform",
+        )))
+    }
+}
+
+/* Most recently deleted first */
+pub async fn list_trashed_records(ctx: &AppContext) -> Result<Vec<TrashedRecord>, Box<ErrFR>> {
+    let root = ctx.root_path_as_buf().await?;
+
+    let mut result: Vec<TrashedRecord> = vec![];
+    for item in platform::list_items_in(&root)? {
+        result.push(TrashedRecord {
+            id: item.id.to_string_lossy().to_string(),
+            path: ctx
+                .absolute_path_to_relative(&item.original_path())
+                .await?
+                .to_string_lossy()
+                .to_string(),
+            name: item.name.to_string_lossy().to_string(),
+            deleted: item.time_deleted,
+        });
+    }
+
+    result.sort_by_key(|r| Reverse(r.deleted));
+
+    Ok(result)
+}
+
+/* Returns relative path of restored item */
+pub async fn restore_from_trash(ctx: &AppContext, id: &str) -> Result<PathBuf, Box<ErrFR>> {
+    let root = ctx.root_path_as_buf().await?;
+
+    let item = platform::list_items_in(&root)?
+        .into_iter()
+        .find(|i| i.id.to_string_lossy() == id)
+        .ok_or(Box::new(
+            ErrFR::new("Item not found in trash").info("It might be restored or removed already"),
+        ))?;
+
+    let path_absolute = item.original_path();
+    let path_relative = ctx.absolute_path_to_relative(&path_absolute).await?;
+
+    platform::restore_item(item)?;
+
+    // Watcher will catch up too, but this way record is readable as soon as command returns
+    if path_absolute.is_dir() {
+        cache_files_folders_schemas(ctx, &path_absolute).await?;
+    } else if path_absolute.extension().is_some_and(|e| e == "md")
+        && ctx.schemas_cache.get_schema(&path_relative).await.is_some()
+    {
+        cache_file(ctx, &path_absolute).await?;
+    }
+
+    Ok(path_relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, path: &str, deleted: i64) -> TrashedRecord {
+        TrashedRecord {
+            id: id.to_string(),
+            path: path.to_string(),
+            name: path.to_string(),
+            deleted,
+        }
+    }
+
+    #[test]
+    fn matches_newest_item_per_path() {
+        let found = match_trashed(
+            vec![
+                record("old", "a.md", 10),
+                record("new", "a.md", 20),
+                record("other", "b.md", 21),
+                record("before", "c.md", 5),
+            ],
+            10,
+        );
+
+        assert_eq!(found.get(Path::new("a.md")).unwrap().id, "new");
+        assert_eq!(found.get(Path::new("b.md")).unwrap().id, "other");
+        assert!(!found.contains_key(Path::new("c.md")));
+    }
+}
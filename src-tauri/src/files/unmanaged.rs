@@ -0,0 +1,369 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_yml::Value;
+use ts_rs::TS;
+
+use crate::cache::cache_thing::cache_files_folders_schemas;
+use crate::cache::query::{get_unmanaged_files_by_path, UnmanagedRecord};
+use crate::core::core_state::AppContext;
+use crate::core::journal::JournalAction;
+use crate::files::utils::{
+    get_content_hash, get_file_modified_time, get_unique_path, split_front_matter,
+};
+use crate::schema::types::{
+    DateSettings, DatesPairCollectionSettings, NumberSettings, Schema, SchemaAttrType, SchemaItem,
+    TextCollectionSettings, TextSettings, SCHEMA_VERSION,
+};
+use crate::utils::errorhandling::{ErrFR, ErrFRActionCode};
+
+#[derive(Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct UnmanagedReadResult {
+    pub record: UnmanagedRecord,
+    /* Raw yaml between --- lines */
+    pub front_matter: String,
+    pub markdown: String,
+    pub parsing_error: Option<ErrFR>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct UnmanagedMoveResult {
+    /* New paths relative to root path, in the same order as moved files */
+    pub moved: Vec<String>,
+    /* One sub error per file that was not moved */
+    pub errors: Option<ErrFR>,
+}
+
+pub struct UnmanagedMoveOutput {
+    pub result: UnmanagedMoveResult,
+    pub undo: Vec<JournalAction>,
+}
+
+fn parse_front_matter(front_matter: &str) -> Result<serde_yml::Mapping, serde_yml::Error> {
+    match front_matter.trim().is_empty() {
+        true => Ok(serde_yml::Mapping::new()),
+        false => serde_yml::from_str(front_matter),
+    }
+}
+
+/* Reads file directly from disk, schema is not needed */
+pub fn read_unmanaged_record(
+    path_absolute: &Path,
+    path_relative: &Path,
+) -> Result<UnmanagedReadResult, Box<ErrFR>> {
+    let raw = fs::read_to_string(path_absolute).map_err(|e| {
+        ErrFR::new("Error reading file")
+            .info(&path_absolute.to_string_lossy())
+            .raw(e)
+            .action_c(ErrFRActionCode::FileReadRetry, "Retry")
+    })?;
+
+    let (front_matter, markdown) = match split_front_matter(&raw) {
+        Some(v) => (v.front_matter, v.after),
+        None => ("", raw.as_str()),
+    };
+
+    let (keys, parsing_error) = match parse_front_matter(front_matter) {
+        Ok(meta) => (
+            meta.keys()
+                .filter_map(|k| k.as_str().map(|s| s.to_string()))
+                .collect(),
+            None,
+        ),
+        Err(e) => (vec![], Some(ErrFR::new("Parsing error").raw(e))),
+    };
+
+    Ok(UnmanagedReadResult {
+        record: UnmanagedRecord {
+            path: path_relative.to_string_lossy().to_string(),
+            modified: get_file_modified_time(path_absolute).ok(),
+            hash: Some(get_content_hash(raw.as_bytes())),
+            keys,
+        },
+        front_matter: front_matter.to_string(),
+        markdown: markdown.to_string(),
+        parsing_error,
+    })
+}
+
+pub async fn read_unmanaged_file(
+    ctx: &AppContext,
+    path_relative: &Path,
+) -> Result<UnmanagedReadResult, Box<ErrFR>> {
+    if ctx.schemas_cache.get_schema(path_relative).await.is_some() {
+        return Err(Box::new(
+            ErrFR::new("Record has a schema")
+                .info("It should be opened as a regular record")
+                .raw(path_relative.to_string_lossy()),
+        ));
+    }
+
+    let path_absolute = ctx.relative_path_to_absolute(path_relative).await?;
+    read_unmanaged_record(&path_absolute, path_relative)
+}
+
+fn is_date(value: &Value) -> bool {
+    value
+        .as_str()
+        .is_some_and(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok())
+}
+
+fn is_date_pair(value: &Value) -> bool {
+    value
+        .as_mapping()
+        .is_some_and(|m| m.contains_key("started") || m.contains_key("finished"))
+}
+
+/*
+    Picks the most specific type that fits every value, falls back to text.
+*/
+pub fn infer_attr_type(values: &[&Value]) -> SchemaAttrType {
+    let values: Vec<&Value> = values.iter().copied().filter(|v| !v.is_null()).collect();
+
+    if values.is_empty() {
+        return SchemaAttrType::Text(TextSettings::default());
+    }
+
+    if values.iter().all(|v| v.is_number()) {
+        let has_fraction = values.iter().any(|v| v.is_f64());
+        return SchemaAttrType::Number(NumberSettings {
+            decimal_places: has_fraction.then_some(2),
+            ..NumberSettings::default()
+        });
+    }
+
+    if values.iter().all(|v| is_date(v)) {
+        return SchemaAttrType::Date(DateSettings::default());
+    }
+
+    let sequences: Option<Vec<&Vec<Value>>> = values.iter().map(|v| v.as_sequence()).collect();
+    if let Some(sequences) = sequences {
+        let items: Vec<&Value> = sequences.into_iter().flatten().collect();
+
+        if !items.is_empty() && items.iter().all(|v| is_date_pair(v)) {
+            return SchemaAttrType::DatesPairCollection(DatesPairCollectionSettings::default());
+        }
+
+        return SchemaAttrType::TextCollection(TextCollectionSettings::default());
+    }
+
+    SchemaAttrType::Text(TextSettings::default())
+}
+
+/*
+    Makes schema for folder out of frontmatter of unmanaged records in it.
+    After schema is saved, records in folder become regular ones.
+*/
+pub async fn create_schema_for_unmanaged(
+    ctx: &AppContext,
+    folder_relative: &Path,
+    name: Option<String>,
+) -> Result<Schema, Box<ErrFR>> {
+    if ctx
+        .schemas_cache
+        .get_schema(folder_relative)
+        .await
+        .is_some()
+    {
+        return Err(Box::new(
+            ErrFR::new("Folder already has a schema").raw(folder_relative.to_string_lossy()),
+        ));
+    }
+
+    let folder_absolute = ctx.relative_path_to_absolute(folder_relative).await?;
+
+    let mut keys: Vec<String> = vec![];
+    let mut metas: Vec<serde_yml::Mapping> = vec![];
+
+    for record in get_unmanaged_files_by_path(ctx, folder_relative).await? {
+        let path_absolute = ctx
+            .relative_path_to_absolute(Path::new(&record.path))
+            .await?;
+        let Ok(raw) = fs::read_to_string(&path_absolute) else {
+            continue;
+        };
+        let front_matter = split_front_matter(&raw)
+            .map(|v| v.front_matter)
+            .unwrap_or("");
+
+        if let Ok(meta) = parse_front_matter(front_matter) {
+            for key in meta.keys().filter_map(|k| k.as_str()) {
+                if !keys.iter().any(|k| k == key) {
+                    keys.push(key.to_string());
+                }
+            }
+            metas.push(meta);
+        }
+    }
+
+    let items = keys
+        .into_iter()
+        .map(|key| {
+            let values: Vec<&Value> = metas.iter().filter_map(|m| m.get(key.as_str())).collect();
+            SchemaItem {
+                value: infer_attr_type(&values),
+                name: key,
+            }
+        })
+        .collect();
+
+    let schema_name = name.unwrap_or(
+        folder_relative
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or("Records".to_string()),
+    );
+
+    let schema = ctx
+        .schemas_cache
+        .save_schema(
+            ctx,
+            folder_relative,
+            Schema {
+                name: schema_name,
+                version: SCHEMA_VERSION.to_string(),
+                fill_from_filename: None,
+                fill_api_search_from: None,
+                items,
+            },
+        )
+        .await?;
+
+    // Watcher will do the same, but this way records are readable as soon as command returns
+    cache_files_folders_schemas(ctx, &folder_absolute).await?;
+
+    Ok(schema)
+}
+
+/* Moves files into folder that has a schema, existing names are not overwritten */
+pub async fn move_unmanaged_to_folder(
+    ctx: &AppContext,
+    paths: Vec<String>,
+    folder_relative: &Path,
+) -> Result<UnmanagedMoveOutput, Box<ErrFR>> {
+    if ctx
+        .schemas_cache
+        .get_schema(folder_relative)
+        .await
+        .is_none()
+    {
+        return Err(Box::new(
+            ErrFR::new("Target folder has no schema").raw(folder_relative.to_string_lossy()),
+        ));
+    }
+
+    let folder_absolute = ctx.relative_path_to_absolute(folder_relative).await?;
+
+    let mut moved: Vec<String> = vec![];
+    let mut undo: Vec<JournalAction> = vec![];
+    let mut err = ErrFR::new("Some files were not moved");
+
+    for path in paths {
+        let from_absolute = ctx.relative_path_to_absolute(&PathBuf::from(&path)).await?;
+
+        let Some(file_name) = from_absolute.file_name() else {
+            err = err.sub(ErrFR::new("Unable to get file name").info(&path));
+            continue;
+        };
+
+        let to_absolute = get_unique_path(folder_absolute.join(file_name));
+
+        if let Err(e) = fs::rename(&from_absolute, &to_absolute) {
+            err = err.sub(ErrFR::new("Failed to move file").info(&path).raw(e));
+            continue;
+        }
+
+        let to_relative = ctx
+            .absolute_path_to_relative(&to_absolute)
+            .await?
+            .to_string_lossy()
+            .to_string();
+
+        undo.push(JournalAction::Rename {
+            from: to_relative.clone(),
+            to: path,
+        });
+        moved.push(to_relative);
+    }
+
+    Ok(UnmanagedMoveOutput {
+        result: UnmanagedMoveResult {
+            moved,
+            errors: match err.sub_errors.is_empty() {
+                true => None,
+                false => Some(err),
+            },
+        },
+        undo,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(s: &str) -> Value {
+        serde_yml::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn infers_types_from_values() {
+        let number = [yaml("2021"), yaml("1999")];
+        assert!(matches!(
+            infer_attr_type(&number.iter().collect::<Vec<_>>()),
+            SchemaAttrType::Number(NumberSettings {
+                decimal_places: None,
+                ..
+            })
+        ));
+
+        let rating = [yaml("4.5"), yaml("3")];
+        assert!(matches!(
+            infer_attr_type(&rating.iter().collect::<Vec<_>>()),
+            SchemaAttrType::Number(NumberSettings {
+                decimal_places: Some(2),
+                ..
+            })
+        ));
+
+        let dates = [yaml("2024-01-02"), yaml("null")];
+        assert!(matches!(
+            infer_attr_type(&dates.iter().collect::<Vec<_>>()),
+            SchemaAttrType::Date(_)
+        ));
+
+        let tags = [yaml("[a, b]"), yaml("[]")];
+        assert!(matches!(
+            infer_attr_type(&tags.iter().collect::<Vec<_>>()),
+            SchemaAttrType::TextCollection(_)
+        ));
+
+        let read = [yaml("[{started: 2024-01-01, finished: 2024-02-01}]")];
+        assert!(matches!(
+            infer_attr_type(&read.iter().collect::<Vec<_>>()),
+            SchemaAttrType::DatesPairCollection(_)
+        ));
+
+        let mixed = [yaml("2021"), yaml("soon")];
+        assert!(matches!(
+            infer_attr_type(&mixed.iter().collect::<Vec<_>>()),
+            SchemaAttrType::Text(_)
+        ));
+    }
+
+    #[test]
+    fn reads_keys_in_file_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("note.md");
+        fs::write(&path, "---\nzeta: 1\nalpha: two\n---\nbody\n").unwrap();
+
+        let res = read_unmanaged_record(&path, Path::new("note.md")).unwrap();
+        assert_eq!(res.record.keys, vec!["zeta", "alpha"]);
+        assert_eq!(res.markdown, "body\n");
+        assert!(res.parsing_error.is_none());
+    }
+}
@@ -1,8 +1,9 @@
 use chrono::offset::Utc;
 use chrono::DateTime;
+use sha2::{Digest, Sha256};
 
 use super::read_save::FileReadMode;
-use std::fs::{self, File};
+use std::fs;
 use std::io::{self, BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
@@ -20,14 +21,26 @@ pub fn get_file_modified_time(path_absolute: &Path) -> Result<i64, String> {
     }
 }
 
+/// Hex encoded sha256 of file bytes. Used instead of modified time to detect real content changes.
+pub fn get_file_content_hash(path_absolute: &Path) -> Result<String, String> {
+    match fs::read(path_absolute) {
+        Ok(bytes) => Ok(get_content_hash(&bytes)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn get_content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
 pub struct FileContent {
     pub front_matter: String,
     pub content: String,
 }
 
-pub fn get_file_content(path_absolute: &Path, read_mode: &FileReadMode) -> io::Result<FileContent> {
-    let file = File::open(path_absolute)?;
-    let reader = BufReader::new(file);
+/* Bytes are read by caller, so the hash of exactly the same bytes can be taken */
+pub fn parse_file_content(bytes: &[u8], read_mode: &FileReadMode) -> io::Result<FileContent> {
+    let reader = BufReader::new(bytes);
 
     let mut front_matter = String::new();
     let mut content = String::new();
@@ -80,6 +93,42 @@ pub fn get_file_content(path_absolute: &Path, read_mode: &FileReadMode) -> io::R
     })
 }
 
+pub struct FrontMatterSplit<'a> {
+    pub before: &'a str,
+    pub front_matter: &'a str,
+    pub after: &'a str,
+}
+
+/*
+    Unlike get_file_content this keeps everything around front matter byte to byte,
+    so file can be written back without touching markdown. None if there is no closed front matter.
+*/
+pub fn split_front_matter(raw: &str) -> Option<FrontMatterSplit<'_>> {
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for line in raw.split_inclusive('\n') {
+        let line_end = offset + line.len();
+
+        if line.trim() == "---" {
+            match start {
+                None => start = Some((offset, line_end)),
+                Some((before_end, fm_start)) => {
+                    return Some(FrontMatterSplit {
+                        before: &raw[..before_end],
+                        front_matter: &raw[fm_start..offset],
+                        after: &raw[line_end..],
+                    })
+                }
+            }
+        }
+
+        offset = line_end;
+    }
+
+    None
+}
+
 pub fn get_unique_path(path: impl AsRef<Path>) -> PathBuf {
     let path = path.as_ref();
 
@@ -120,6 +169,44 @@ mod tests {
     use std::fs::File;
     use tempfile::tempdir;
 
+    /** get_file_content_hash */
+    #[test]
+    fn test_hash_follows_content() {
+        let dir = tempdir().unwrap();
+        let path_a = dir.path().join("a.md");
+        let path_b = dir.path().join("b.md");
+        fs::write(&path_a, "---\ntitle: a\n---\n").unwrap();
+        fs::write(&path_b, "---\ntitle: a\n---\n").unwrap();
+
+        let hash_a = get_file_content_hash(&path_a).unwrap();
+        assert_eq!(hash_a, get_file_content_hash(&path_b).unwrap());
+
+        fs::write(&path_b, "---\ntitle: b\n---\n").unwrap();
+        assert_ne!(hash_a, get_file_content_hash(&path_b).unwrap());
+    }
+
+    #[test]
+    fn test_hash_missing_file() {
+        let dir = tempdir().unwrap();
+        assert!(get_file_content_hash(&dir.path().join("nope.md")).is_err());
+    }
+
+    /** split_front_matter */
+    #[test]
+    fn test_split_keeps_body_bytes() {
+        let raw = "---\r\ntitle: a\r\n---\r\n\r\nBody  \r\nno newline at end";
+        let split = split_front_matter(raw).unwrap();
+        assert_eq!(split.before, "");
+        assert_eq!(split.front_matter, "title: a\r\n");
+        assert_eq!(split.after, "\r\nBody  \r\nno newline at end");
+    }
+
+    #[test]
+    fn test_split_without_front_matter() {
+        assert!(split_front_matter("Just text\n").is_none());
+        assert!(split_front_matter("---\ntitle: not closed\n").is_none());
+    }
+
     /** get_unique_path */
     #[test]
     fn test_nonexistent_file() {
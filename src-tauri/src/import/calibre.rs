@@ -0,0 +1,631 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::{Connection, Row};
+use ts_rs::TS;
+
+use crate::cache::cache_thing::cache_file;
+use crate::cache::query::{get_files_by_path, RecordFromDb};
+use crate::core::core_state::AppContext;
+use crate::core::journal::{snapshot_file, JournalAction};
+use crate::files::assets::{import_asset, AssetSource, ASSETS_FOLDER_NAME};
+use crate::files::metadata::get_default_metadata;
+use crate::files::read_save::update_attributes;
+use crate::import::{
+    attr_value_text, author_key, convert_value, import_rows, match_key, normalize_date,
+    rescale_rating, ImportRecord, ImportRow, ImportRowError, ImportSettings,
+};
+use crate::schema::types::{
+    find_schema_item, is_collection, is_date, is_number, is_text, is_text_or_collection, AttrValue,
+    Schema, SchemaAttrType,
+};
+use crate::utils::errorhandling::ErrFR;
+use crate::utils::helpers::{split_names, NAMES_SEPARATOR};
+
+const CALIBRE_DB_NAME: &str = "metadata.db";
+const CALIBRE_RATING_MAX: f64 = 10.0;
+const CALIBRE_ID_PREFIX: &str = "calibre:";
+const SEPARATOR: &str = ";";
+
+/* Schema item names for Calibre fields, fields without item are not imported */
+#[derive(Serialize, Deserialize, Clone, Debug, Default, TS)]
+#[ts(export)]
+pub struct CalibreMapping {
+    pub title: Option<String>,
+    pub authors: Option<String>,
+    pub series: Option<String>,
+    pub series_index: Option<String>,
+    pub tags: Option<String>,
+    pub rating: Option<String>,
+    pub publisher: Option<String>,
+    /* Date item gets the full date, number item gets the year */
+    pub pubdate: Option<String>,
+    pub isbn: Option<String>,
+    /* Collection of "type:value", Calibre id is added so re-runs find the same record */
+    pub identifiers: Option<String>,
+    pub cover: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct CalibreImportResult {
+    /* Paths relative to root path */
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    /* Records that already had every imported value */
+    pub unchanged: Vec<String>,
+    /* Row is position of the book in Calibre library ordered by id, 1-based */
+    pub errors: Vec<ImportRowError>,
+}
+
+pub struct CalibreImportOutput {
+    pub result: CalibreImportResult,
+    pub undo: Vec<JournalAction>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct CalibreBook {
+    uuid: String,
+    title: String,
+    authors: Vec<String>,
+    series: Option<String>,
+    series_index: Option<f64>,
+    tags: Vec<String>,
+    rating: Option<f64>,
+    publisher: Option<String>,
+    pubdate: Option<String>,
+    identifiers: Vec<(String, String)>,
+    cover: Option<PathBuf>,
+}
+
+impl CalibreBook {
+    fn identifier(&self, kind: &str) -> Option<&str> {
+        self.identifiers
+            .iter()
+            .find(|(k, _)| k == kind)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn calibre_id(&self) -> String {
+        format!("{}{}", CALIBRE_ID_PREFIX, self.uuid)
+    }
+
+    fn match_key(&self) -> String {
+        format!(
+            "{}|{}",
+            match_key(&self.title),
+            author_key(self.authors.first().map(|a| a.as_str()).unwrap_or(""))
+        )
+    }
+}
+
+/* Guess based on attribute names and types, user can adjust it before import */
+pub fn guess_calibre_mapping(schema: &Schema) -> CalibreMapping {
+    CalibreMapping {
+        title: find_schema_item(schema, &["title", "name"], is_text),
+        authors: find_schema_item(
+            schema,
+            &["authors", "author", "writer"],
+            is_text_or_collection,
+        ),
+        series: find_schema_item(schema, &["series", "cycle"], is_text),
+        series_index: find_schema_item(
+            schema,
+            &["seriesIndex", "series_index", "seriesNumber", "number"],
+            is_number,
+        ),
+        tags: find_schema_item(schema, &["tags", "genres", "shelves"], is_collection),
+        rating: find_schema_item(schema, &["myRating", "rating", "score"], is_number),
+        publisher: find_schema_item(schema, &["publisher"], is_text),
+        pubdate: find_schema_item(
+            schema,
+            &["published", "pubdate", "publicationDate"],
+            is_date,
+        )
+        .or(find_schema_item(schema, &["year", "published"], is_number)),
+        isbn: find_schema_item(schema, &["isbn", "ISBN13", "isbn13"], is_text),
+        identifiers: find_schema_item(schema, &["identifiers", "ids"], is_collection),
+        cover: find_schema_item(schema, &["cover", "image"], |t| {
+            matches!(t, SchemaAttrType::Image(_))
+        }),
+    }
+}
+
+/* Library folder or metadata.db itself */
+fn resolve_db_path(library: &Path) -> PathBuf {
+    match library.is_dir() {
+        true => library.join(CALIBRE_DB_NAME),
+        false => library.to_path_buf(),
+    }
+}
+
+fn db_err(e: sqlx::Error) -> Box<ErrFR> {
+    Box::new(ErrFR::new("Error reading Calibre library").raw(e))
+}
+
+/* Book id to values, in order of query */
+async fn read_links(
+    conn: &mut SqliteConnection,
+    query: &'static str,
+) -> Result<HashMap<i64, Vec<String>>, Box<ErrFR>> {
+    let mut result: HashMap<i64, Vec<String>> = HashMap::new();
+
+    for row in sqlx::query(query)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_err)?
+    {
+        let value: Option<String> = row.get("value");
+        if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+            result.entry(row.get("book")).or_default().push(value);
+        }
+    }
+
+    Ok(result)
+}
+
+async fn read_calibre_books(library: &Path) -> Result<Vec<CalibreBook>, Box<ErrFR>> {
+    let db_path = resolve_db_path(library);
+    if !db_path.is_file() {
+        return Err(Box::new(
+            ErrFR::new("Not a Calibre library")
+                .info("Folder should contain metadata.db")
+                .raw(library.to_string_lossy()),
+        ));
+    }
+    let library_folder = db_path.parent().unwrap_or(library).to_path_buf();
+
+    // Calibre might be running, we never write to its database
+    let options = SqliteConnectOptions::new()
+        .filename(&db_path)
+        .read_only(true);
+    let mut conn = SqliteConnection::connect_with(&options)
+        .await
+        .map_err(db_err)?;
+
+    let mut authors = read_links(
+        &mut conn,
+        "SELECT l.book AS book, a.name AS value FROM books_authors_link l JOIN authors a ON a.id = l.author ORDER BY l.id",
+    )
+    .await?;
+    let mut tags = read_links(
+        &mut conn,
+        "SELECT l.book AS book, t.name AS value FROM books_tags_link l JOIN tags t ON t.id = l.tag ORDER BY t.name",
+    )
+    .await?;
+    let mut series = read_links(
+        &mut conn,
+        "SELECT l.book AS book, s.name AS value FROM books_series_link l JOIN series s ON s.id = l.series",
+    )
+    .await?;
+    let mut publishers = read_links(
+        &mut conn,
+        "SELECT l.book AS book, p.name AS value FROM books_publishers_link l JOIN publishers p ON p.id = l.publisher",
+    )
+    .await?;
+    let mut ratings = read_links(
+        &mut conn,
+        "SELECT l.book AS book, CAST(r.rating AS TEXT) AS value FROM books_ratings_link l JOIN ratings r ON r.id = l.rating",
+    )
+    .await?;
+    let mut identifiers = read_links(
+        &mut conn,
+        "SELECT book, type || ':' || val AS value FROM identifiers ORDER BY type",
+    )
+    .await?;
+
+    let rows = sqlx::query(
+        "SELECT id, uuid, title, path, has_cover, pubdate, series_index FROM books ORDER BY id",
+    )
+    .fetch_all(&mut conn)
+    .await
+    .map_err(db_err)?;
+
+    conn.close().await.map_err(db_err)?;
+
+    Ok(rows
+        .iter()
+        .map(|r| {
+            let id: i64 = r.get("id");
+            let has_cover: Option<bool> = r.get("has_cover");
+            let path: String = r.get("path");
+            let first = |map: &mut HashMap<i64, Vec<String>>| {
+                map.remove(&id).and_then(|v| v.into_iter().next())
+            };
+
+            CalibreBook {
+                uuid: r.get::<Option<String>, _>("uuid").unwrap_or_default(),
+                title: r.get("title"),
+                authors: authors.remove(&id).unwrap_or_default(),
+                series: first(&mut series),
+                series_index: r.get("series_index"),
+                tags: tags.remove(&id).unwrap_or_default(),
+                rating: first(&mut ratings).and_then(|v| v.parse().ok()),
+                publisher: first(&mut publishers),
+                pubdate: r
+                    .get::<Option<String>, _>("pubdate")
+                    .as_deref()
+                    .and_then(calibre_date),
+                identifiers: identifiers
+                    .remove(&id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|v| {
+                        v.split_once(':')
+                            .map(|(k, v)| (k.to_string(), v.to_string()))
+                    })
+                    .collect(),
+                cover: has_cover
+                    .unwrap_or(false)
+                    .then(|| library_folder.join(&path).join("cover.jpg"))
+                    .filter(|p| p.is_file()),
+            }
+        })
+        .collect())
+}
+
+/* Calibre stores missing dates as year 101 */
+fn calibre_date(value: &str) -> Option<String> {
+    normalize_date(value).filter(|d| !d.starts_with("0101"))
+}
+
+fn item_type<'a>(schema: &'a Schema, attribute: &Option<String>) -> Option<&'a SchemaAttrType> {
+    let attribute = attribute.as_ref()?;
+    schema
+        .items
+        .iter()
+        .find(|i| &i.name == attribute)
+        .map(|i| &i.value)
+}
+
+/* Cover is not part of the row, it is imported separately into assets */
+fn to_import_row(book: &CalibreBook, mapping: &CalibreMapping, schema: &Schema) -> ImportRow {
+    let mut values = ImportRow::new();
+    let mut set = |attribute: &Option<String>, value: String| {
+        if let Some(attribute) = attribute {
+            values.insert(attribute.clone(), value);
+        }
+    };
+
+    set(&mapping.title, book.title.clone());
+    set(
+        &mapping.authors,
+        match item_type(schema, &mapping.authors).is_some_and(is_collection) {
+            true => book.authors.join(SEPARATOR),
+            false => book.authors.join(NAMES_SEPARATOR),
+        },
+    );
+    set(&mapping.series, book.series.clone().unwrap_or_default());
+    set(
+        &mapping.series_index,
+        match book.series {
+            Some(_) => book.series_index.map(|v| v.to_string()).unwrap_or_default(),
+            None => "".to_string(),
+        },
+    );
+    set(&mapping.tags, book.tags.join(SEPARATOR));
+    set(
+        &mapping.publisher,
+        book.publisher.clone().unwrap_or_default(),
+    );
+    set(
+        &mapping.pubdate,
+        match item_type(schema, &mapping.pubdate).is_some_and(is_number) {
+            true => book
+                .pubdate
+                .as_ref()
+                .map(|d| d[..4].to_string())
+                .unwrap_or_default(),
+            false => book.pubdate.clone().unwrap_or_default(),
+        },
+    );
+    set(
+        &mapping.isbn,
+        book.identifier("isbn").unwrap_or_default().to_string(),
+    );
+    set(
+        &mapping.identifiers,
+        std::iter::once(book.calibre_id())
+            .chain(book.identifiers.iter().map(|(k, v)| format!("{}:{}", k, v)))
+            .collect::<Vec<_>>()
+            .join(SEPARATOR),
+    );
+
+    // 0 means the book is not rated
+    if let (Some(SchemaAttrType::Number(settings)), Some(rating)) =
+        (item_type(schema, &mapping.rating), book.rating)
+    {
+        if rating > 0.0 {
+            set(
+                &mapping.rating,
+                rescale_rating(rating, CALIBRE_RATING_MAX, settings).to_string(),
+            );
+        }
+    }
+
+    values
+}
+
+/* Existing records in folder by Calibre id and by title with author */
+fn existing_index(records: &[RecordFromDb], mapping: &CalibreMapping) -> HashMap<String, usize> {
+    let mut index = HashMap::new();
+
+    for (i, record) in records.iter().enumerate() {
+        let get = |attribute: &Option<String>| {
+            attribute
+                .as_ref()
+                .and_then(|a| attr_value_text(record.attrs.get(a)))
+        };
+
+        if let Some(identifiers) = get(&mapping.identifiers) {
+            for id in identifiers.split(';').map(|v| v.trim()) {
+                if id.starts_with(CALIBRE_ID_PREFIX) {
+                    index.insert(id.to_string(), i);
+                }
+            }
+        }
+        if let Some(title) = get(&mapping.title) {
+            let authors = split_names(&get(&mapping.authors).unwrap_or_default());
+            let first_author = authors.first().map(|a| a.as_str()).unwrap_or("");
+            index
+                .entry(format!(
+                    "{}|{}",
+                    match_key(&title),
+                    author_key(first_author)
+                ))
+                .or_insert(i);
+        }
+    }
+
+    index
+}
+
+/*
+    Values of book that differ from record. Empty values never clear what is already in record,
+    so attributes that user filled by hand survive re-import.
+*/
+fn changed_values(
+    record: &RecordFromDb,
+    values: &ImportRow,
+    schema: &Schema,
+    settings: &ImportSettings,
+) -> Result<HashMap<String, AttrValue>, Box<ErrFR>> {
+    let mut patch = HashMap::new();
+    let mut err = ErrFR::new("Record was not updated");
+
+    for item in schema.items.iter() {
+        let Some(raw) = values.get(&item.name) else {
+            continue;
+        };
+        match convert_value(raw, &item.value, settings) {
+            Ok(v) if v == get_default_metadata(item.value.clone()) => (),
+            Ok(v) if record.attrs.get(&item.name) == Some(&v) => (),
+            Ok(v) => {
+                patch.insert(item.name.clone(), v);
+            }
+            Err(e) => err = err.sub(e.info(&item.name)),
+        }
+    }
+
+    match err.sub_errors.is_empty() {
+        true => Ok(patch),
+        false => Err(Box::new(err)),
+    }
+}
+
+async fn update_record(
+    ctx: &AppContext,
+    record: &RecordFromDb,
+    values: &ImportRow,
+    schema: &Schema,
+    settings: &ImportSettings<'_>,
+    undo: &mut Vec<JournalAction>,
+) -> Result<bool, Box<ErrFR>> {
+    let patch = changed_values(record, values, schema, settings)?;
+    if patch.is_empty() {
+        return Ok(false);
+    }
+
+    let path_relative = PathBuf::from(record.path.clone().unwrap_or_default());
+    let snapshot = snapshot_file(ctx, &path_relative).await;
+
+    update_attributes(
+        ctx,
+        &path_relative,
+        patch,
+        record.hash.clone(),
+        record.modified,
+        false,
+    )
+    .await?;
+
+    undo.push(snapshot);
+    cache_file(ctx, &ctx.relative_path_to_absolute(&path_relative).await?).await?;
+
+    Ok(true)
+}
+
+/*
+    Books that are already in folder are updated in place, the rest are created.
+    Running import again on the same library changes nothing.
+*/
+pub async fn import_calibre(
+    ctx: &AppContext,
+    library: &Path,
+    folder_relative: &Path,
+    mapping: &CalibreMapping,
+) -> Result<CalibreImportOutput, Box<ErrFR>> {
+    let schema = ctx
+        .schemas_cache
+        .get_schema(folder_relative)
+        .await
+        .ok_or(Box::new(
+            ErrFR::new("Target folder has no schema").raw(folder_relative.to_string_lossy()),
+        ))?
+        .schema;
+
+    let books = read_calibre_books(library).await?;
+    let existing = get_files_by_path(ctx, folder_relative).await?.records;
+    let index = existing_index(&existing, mapping);
+
+    let filename_template = match &mapping.title {
+        Some(title) => format!("{{{}}}", title),
+        None => "Untitled".to_string(),
+    };
+    let settings = ImportSettings {
+        collection_separator: SEPARATOR,
+        date_pair_separator: " - ",
+        filename_template: &filename_template,
+        decimal_comma: false,
+    };
+
+    let mut result = CalibreImportResult {
+        created: vec![],
+        updated: vec![],
+        unchanged: vec![],
+        errors: vec![],
+    };
+    let mut undo: Vec<JournalAction> = vec![];
+    let mut records: Vec<ImportRecord> = vec![];
+
+    for (i, book) in books.iter().enumerate() {
+        let row_number = i as u32 + 1;
+        let mut values = to_import_row(book, mapping, &schema);
+
+        // Assets are deduplicated by content, so importing the same cover again gives the same name
+        if let (Some(attribute), Some(cover)) = (&mapping.cover, &book.cover) {
+            match import_asset(
+                ctx,
+                AssetSource::Path(cover.to_string_lossy().to_string()),
+                &book.title,
+            )
+            .await
+            {
+                Ok(asset) => {
+                    if !asset.existing {
+                        undo.push(JournalAction::MoveToTrash {
+                            path: Path::new(ASSETS_FOLDER_NAME)
+                                .join(&asset.path)
+                                .to_string_lossy()
+                                .to_string(),
+                        });
+                    }
+                    values.insert(attribute.clone(), asset.path);
+                }
+                // Book is still imported, only without cover
+                Err(e) => result.errors.push(ImportRowError {
+                    row: row_number,
+                    error: ErrFR::new("Cover was not imported").sub(*e),
+                }),
+            }
+        }
+
+        let found = index
+            .get(&book.calibre_id())
+            .or(index.get(&book.match_key()))
+            .map(|i| &existing[*i]);
+
+        let Some(record) = found else {
+            records.push(ImportRecord {
+                row: row_number,
+                values,
+                markdown: None,
+            });
+            continue;
+        };
+
+        let path = record.path.clone().unwrap_or_default();
+        match update_record(ctx, record, &values, &schema, &settings, &mut undo).await {
+            Ok(true) => result.updated.push(path),
+            Ok(false) => result.unchanged.push(path),
+            Err(e) => result.errors.push(ImportRowError {
+                row: row_number,
+                error: e.info(&path),
+            }),
+        }
+    }
+
+    let created = import_rows(ctx, folder_relative, &schema, records, &settings).await?;
+
+    result.created = created.result.created;
+    result.errors.extend(created.result.errors);
+    result.errors.sort_by_key(|e| e.row);
+    undo.extend(created.undo);
+
+    Ok(CalibreImportOutput { result, undo })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::types::{NumberSettings, SchemaItem, TextCollectionSettings, TextSettings};
+
+    fn schema() -> Schema {
+        let item = |name: &str, value: SchemaAttrType| SchemaItem {
+            name: name.to_string(),
+            value,
+        };
+        Schema {
+            name: "books".to_string(),
+            version: "1.0".to_string(),
+            fill_from_filename: None,
+            fill_api_search_from: None,
+            items: vec![
+                item("title", SchemaAttrType::Text(TextSettings::default())),
+                item(
+                    "authors",
+                    SchemaAttrType::TextCollection(TextCollectionSettings::default()),
+                ),
+                item("year", SchemaAttrType::Number(NumberSettings::default())),
+                item(
+                    "rating",
+                    SchemaAttrType::Number(NumberSettings {
+                        max: Some(5.0),
+                        decimal_places: Some(1),
+                        ..NumberSettings::default()
+                    }),
+                ),
+                item(
+                    "identifiers",
+                    SchemaAttrType::TextCollection(TextCollectionSettings::default()),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn builds_row_from_book() {
+        let schema = schema();
+        let mapping = guess_calibre_mapping(&schema);
+        assert_eq!(mapping.pubdate.as_deref(), Some("year"));
+
+        let book = CalibreBook {
+            uuid: "abc".to_string(),
+            title: "Good Omens".to_string(),
+            authors: vec!["Terry Pratchett".to_string(), "Neil Gaiman".to_string()],
+            rating: Some(9.0),
+            pubdate: calibre_date("1990-05-01 00:00:00+00:00"),
+            identifiers: vec![("isbn".to_string(), "9780060853983".to_string())],
+            ..CalibreBook::default()
+        };
+
+        let row = to_import_row(&book, &mapping, &schema);
+        assert_eq!(row["authors"], "Terry Pratchett;Neil Gaiman");
+        assert_eq!(row["year"], "1990");
+        assert_eq!(row["rating"], "4.5");
+        assert_eq!(row["identifiers"], "calibre:abc;isbn:9780060853983");
+
+        assert_eq!(calibre_date("0101-01-01 00:00:00+00:00"), None);
+
+        // Text item gets names joined the way exporters split them
+        let mut text_schema = schema.clone();
+        text_schema.items[1].value = SchemaAttrType::Text(TextSettings::default());
+        let row = to_import_row(&book, &mapping, &text_schema);
+        assert_eq!(
+            split_names(&row["authors"]),
+            vec!["Terry Pratchett", "Neil Gaiman"]
+        );
+    }
+}
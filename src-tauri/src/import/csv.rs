@@ -0,0 +1,233 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::core::core_state::AppContext;
+use crate::import::{import_rows, ImportOutput, ImportRecord, ImportRow, ImportSettings};
+use crate::utils::errorhandling::ErrFR;
+
+const DELIMITERS: [u8; 3] = [b',', b';', b'\t'];
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct CsvPreview {
+    pub headers: Vec<String>,
+    /* First rows of the file, to show how values look like */
+    pub rows: Vec<Vec<String>>,
+    pub total_rows: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct CsvColumnMapping {
+    /* Header of the column */
+    pub column: String,
+    /* Schema item name */
+    pub attribute: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct CsvImportOptions {
+    /* Columns that are not mapped are skipped */
+    pub mapping: Vec<CsvColumnMapping>,
+    /* Attribute names in curly braces are replaced with values, "{title} ({year})" */
+    pub filename_template: String,
+    pub collection_separator: String,
+    pub date_pair_separator: String,
+    /* Numbers are written as "4,5" */
+    pub decimal_comma: bool,
+}
+
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/* Spreadsheets in some locales save with ";" and tab separated files are common too */
+fn detect_delimiter(content: &str) -> u8 {
+    let first_line = content.lines().next().unwrap_or("");
+    DELIMITERS
+        .iter()
+        .copied()
+        .max_by_key(|d| first_line.bytes().filter(|b| b == d).count())
+        .filter(|d| first_line.bytes().any(|b| b == *d))
+        .unwrap_or(b',')
+}
+
+pub fn parse_csv(content: &str) -> Result<CsvTable, Box<ErrFR>> {
+    let content = content.trim_start_matches('\u{FEFF}');
+
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(detect_delimiter(content))
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| Box::new(ErrFR::new("Unable to read csv headers").raw(e)))?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+
+    let rows = reader
+        .records()
+        .map(|r| r.map(|r| r.iter().map(|v| v.to_string()).collect::<Vec<_>>()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Box::new(ErrFR::new("Unable to read csv").raw(e)))?;
+
+    Ok(CsvTable { headers, rows })
+}
+
+pub fn read_csv_file(path: &Path) -> Result<CsvTable, Box<ErrFR>> {
+    let bytes = fs::read(path).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error reading file")
+                .info(&path.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+
+    parse_csv(&String::from_utf8_lossy(&bytes))
+}
+
+pub fn preview_csv(path: &Path, sample_size: usize) -> Result<CsvPreview, Box<ErrFR>> {
+    let table = read_csv_file(path)?;
+
+    Ok(CsvPreview {
+        total_rows: table.rows.len() as u32,
+        rows: table.rows.into_iter().take(sample_size).collect(),
+        headers: table.headers,
+    })
+}
+
+/* Turns table rows into values keyed by attribute names */
+pub fn map_rows(
+    table: &CsvTable,
+    mapping: &[CsvColumnMapping],
+) -> Result<Vec<ImportRow>, Box<ErrFR>> {
+    let mut columns: Vec<(usize, &str)> = vec![];
+
+    for m in mapping {
+        let index = table
+            .headers
+            .iter()
+            .position(|h| h == &m.column)
+            .ok_or(Box::new(
+                ErrFR::new("Column not found in file").info(&m.column),
+            ))?;
+        columns.push((index, m.attribute.as_str()));
+    }
+
+    Ok(table
+        .rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|(i, attribute)| {
+                    (
+                        attribute.to_string(),
+                        row.get(*i).cloned().unwrap_or_default(),
+                    )
+                })
+                .collect()
+        })
+        .collect())
+}
+
+pub async fn import_csv(
+    ctx: &AppContext,
+    path: &Path,
+    folder_relative: &Path,
+    options: &CsvImportOptions,
+) -> Result<ImportOutput, Box<ErrFR>> {
+    let schema = ctx
+        .schemas_cache
+        .get_schema(folder_relative)
+        .await
+        .ok_or(Box::new(
+            ErrFR::new("Target folder has no schema").raw(folder_relative.to_string_lossy()),
+        ))?
+        .schema;
+
+    if let Some(m) = options
+        .mapping
+        .iter()
+        .find(|m| !schema.items.iter().any(|i| i.name == m.attribute))
+    {
+        return Err(Box::new(
+            ErrFR::new("Attribute is not in schema").info(&m.attribute),
+        ));
+    }
+
+    let table = read_csv_file(path)?;
+    let records = map_rows(&table, &options.mapping)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, values)| ImportRecord {
+            row: i as u32 + 1,
+            values,
+            markdown: None,
+        })
+        .collect();
+
+    import_rows(
+        ctx,
+        folder_relative,
+        &schema,
+        records,
+        &ImportSettings {
+            collection_separator: &options.collection_separator,
+            date_pair_separator: &options.date_pair_separator,
+            filename_template: &options.filename_template,
+            decimal_comma: options.decimal_comma,
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_with_bom_and_semicolons() {
+        let table = parse_csv("\u{FEFF}Title;Year\n\"Dune; Messiah\";1969\nShort\n").unwrap();
+        assert_eq!(table.headers, vec!["Title", "Year"]);
+        assert_eq!(table.rows[0], vec!["Dune; Messiah", "1969"]);
+        assert_eq!(table.rows[1], vec!["Short"]);
+
+        let rows = map_rows(
+            &table,
+            &[
+                CsvColumnMapping {
+                    column: "Year".to_string(),
+                    attribute: "year".to_string(),
+                },
+                CsvColumnMapping {
+                    column: "Title".to_string(),
+                    attribute: "title".to_string(),
+                },
+            ],
+        )
+        .unwrap();
+        assert_eq!(rows[0]["title"], "Dune; Messiah");
+        assert_eq!(rows[1]["year"], "");
+    }
+
+    #[test]
+    fn unknown_column_is_an_error() {
+        let table = parse_csv("a,b\n1,2\n").unwrap();
+        assert!(map_rows(
+            &table,
+            &[CsvColumnMapping {
+                column: "c".to_string(),
+                attribute: "c".to_string(),
+            }]
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,310 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache::cache_thing::cache_file;
+use crate::cache::query::RecordFromDb;
+use crate::core::core_state::AppContext;
+use crate::core::journal::{snapshot_file, JournalAction};
+use crate::export::json::{Dump, DumpHeader, DumpRecord, DUMP_FORMAT_VERSION};
+use crate::files::read_save::save_file;
+use crate::import::{ImportOutput, ImportResult, ImportRowError};
+use crate::schema::schema_cache::locate_schema_and_folder;
+use crate::utils::errorhandling::ErrFR;
+
+pub struct ParsedDump {
+    pub header: DumpHeader,
+    /* Row is 1-based position of record in dump */
+    pub records: Vec<(u32, Result<DumpRecord, Box<ErrFR>>)>,
+}
+
+fn parse_err(e: serde_json::Error) -> Box<ErrFR> {
+    Box::new(
+        ErrFR::new("Unable to parse file")
+            .info("File should be a JSON or NDJSON export made by this app")
+            .raw(e),
+    )
+}
+
+/* Accepts both formats, NDJSON is tried when file is not a single JSON object */
+pub fn parse_dump(raw: &str) -> Result<ParsedDump, Box<ErrFR>> {
+    let raw = raw.trim_start_matches('\u{FEFF}');
+
+    let json_err = match serde_json::from_str::<Dump>(raw) {
+        Ok(dump) => {
+            return Ok(ParsedDump {
+                header: DumpHeader {
+                    version: dump.version,
+                    folder: dump.folder,
+                    schema: dump.schema,
+                },
+                records: dump
+                    .records
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, r)| (i as u32 + 1, Ok(r)))
+                    .collect(),
+            })
+        }
+        Err(e) => e,
+    };
+
+    let mut lines = raw.lines().filter(|l| !l.trim().is_empty());
+    let header = lines
+        .next()
+        .and_then(|l| serde_json::from_str::<DumpHeader>(l).ok())
+        .ok_or_else(|| parse_err(json_err))?;
+
+    // Broken line only loses its own record
+    let records = lines
+        .enumerate()
+        .map(|(i, line)| {
+            (
+                i as u32 + 1,
+                serde_json::from_str::<DumpRecord>(line).map_err(parse_err),
+            )
+        })
+        .collect();
+
+    Ok(ParsedDump { header, records })
+}
+
+/* Record paths come from a file, they should never point outside of target folder */
+pub fn safe_record_path(path: &str) -> Option<PathBuf> {
+    let parts: Vec<&str> = path.split(['/', '\\']).collect();
+
+    let is_safe = parts
+        .iter()
+        .all(|p| !p.is_empty() && *p != "." && *p != ".." && !p.contains(':'));
+
+    match is_safe && path.ends_with(".md") {
+        true => Some(parts.iter().collect()),
+        false => None,
+    }
+}
+
+/* Creates missing folders between target folder and record, outermost first */
+async fn create_parent_folders(
+    ctx: &AppContext,
+    folder_absolute: &Path,
+    path_absolute: &Path,
+    undo: &mut Vec<JournalAction>,
+) -> Result<(), Box<ErrFR>> {
+    let mut missing: Vec<&Path> = path_absolute
+        .ancestors()
+        .skip(1)
+        .take_while(|p| *p != folder_absolute && !p.exists())
+        .collect();
+    missing.reverse();
+
+    for folder in missing {
+        fs::create_dir(folder).map_err(|e| {
+            Box::new(
+                ErrFR::new("Error creating directory")
+                    .info(&folder.to_string_lossy())
+                    .raw(e),
+            )
+        })?;
+        undo.push(JournalAction::RemoveFolder {
+            path: ctx
+                .absolute_path_to_relative(folder)
+                .await?
+                .to_string_lossy()
+                .to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/*
+    Restores records of a dump into folder, existing files are never overwritten.
+    Folder without schema gets the one from dump, otherwise its own schema is kept.
+*/
+pub async fn import_dump(
+    ctx: &AppContext,
+    path: &Path,
+    folder_relative: &Path,
+) -> Result<ImportOutput, Box<ErrFR>> {
+    let raw = fs::read_to_string(path).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error reading file")
+                .info(&path.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+    let dump = parse_dump(&raw)?;
+
+    if dump.header.version > DUMP_FORMAT_VERSION {
+        return Err(Box::new(
+            ErrFR::new("Export was made by a newer version of the app")
+                .info("Update the app to import it")
+                .raw(dump.header.version),
+        ));
+    }
+
+    let folder_absolute = ctx.relative_path_to_absolute(folder_relative).await?;
+    if !folder_absolute.is_dir() {
+        return Err(Box::new(
+            ErrFR::new("Target folder does not exist").raw(folder_relative.to_string_lossy()),
+        ));
+    }
+
+    let mut undo: Vec<JournalAction> = vec![];
+
+    if ctx
+        .schemas_cache
+        .get_schema(folder_relative)
+        .await
+        .is_none()
+    {
+        let (schema_path, _) = locate_schema_and_folder(&folder_absolute)?;
+        undo.push(snapshot_file(ctx, &ctx.absolute_path_to_relative(&schema_path).await?).await);
+        ctx.schemas_cache
+            .save_schema(ctx, folder_relative, dump.header.schema)
+            .await?;
+    }
+
+    let mut created: Vec<String> = vec![];
+    let mut errors: Vec<ImportRowError> = vec![];
+
+    for (row, record) in dump.records {
+        let record = match record {
+            Ok(v) => v,
+            Err(e) => {
+                errors.push(ImportRowError { row, error: *e });
+                continue;
+            }
+        };
+
+        let Some(record_path) = safe_record_path(&record.path) else {
+            errors.push(ImportRowError {
+                row,
+                error: ErrFR::new("Invalid record path").raw(&record.path),
+            });
+            continue;
+        };
+
+        let path_relative = folder_relative.join(&record_path);
+        let path_absolute = folder_absolute.join(&record_path);
+
+        if let Err(e) =
+            create_parent_folders(ctx, &folder_absolute, &path_absolute, &mut undo).await
+        {
+            errors.push(ImportRowError { row, error: *e });
+            continue;
+        }
+
+        let saved = save_file(
+            ctx,
+            RecordFromDb {
+                path: Some(path_relative.to_string_lossy().to_string()),
+                attrs: record.attrs.into_iter().collect(),
+                markdown: record.markdown,
+                ..RecordFromDb::default()
+            },
+            false,
+            true,
+        )
+        .await;
+
+        match saved {
+            Ok(saved) => {
+                let saved_absolute = ctx
+                    .relative_path_to_absolute(Path::new(&saved.path))
+                    .await?;
+                if let Err(e) = cache_file(ctx, &saved_absolute).await {
+                    log::warn!("import: unable to cache {} {:?}", saved.path, e);
+                }
+                undo.push(JournalAction::MoveToTrash {
+                    path: saved.path.clone(),
+                });
+                created.push(saved.path);
+            }
+            Err(e) => errors.push(ImportRowError { row, error: *e }),
+        }
+    }
+
+    Ok(ImportOutput {
+        result: ImportResult {
+            created,
+            errors,
+            duplicates: vec![],
+        },
+        undo,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::export::json::{dump_to_string, DumpFormat};
+    use crate::schema::types::{AttrValue, DatePair, Schema};
+
+    fn dump() -> Dump {
+        Dump {
+            version: DUMP_FORMAT_VERSION,
+            folder: "books".to_string(),
+            schema: Schema {
+                name: "Books".to_string(),
+                version: "1.0".to_string(),
+                fill_from_filename: None,
+                fill_api_search_from: None,
+                items: vec![],
+            },
+            records: vec![DumpRecord {
+                path: "sub/Dune.md".to_string(),
+                attrs: BTreeMap::from([
+                    ("year".to_string(), AttrValue::Integer(Some(1965.0))),
+                    (
+                        "read".to_string(),
+                        AttrValue::DatePairVec(Some(vec![DatePair {
+                            started: Some("2024-01-01".to_string()),
+                            finished: None,
+                        }])),
+                    ),
+                ]),
+                markdown: Some("Spice\n".to_string()),
+            }],
+        }
+    }
+
+    #[test]
+    fn both_formats_round_trip() {
+        for format in [DumpFormat::Json, DumpFormat::Ndjson] {
+            let raw = dump_to_string(dump(), format).unwrap();
+            let parsed = parse_dump(&raw).unwrap();
+
+            assert_eq!(parsed.header.folder, "books");
+            assert_eq!(parsed.records.len(), 1);
+            assert_eq!(
+                parsed.records[0].1.as_ref().unwrap(),
+                &dump().records[0],
+                "{:?}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn broken_ndjson_line_is_reported() {
+        let raw = dump_to_string(dump(), DumpFormat::Ndjson).unwrap() + "{not json\n";
+        let parsed = parse_dump(&raw).unwrap();
+        assert_eq!(parsed.records.len(), 2);
+        assert_eq!(parsed.records[1].0, 2);
+        assert!(parsed.records[1].1.is_err());
+    }
+
+    #[test]
+    fn record_paths_stay_in_folder() {
+        assert_eq!(
+            safe_record_path("sub/Dune.md"),
+            Some(Path::new("sub").join("Dune.md"))
+        );
+        assert_eq!(safe_record_path("../Dune.md"), None);
+        assert_eq!(safe_record_path("/etc/Dune.md"), None);
+        assert_eq!(safe_record_path("C:\\Dune.md"), None);
+        assert_eq!(safe_record_path("notes.txt"), None);
+    }
+}
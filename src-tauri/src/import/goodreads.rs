@@ -0,0 +1,347 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::query::get_files_by_path;
+use crate::core::core_state::AppContext;
+use crate::import::csv::{read_csv_file, CsvTable};
+use crate::import::{
+    attr_value_text, author_key, import_rows, match_key, normalize_date, rescale_rating,
+    ImportOutput, ImportRecord, ImportRow, ImportSettings,
+};
+use crate::schema::types::{
+    find_schema_item, is_collection, is_dates_pair, is_number, is_text, is_text_or_collection,
+    Schema, SchemaAttrType,
+};
+use crate::utils::errorhandling::ErrFR;
+use crate::utils::helpers::split_names;
+
+const GOODREADS_RATING_MAX: f64 = 5.0;
+const SEPARATOR: &str = ";";
+const PAIR_SEPARATOR: &str = "..";
+
+/* Schema item names for Goodreads fields, fields without item are not imported */
+#[derive(Serialize, Deserialize, Clone, Debug, Default, TS)]
+#[ts(export)]
+pub struct GoodreadsMapping {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub isbn: Option<String>,
+    pub rating: Option<String>,
+    pub shelves: Option<String>,
+    /* Date Added goes to started, Date Read to finished */
+    pub read: Option<String>,
+    pub year: Option<String>,
+    pub pages: Option<String>,
+    /* Review becomes markdown of the record */
+    pub review_as_markdown: bool,
+}
+
+/* Guess based on attribute names and types, user can adjust it before import */
+pub fn guess_goodreads_mapping(schema: &Schema) -> GoodreadsMapping {
+    GoodreadsMapping {
+        title: find_schema_item(schema, &["title", "name"], is_text),
+        author: find_schema_item(
+            schema,
+            &["author", "authors", "writer"],
+            is_text_or_collection,
+        ),
+        isbn: find_schema_item(schema, &["isbn", "isbn13"], is_text),
+        rating: find_schema_item(schema, &["myRating", "rating", "score"], is_number),
+        shelves: find_schema_item(schema, &["shelves", "tags", "genres"], is_collection),
+        read: find_schema_item(schema, &["read", "dates", "readDates"], is_dates_pair),
+        year: find_schema_item(schema, &["year", "published"], is_number),
+        pages: find_schema_item(schema, &["pages", "pageCount"], is_number),
+        review_as_markdown: true,
+    }
+}
+
+/* Goodreads writes ISBNs as ="0441013597" so spreadsheets keep leading zeros */
+fn clean_isbn(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == 'X' || *c == 'x')
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/* "Dune (Dune Chronicles, #1)" and "Dune" are the same book */
+fn title_key(title: &str) -> String {
+    let base = match title.trim_end().ends_with(')') {
+        true => title.rsplit_once(" (").map(|(t, _)| t).unwrap_or(title),
+        false => title,
+    };
+    match_key(base)
+}
+
+/*
+    Built from the same mapped fields for records in folder and for imported rows,
+    otherwise records without author would never match. Only first author is compared.
+*/
+fn title_author_key(title: &str, author: Option<&str>) -> String {
+    let authors = split_names(author.unwrap_or(""));
+    let first_author = authors.first().map(|a| a.as_str()).unwrap_or("");
+    format!("{}|{}", title_key(title), author_key(first_author))
+}
+
+struct GoodreadsRow<'a> {
+    table: &'a CsvTable,
+    row: &'a [String],
+}
+
+impl GoodreadsRow<'_> {
+    fn get(&self, column: &str) -> &str {
+        self.table
+            .headers
+            .iter()
+            .position(|h| h == column)
+            .and_then(|i| self.row.get(i))
+            .map(|v| v.trim())
+            .unwrap_or("")
+    }
+
+    fn isbn(&self) -> String {
+        match clean_isbn(self.get("ISBN13")) {
+            v if !v.is_empty() => v,
+            _ => clean_isbn(self.get("ISBN")),
+        }
+    }
+
+    fn shelves(&self) -> Vec<String> {
+        let mut shelves: Vec<String> = vec![];
+        for shelf in std::iter::once(self.get("Exclusive Shelf"))
+            .chain(self.get("Bookshelves").split(','))
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+        {
+            if !shelves.iter().any(|s| s == shelf) {
+                shelves.push(shelf.to_string());
+            }
+        }
+        shelves
+    }
+
+    fn read_dates(&self) -> Option<String> {
+        let added = normalize_date(self.get("Date Added")).unwrap_or_default();
+        let read = normalize_date(self.get("Date Read")).unwrap_or_default();
+
+        match (self.get("Exclusive Shelf"), read.is_empty()) {
+            (_, false) => Some(format!("{}{}{}", added, PAIR_SEPARATOR, read)),
+            ("currently-reading", true) => Some(added),
+            _ => None,
+        }
+    }
+}
+
+fn to_import_row(row: &GoodreadsRow, mapping: &GoodreadsMapping, schema: &Schema) -> ImportRow {
+    let mut values = ImportRow::new();
+    let mut set = |attribute: &Option<String>, value: String| {
+        if let Some(attribute) = attribute {
+            values.insert(attribute.clone(), value);
+        }
+    };
+
+    set(&mapping.title, row.get("Title").to_string());
+    set(&mapping.isbn, row.isbn());
+    set(&mapping.pages, row.get("Number of Pages").to_string());
+    set(
+        &mapping.year,
+        match row.get("Original Publication Year") {
+            "" => row.get("Year Published").to_string(),
+            v => v.to_string(),
+        },
+    );
+    set(&mapping.shelves, row.shelves().join(SEPARATOR));
+    set(&mapping.read, row.read_dates().unwrap_or_default());
+
+    let author_is_collection = mapping.author.as_ref().is_some_and(|a| {
+        schema
+            .items
+            .iter()
+            .any(|i| &i.name == a && is_collection(&i.value))
+    });
+    let mut authors = vec![row.get("Author")];
+    if author_is_collection {
+        authors.extend(row.get("Additional Authors").split(','));
+    }
+    set(
+        &mapping.author,
+        authors
+            .into_iter()
+            .map(|a| a.trim())
+            .filter(|a| !a.is_empty())
+            .collect::<Vec<_>>()
+            .join(SEPARATOR),
+    );
+
+    // 0 means the book is not rated
+    let rating_settings = schema.items.iter().find_map(|i| match &i.value {
+        SchemaAttrType::Number(s) if Some(&i.name) == mapping.rating.as_ref() => Some(s),
+        _ => None,
+    });
+    if let (Some(settings), Ok(rating)) = (rating_settings, row.get("My Rating").parse::<f64>()) {
+        if rating > 0.0 {
+            set(
+                &mapping.rating,
+                rescale_rating(rating, GOODREADS_RATING_MAX, settings).to_string(),
+            );
+        }
+    }
+
+    values
+}
+
+/* Keys of records that are already in folder, by isbn and by title with author */
+async fn existing_keys(
+    ctx: &AppContext,
+    folder_relative: &Path,
+    mapping: &GoodreadsMapping,
+) -> Result<HashSet<String>, Box<ErrFR>> {
+    let mut keys = HashSet::new();
+
+    for record in get_files_by_path(ctx, folder_relative).await?.records {
+        let get = |attribute: &Option<String>| {
+            attribute
+                .as_ref()
+                .and_then(|a| attr_value_text(record.attrs.get(a)))
+        };
+
+        if let Some(isbn) = get(&mapping.isbn).map(|v| clean_isbn(&v)) {
+            if !isbn.is_empty() {
+                keys.insert(isbn);
+            }
+        }
+        if let Some(title) = get(&mapping.title) {
+            let author = mapping
+                .author
+                .as_ref()
+                .map(|_| get(&mapping.author).unwrap_or_default());
+            keys.insert(title_author_key(&title, author.as_deref()));
+        }
+    }
+
+    Ok(keys)
+}
+
+pub async fn import_goodreads(
+    ctx: &AppContext,
+    path: &Path,
+    folder_relative: &Path,
+    mapping: &GoodreadsMapping,
+) -> Result<ImportOutput, Box<ErrFR>> {
+    let schema = ctx
+        .schemas_cache
+        .get_schema(folder_relative)
+        .await
+        .ok_or(Box::new(
+            ErrFR::new("Target folder has no schema").raw(folder_relative.to_string_lossy()),
+        ))?
+        .schema;
+
+    let table = read_csv_file(path)?;
+    if !table.headers.iter().any(|h| h == "Book Id") {
+        return Err(Box::new(ErrFR::new("Not a Goodreads export").info(
+            "Export your library on Goodreads in My Books > Import and export",
+        )));
+    }
+
+    let mut seen = existing_keys(ctx, folder_relative, mapping).await?;
+    let mut records: Vec<ImportRecord> = vec![];
+    let mut duplicates: Vec<u32> = vec![];
+
+    for (index, row) in table.rows.iter().enumerate() {
+        let row = GoodreadsRow { table: &table, row };
+        let row_number = index as u32 + 1;
+
+        // Isbn is compared only when it's mapped, same as title and author
+        let isbn = match mapping.isbn {
+            Some(_) => row.isbn(),
+            None => String::new(),
+        };
+        let title_author = mapping.title.as_ref().map(|_| {
+            title_author_key(
+                row.get("Title"),
+                mapping.author.as_ref().map(|_| row.get("Author")),
+            )
+        });
+
+        let is_duplicate = (!isbn.is_empty() && seen.contains(&isbn))
+            || title_author.as_ref().is_some_and(|k| seen.contains(k));
+        if is_duplicate {
+            duplicates.push(row_number);
+            continue;
+        }
+
+        if !isbn.is_empty() {
+            seen.insert(isbn);
+        }
+        if let Some(title_author) = title_author {
+            seen.insert(title_author);
+        }
+
+        let review = row.get("My Review").replace("<br/>", "\n");
+        records.push(ImportRecord {
+            row: row_number,
+            values: to_import_row(&row, mapping, &schema),
+            markdown: (mapping.review_as_markdown && !review.is_empty())
+                .then(|| format!("{}\n", review)),
+        });
+    }
+
+    let mut output = import_rows(
+        ctx,
+        folder_relative,
+        &schema,
+        records,
+        &ImportSettings {
+            collection_separator: SEPARATOR,
+            date_pair_separator: PAIR_SEPARATOR,
+            filename_template: "{title}",
+            decimal_comma: false,
+        },
+    )
+    .await?;
+
+    output.result.duplicates = duplicates;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::csv::parse_csv;
+
+    const EXPORT: &str = "Book Id,Title,Author,Author l-f,Additional Authors,ISBN,ISBN13,My Rating,Average Rating,Publisher,Binding,Number of Pages,Year Published,Original Publication Year,Date Read,Date Added,Bookshelves,Bookshelves with positions,Exclusive Shelf,My Review,Spoiler,Private Notes,Read Count,Owned Copies
+234225,\"Dune (Dune, #1)\",Frank Herbert,\"Herbert, Frank\",,\"=\"\"0441013597\"\"\",\"=\"\"9780441013593\"\"\",4,4.27,Ace,Paperback,688,2005,1965,2024/03/01,2024/01/15,\"sci-fi, favorites\",\"sci-fi (#3), favorites (#1)\",read,Great<br/>book,,,1,0
+";
+
+    #[test]
+    fn title_author_keys_match() {
+        assert_eq!(
+            title_author_key("Dune (Dune, #1)", Some("Frank Herbert")),
+            title_author_key("Dune", Some("Herbert, Frank; Brian Herbert"))
+        );
+        assert_eq!(title_author_key("Dune", None), "dune|");
+        assert_ne!(
+            title_author_key("Dune", None),
+            title_author_key("Dune", Some("Frank Herbert"))
+        );
+    }
+
+    #[test]
+    fn reads_export_row() {
+        let table = parse_csv(EXPORT).unwrap();
+        let row = GoodreadsRow {
+            table: &table,
+            row: &table.rows[0],
+        };
+
+        assert_eq!(row.isbn(), "9780441013593");
+        assert_eq!(row.shelves(), vec!["read", "sci-fi", "favorites"]);
+        assert_eq!(row.read_dates(), Some("2024-01-15..2024-03-01".to_string()));
+        assert_eq!(title_key("Dune (Dune, #1)"), title_key("Dune"));
+        assert_eq!(author_key("Herbert, Frank"), author_key("Frank Herbert"));
+    }
+}
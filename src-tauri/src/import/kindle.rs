@@ -0,0 +1,564 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::cache_thing::cache_file;
+use crate::cache::query::get_files_by_path;
+use crate::core::core_state::AppContext;
+use crate::core::journal::{snapshot_file, JournalAction};
+use crate::files::utils::split_front_matter;
+use crate::import::{attr_value_text, match_key};
+use crate::schema::types::{find_schema_item, is_text, is_text_or_collection};
+use crate::utils::errorhandling::{ErrFR, ErrFRActionCode};
+
+const CLIPPING_SEPARATOR: &str = "==========";
+const SECTION_START: &str = "<!-- asom:kindle-highlights:start -->";
+const SECTION_END: &str = "<!-- asom:kindle-highlights:end -->";
+const SECTION_TITLE: &str = "## Highlights";
+// Below this books are shown without a match and user picks record by hand
+const MATCH_THRESHOLD: f32 = 0.75;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, TS)]
+#[ts(export)]
+pub enum ClippingKind {
+    Highlight,
+    Note,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+pub struct KindleClipping {
+    pub kind: ClippingKind,
+    /* "page 12 | Location 170-172" as Kindle writes it */
+    pub location: String,
+    /* YYYY-MM-DD, only for English Kindle locale */
+    pub added: Option<String>,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct KindleBook {
+    pub title: String,
+    pub author: Option<String>,
+    pub clippings: Vec<KindleClipping>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct KindleBookMatch {
+    pub title: String,
+    pub author: Option<String>,
+    pub clippings: u32,
+    /* Best matching record in folder, None when nothing is close enough */
+    pub record: Option<String>,
+    /* 0..1, how close title and author are */
+    pub score: f32,
+    /* Clippings that are not in the record yet */
+    pub new_clippings: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct KindleBookTarget {
+    pub title: String,
+    pub author: Option<String>,
+    /* Record path relative to root path */
+    pub record: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct KindleImportResult {
+    pub updated: Vec<String>,
+    pub added: u32,
+    /* Clippings that were already in records */
+    pub skipped: u32,
+    pub errors: Option<ErrFR>,
+}
+
+pub struct KindleImportOutput {
+    pub result: KindleImportResult,
+    pub undo: Vec<JournalAction>,
+}
+
+/* "Title (Author)", author can have parentheses inside title too, so last pair wins */
+fn parse_title_line(line: &str) -> (String, Option<String>) {
+    let line = line.trim_start_matches('\u{FEFF}').trim();
+
+    if let Some(stripped) = line.strip_suffix(')') {
+        if let Some((title, author)) = stripped.rsplit_once(" (") {
+            return (title.trim().to_string(), Some(author.trim().to_string()));
+        }
+    }
+    (line.to_string(), None)
+}
+
+fn parse_added(meta: &str) -> Option<String> {
+    let value = meta.split("Added on").nth(1)?.trim();
+    NaiveDateTime::parse_from_str(value, "%A, %B %d, %Y %I:%M:%S %p")
+        .ok()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+/* Bookmarks and empty clippings are skipped, books keep the order of the file */
+pub fn parse_clippings(content: &str) -> Vec<KindleBook> {
+    let mut books: Vec<KindleBook> = vec![];
+
+    for block in content.split(CLIPPING_SEPARATOR) {
+        let mut lines = block.lines().map(|l| l.trim()).skip_while(|l| l.is_empty());
+
+        let (Some(title_line), Some(meta)) = (lines.next(), lines.next()) else {
+            continue;
+        };
+
+        let kind = match meta {
+            m if m.contains("Your Highlight") => ClippingKind::Highlight,
+            m if m.contains("Your Note") => ClippingKind::Note,
+            _ => continue,
+        };
+
+        let text = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+
+        let location = meta
+            .trim_start_matches('-')
+            .split('|')
+            .filter(|p| !p.contains("Added on"))
+            .map(|p| {
+                p.trim()
+                    .trim_start_matches("Your Highlight on ")
+                    .trim_start_matches("Your Note on ")
+                    .trim()
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        let clipping = KindleClipping {
+            kind,
+            location,
+            added: parse_added(meta),
+            text,
+        };
+
+        let (title, author) = parse_title_line(title_line);
+        match books
+            .iter_mut()
+            .find(|b| b.title == title && b.author == author)
+        {
+            Some(book) => {
+                if !book.clippings.contains(&clipping) {
+                    book.clippings.push(clipping)
+                }
+            }
+            None => books.push(KindleBook {
+                title,
+                author,
+                clippings: vec![clipping],
+            }),
+        }
+    }
+
+    books
+}
+
+fn bigrams(value: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = match_key(value).chars().collect();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/* Dice coefficient on character pairs, tolerant to small typos and punctuation */
+pub fn similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let mut rest = b.clone();
+    let mut common = 0;
+    for pair in a.iter() {
+        if let Some(i) = rest.iter().position(|p| p == pair) {
+            rest.swap_remove(i);
+            common += 1;
+        }
+    }
+
+    (2 * common) as f32 / (a.len() + b.len()) as f32
+}
+
+/* Kindle titles often have subtitles or series in them, records often do not */
+fn short_title(title: &str) -> &str {
+    title
+        .split([':', '('])
+        .next()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .unwrap_or(title)
+}
+
+fn author_words(author: &str) -> Vec<String> {
+    let mut words: Vec<String> = author
+        .split(|c: char| !c.is_alphanumeric())
+        .map(match_key)
+        .filter(|w| w.len() > 1)
+        .collect();
+    words.sort();
+    words
+}
+
+fn match_score(book: &KindleBook, title: &str, author: Option<&str>) -> f32 {
+    let title_score = similarity(&book.title, title)
+        .max(similarity(short_title(&book.title), short_title(title)));
+
+    match (&book.author, author) {
+        (Some(a), Some(b)) => {
+            let (a, b) = (author_words(a), author_words(b));
+            let common = a.iter().filter(|w| b.contains(w)).count();
+            let author_score = match a.len().max(b.len()) {
+                0 => 0.0,
+                n => common as f32 / n as f32,
+            };
+            title_score * 0.8 + author_score * 0.2
+        }
+        _ => title_score,
+    }
+}
+
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/* Clipping texts that are already in managed section of markdown */
+fn existing_clippings(markdown: &str) -> HashSet<String> {
+    let Some(section) = markdown
+        .split_once(SECTION_START)
+        .and_then(|(_, rest)| rest.split_once(SECTION_END))
+        .map(|(s, _)| s)
+    else {
+        return HashSet::new();
+    };
+
+    section
+        .split("\n\n")
+        .filter_map(|block| {
+            let lines: Vec<&str> = block
+                .lines()
+                .filter(|l| !l.starts_with("> —") && !l.trim().is_empty())
+                .collect();
+            let text = lines
+                .iter()
+                .map(|l| {
+                    l.trim_start_matches('>')
+                        .trim()
+                        .split_once("**Note:** ")
+                        .map(|(_, t)| t)
+                        .unwrap_or(l.trim_start_matches('>').trim())
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            (!text.is_empty()).then(|| normalize_text(&text))
+        })
+        .collect()
+}
+
+fn render_clipping(clipping: &KindleClipping) -> String {
+    let source = match &clipping.added {
+        Some(added) => format!("{}, {}", clipping.location, added),
+        None => clipping.location.clone(),
+    };
+
+    match clipping.kind {
+        ClippingKind::Highlight => {
+            let quote = clipping
+                .text
+                .lines()
+                .map(|l| format!("> {}", l))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("{}\n> — {}", quote, source)
+        }
+        ClippingKind::Note => format!(
+            "**Note:** {}\n> — {}",
+            normalize_text(&clipping.text),
+            source
+        ),
+    }
+}
+
+/* Returns new markdown and number of added clippings, existing clippings are not repeated */
+pub fn merge_clippings(markdown: &str, clippings: &[KindleClipping]) -> (String, u32) {
+    let existing = existing_clippings(markdown);
+
+    let new: Vec<String> = clippings
+        .iter()
+        .filter(|c| !existing.contains(&normalize_text(&c.text)))
+        .map(render_clipping)
+        .collect();
+
+    if new.is_empty() {
+        return (markdown.to_string(), 0);
+    }
+
+    let added = new.len() as u32;
+    let rendered = new.join("\n\n");
+
+    let result = match markdown.split_once(SECTION_END) {
+        Some((before, after)) if markdown.contains(SECTION_START) => format!(
+            "{}\n\n{}\n\n{}{}",
+            before.trim_end(),
+            rendered,
+            SECTION_END,
+            after
+        ),
+        _ => {
+            let body = markdown.trim_end();
+            let gap = if body.is_empty() { "" } else { "\n\n" };
+            format!(
+                "{}{}{}\n\n{}\n\n{}\n\n{}\n",
+                body, gap, SECTION_TITLE, SECTION_START, rendered, SECTION_END
+            )
+        }
+    };
+
+    (result, added)
+}
+
+fn read_clippings_file(path: &Path) -> Result<Vec<KindleBook>, Box<ErrFR>> {
+    let bytes = fs::read(path).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error reading file")
+                .info(&path.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+    Ok(parse_clippings(&String::from_utf8_lossy(&bytes)))
+}
+
+fn markdown_of(raw: &str) -> &str {
+    split_front_matter(raw).map(|s| s.after).unwrap_or(raw)
+}
+
+pub async fn preview_kindle_import(
+    ctx: &AppContext,
+    path: &Path,
+    folder_relative: &Path,
+) -> Result<Vec<KindleBookMatch>, Box<ErrFR>> {
+    let schema = ctx
+        .schemas_cache
+        .get_schema_safe(folder_relative)
+        .await?
+        .schema;
+    let title_attr = find_schema_item(&schema, &["title", "name"], is_text);
+    let author_attr = find_schema_item(&schema, &["author", "authors"], is_text_or_collection);
+
+    let records = get_files_by_path(ctx, folder_relative).await?.records;
+    let books = read_clippings_file(path)?;
+
+    let mut result: Vec<KindleBookMatch> = vec![];
+
+    for book in books {
+        let best = records
+            .iter()
+            .filter_map(|r| {
+                let title = title_attr
+                    .as_ref()
+                    .and_then(|a| attr_value_text(r.attrs.get(a)))
+                    .or_else(|| {
+                        r.path
+                            .as_ref()
+                            .and_then(|p| Path::new(p).file_stem())
+                            .map(|s| s.to_string_lossy().to_string())
+                    })?;
+                let author = author_attr
+                    .as_ref()
+                    .and_then(|a| attr_value_text(r.attrs.get(a)));
+                Some((r, match_score(&book, &title, author.as_deref())))
+            })
+            .filter(|(_, score)| *score >= MATCH_THRESHOLD)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        let (record, score, new_clippings) = match best {
+            Some((r, score)) => {
+                let path = r.path.clone().unwrap_or_default();
+                let raw =
+                    fs::read_to_string(ctx.relative_path_to_absolute(Path::new(&path)).await?)
+                        .unwrap_or_default();
+                let (_, added) = merge_clippings(markdown_of(&raw), &book.clippings);
+                (Some(path), score, added)
+            }
+            None => (None, 0.0, book.clippings.len() as u32),
+        };
+
+        result.push(KindleBookMatch {
+            title: book.title,
+            author: book.author,
+            clippings: book.clippings.len() as u32,
+            record,
+            score,
+            new_clippings,
+        });
+    }
+
+    Ok(result)
+}
+
+/* Writes only markdown part, front matter stays byte to byte */
+async fn append_to_record(
+    ctx: &AppContext,
+    record: &str,
+    clippings: &[KindleClipping],
+) -> Result<(u32, u32, Option<JournalAction>), Box<ErrFR>> {
+    let path_relative = Path::new(record);
+    let path_absolute = ctx.relative_path_to_absolute(path_relative).await?;
+
+    let raw = fs::read_to_string(&path_absolute).map_err(|e| {
+        ErrFR::new("Error reading file")
+            .info(record)
+            .raw(e)
+            .action_c(ErrFRActionCode::FileReadRetry, "Retry")
+    })?;
+
+    let (head, markdown) = match split_front_matter(&raw) {
+        Some(v) => (&raw[..raw.len() - v.after.len()], v.after),
+        None => ("", raw.as_str()),
+    };
+
+    let (merged, added) = merge_clippings(markdown, clippings);
+    let skipped = clippings.len() as u32 - added;
+    if added == 0 {
+        return Ok((0, skipped, None));
+    }
+
+    let snapshot = snapshot_file(ctx, path_relative).await;
+    fs::write(&path_absolute, format!("{}{}", head, merged))
+        .map_err(|e| ErrFR::new("Error writing to disk").info(record).raw(e))?;
+    cache_file(ctx, &path_absolute).await?;
+
+    Ok((added, skipped, Some(snapshot)))
+}
+
+/* Targets come from preview after user confirmed or changed matched records */
+pub async fn import_kindle(
+    ctx: &AppContext,
+    path: &Path,
+    targets: Vec<KindleBookTarget>,
+) -> Result<KindleImportOutput, Box<ErrFR>> {
+    let books = read_clippings_file(path)?;
+
+    let mut result = KindleImportResult {
+        updated: vec![],
+        added: 0,
+        skipped: 0,
+        errors: None,
+    };
+    let mut undo: Vec<JournalAction> = vec![];
+    let mut err = ErrFR::new("Some highlights were not imported");
+
+    for target in targets {
+        let Some(book) = books
+            .iter()
+            .find(|b| b.title == target.title && b.author == target.author)
+        else {
+            err = err.sub(ErrFR::new("Book not found in clippings").info(&target.title));
+            continue;
+        };
+
+        match append_to_record(ctx, &target.record, &book.clippings).await {
+            Ok((added, skipped, snapshot)) => {
+                result.added += added;
+                result.skipped += skipped;
+                if let Some(snapshot) = snapshot {
+                    undo.push(snapshot);
+                    result.updated.push(target.record);
+                }
+            }
+            Err(e) => err = err.sub(e.info(&target.title)),
+        }
+    }
+
+    if !err.sub_errors.is_empty() {
+        result.errors = Some(err);
+    }
+
+    Ok(KindleImportOutput { result, undo })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIPPINGS: &str = "\u{FEFF}Dune (Dune Chronicles, #1) (Herbert, Frank)
+- Your Highlight on page 8 | Location 95-96 | Added on Sunday, March 3, 2024 10:15:32 PM
+
+I must not fear.
+Fear is the mind-killer.
+==========
+Dune (Dune Chronicles, #1) (Herbert, Frank)
+- Your Bookmark on page 9 | Location 100 | Added on Sunday, March 3, 2024 10:16:00 PM
+
+
+==========
+Dune (Dune Chronicles, #1) (Herbert, Frank)
+- Your Note on page 8 | Location 96 | Added on Sunday, March 3, 2024 10:17:00 PM
+
+Litany against fear
+==========
+Dune (Dune Chronicles, #1) (Herbert, Frank)
+- Your Highlight on page 8 | Location 95-96 | Added on Sunday, March 3, 2024 10:15:32 PM
+
+I must not fear.
+Fear is the mind-killer.
+==========
+";
+
+    #[test]
+    fn parses_and_groups_by_book() {
+        let books = parse_clippings(CLIPPINGS);
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].title, "Dune (Dune Chronicles, #1)");
+        assert_eq!(books[0].author.as_deref(), Some("Herbert, Frank"));
+        assert_eq!(books[0].clippings.len(), 2);
+        assert_eq!(books[0].clippings[0].location, "page 8 | Location 95-96");
+        assert_eq!(books[0].clippings[0].added.as_deref(), Some("2024-03-03"));
+        assert_eq!(books[0].clippings[1].kind, ClippingKind::Note);
+    }
+
+    #[test]
+    fn fuzzy_match() {
+        let book = &parse_clippings(CLIPPINGS)[0];
+        assert!(match_score(book, "Dune", Some("Frank Herbert")) > 0.9);
+        assert!(match_score(book, "Dune Messiah", Some("Frank Herbert")) < MATCH_THRESHOLD);
+    }
+
+    #[test]
+    fn merge_does_not_duplicate() {
+        let clippings = &parse_clippings(CLIPPINGS)[0].clippings;
+
+        let (first, added) = merge_clippings("My notes\n", clippings);
+        assert_eq!(added, 2);
+        assert!(first.starts_with("My notes\n\n## Highlights\n"));
+        assert!(first.contains("> I must not fear.\n> Fear is the mind-killer.\n> — page 8"));
+
+        let (second, added) = merge_clippings(&first, clippings);
+        assert_eq!(added, 0);
+        assert_eq!(first, second);
+
+        let mut more = clippings.clone();
+        more.push(KindleClipping {
+            kind: ClippingKind::Highlight,
+            location: "Location 200".to_string(),
+            added: None,
+            text: "The spice must flow.".to_string(),
+        });
+        let (third, added) = merge_clippings(&first, &more);
+        assert_eq!(added, 1);
+        assert!(third.contains("> The spice must flow.\n> — Location 200\n\n<!-- asom"));
+        assert_eq!(third.matches(SECTION_START).count(), 1);
+    }
+}
@@ -0,0 +1,475 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::cache_thing::cache_file;
+use crate::cache::query::RecordFromDb;
+use crate::core::core_state::AppContext;
+use crate::core::journal::JournalAction;
+use crate::files::metadata::get_default_metadata;
+use crate::files::read_save::save_file;
+use crate::schema::types::{AttrValue, DatePair, NumberSettings, Schema, SchemaAttrType};
+use crate::utils::errorhandling::ErrFR;
+use crate::utils::helpers::{sanitize_file_name, NAMES_SEPARATOR};
+
+pub mod calibre;
+pub mod csv;
+pub mod dump;
+pub mod goodreads;
+pub mod kindle;
+pub mod movies;
+
+const DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct ImportRowError {
+    /* 1-based, header is not counted */
+    pub row: u32,
+    pub error: ErrFR,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct ImportResult {
+    /* Paths of created records relative to root path */
+    pub created: Vec<String>,
+    /* Rows that were skipped, everything else is imported */
+    pub errors: Vec<ImportRowError>,
+    /* Rows that match existing records and were not imported */
+    pub duplicates: Vec<u32>,
+}
+
+pub struct ImportOutput {
+    pub result: ImportResult,
+    pub undo: Vec<JournalAction>,
+}
+
+/* Values in imported files as they come, keys are schema item names */
+pub type ImportRow = HashMap<String, String>;
+
+pub struct ImportRecord {
+    /* 1-based, header is not counted */
+    pub row: u32,
+    pub values: ImportRow,
+    pub markdown: Option<String>,
+}
+
+pub struct ImportSettings<'a> {
+    /* Joins items of collections and date pairs */
+    pub collection_separator: &'a str,
+    /* Goes between start and finish of a date pair */
+    pub date_pair_separator: &'a str,
+    /* Attribute names in curly braces are replaced with values, "{title} ({year})" */
+    pub filename_template: &'a str,
+    /* Numbers are written as "4,5", otherwise comma is not accepted in numbers */
+    pub decimal_comma: bool,
+}
+
+/* Dates are stored as YYYY-MM-DD, anything with time after date is cut */
+pub fn normalize_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    let date_part = value.split([' ', 'T']).next().unwrap_or(value);
+
+    DATE_FORMATS
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(date_part, f).ok())
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+fn split_collection(value: &str, separator: &str) -> Vec<String> {
+    match separator.trim().is_empty() {
+        true => vec![value.trim().to_string()],
+        false => value
+            .split(separator.trim())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+    }
+}
+
+fn date_or_err(value: &str) -> Result<String, Box<ErrFR>> {
+    normalize_date(value)
+        .ok_or_else(|| Box::new(ErrFR::new("Unable to parse date").info(value.trim())))
+}
+
+/* Converts text value into schema type, empty text is an empty value */
+pub fn convert_value(
+    value: &str,
+    schema_type: &SchemaAttrType,
+    settings: &ImportSettings,
+) -> Result<AttrValue, Box<ErrFR>> {
+    let value = value.trim();
+
+    if value.is_empty() {
+        return Ok(get_default_metadata(schema_type.clone()));
+    }
+
+    match schema_type {
+        SchemaAttrType::Text(_) | SchemaAttrType::Image(_) => {
+            Ok(AttrValue::String(Some(value.to_string())))
+        }
+        SchemaAttrType::Date(_) => Ok(AttrValue::String(Some(date_or_err(value)?))),
+        SchemaAttrType::Number(number_settings) => {
+            // "1,234" is a thousand in one locale and a fraction in another, guessing would corrupt data
+            let normalized = match settings.decimal_comma {
+                true => value.replace(',', "."),
+                false => value.to_string(),
+            };
+            let n = normalized
+                .parse::<f64>()
+                .map_err(|e| Box::new(ErrFR::new("Unable to parse number").info(value).raw(e)))?;
+            match number_settings.decimal_places.is_some_and(|v| v > 0) {
+                true => Ok(AttrValue::Float(Some(n))),
+                false if n.fract() != 0.0 => Err(Box::new(
+                    ErrFR::new("Number has a fraction, but attribute has no decimal places")
+                        .info(value),
+                )),
+                false => Ok(AttrValue::Integer(Some(n))),
+            }
+        }
+        SchemaAttrType::TextCollection(_) => Ok(AttrValue::StringVec(Some(split_collection(
+            value,
+            settings.collection_separator,
+        )))),
+        SchemaAttrType::DateCollection(_) => Ok(AttrValue::StringVec(Some(
+            split_collection(value, settings.collection_separator)
+                .iter()
+                .map(|v| date_or_err(v))
+                .collect::<Result<Vec<_>, _>>()?,
+        ))),
+        SchemaAttrType::DatesPairCollection(_) => {
+            let mut pairs = vec![];
+            for pair in split_collection(value, settings.collection_separator) {
+                let (started, finished) = match pair.split_once(settings.date_pair_separator.trim())
+                {
+                    Some((s, f)) => (s.trim(), f.trim()),
+                    None => (pair.as_str(), ""),
+                };
+                pairs.push(DatePair {
+                    started: (!started.is_empty())
+                        .then(|| date_or_err(started))
+                        .transpose()?,
+                    finished: (!finished.is_empty())
+                        .then(|| date_or_err(finished))
+                        .transpose()?,
+                });
+            }
+            Ok(AttrValue::DatePairVec(Some(pairs)))
+        }
+    }
+}
+
+/*
+    Moves rating from source scale into scale of schema item.
+    Max of item wins over stars count, items without both are assumed to use source scale.
+*/
+pub fn rescale_rating(value: f64, source_max: f64, settings: &NumberSettings) -> f64 {
+    let target_max = settings
+        .max
+        .or(settings.stars_count.map(f64::from))
+        .unwrap_or(source_max);
+
+    let scaled = value / source_max * target_max;
+    let precision = 10f64.powi(settings.decimal_places.unwrap_or(0) as i32);
+    (scaled * precision).round() / precision
+}
+
+/* Lowercase letters and digits only, used to compare titles and names from different sources */
+pub fn match_key(value: &str) -> String {
+    value
+        .chars()
+        .flat_map(|c| c.to_lowercase())
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/* "Adler, Mortimer J." and "Mortimer J. Adler" are the same person */
+pub fn author_key(author: &str) -> String {
+    let mut words: Vec<String> = author
+        .split(|c: char| !c.is_alphanumeric())
+        .map(match_key)
+        .filter(|w| !w.is_empty())
+        .collect();
+    words.sort();
+    words.join(" ")
+}
+
+/* Text form of a value for matching against imported data */
+pub fn attr_value_text(value: Option<&AttrValue>) -> Option<String> {
+    match value? {
+        AttrValue::String(Some(v)) => Some(v.clone()),
+        AttrValue::StringVec(Some(v)) => Some(v.join(NAMES_SEPARATOR)),
+        AttrValue::Integer(Some(v)) => Some((v.round() as i64).to_string()),
+        AttrValue::Float(Some(v)) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+/*
+    Fills {attribute} placeholders from row in one pass, values are inserted as they are.
+    Placeholders without value are dropped together with brackets around them, like "Title ({year})"
+*/
+pub fn render_file_name(template: &str, row: &ImportRow) -> String {
+    let mut name = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let key = &rest[start + 1..start + length];
+        let value = row.get(key).map(|v| v.trim()).unwrap_or("");
+        let before = &rest[..start];
+        let after = &rest[start + length + 1..];
+
+        match (
+            value.is_empty(),
+            before.chars().last(),
+            after.chars().next(),
+        ) {
+            (true, Some('('), Some(')')) | (true, Some('['), Some(']')) => {
+                name.push_str(&before[..before.len() - 1]);
+                rest = &after[1..];
+            }
+            _ => {
+                name.push_str(before);
+                name.push_str(value);
+                rest = after;
+            }
+        }
+    }
+    name.push_str(rest);
+
+    sanitize_file_name(&name)
+}
+
+/*
+    Creates one record per row in folder, names that are already taken get a number.
+    Values for attributes that are not in schema are ignored.
+*/
+pub async fn import_rows(
+    ctx: &AppContext,
+    folder_relative: &Path,
+    schema: &Schema,
+    records: Vec<ImportRecord>,
+    settings: &ImportSettings<'_>,
+) -> Result<ImportOutput, Box<ErrFR>> {
+    let mut created: Vec<String> = vec![];
+    let mut errors: Vec<ImportRowError> = vec![];
+    let mut undo: Vec<JournalAction> = vec![];
+
+    for ImportRecord {
+        row: row_number,
+        values: row,
+        markdown,
+    } in records
+    {
+        let mut attrs: HashMap<String, AttrValue> = HashMap::new();
+        let mut row_err = ErrFR::new("Row was not imported");
+
+        for item in schema.items.iter() {
+            let Some(raw) = row.get(&item.name) else {
+                continue;
+            };
+            match convert_value(raw, &item.value, settings) {
+                Ok(v) => {
+                    attrs.insert(item.name.clone(), v);
+                }
+                Err(e) => row_err = row_err.sub(e.info(&item.name)),
+            }
+        }
+
+        if !row_err.sub_errors.is_empty() {
+            errors.push(ImportRowError {
+                row: row_number,
+                error: row_err,
+            });
+            continue;
+        }
+
+        let file_name = format!("{}.md", render_file_name(settings.filename_template, &row));
+        let record = RecordFromDb {
+            path: Some(
+                folder_relative
+                    .join(file_name)
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            attrs,
+            markdown,
+            ..RecordFromDb::default()
+        };
+
+        match save_file(ctx, record, false, true).await {
+            Ok(saved) => {
+                // Without waiting for watcher, import that runs right after sees created records
+                let path_absolute = ctx
+                    .relative_path_to_absolute(Path::new(&saved.path))
+                    .await?;
+                if let Err(e) = cache_file(ctx, &path_absolute).await {
+                    log::warn!("import: unable to cache {} {:?}", saved.path, e);
+                }
+                undo.push(JournalAction::MoveToTrash {
+                    path: saved.path.clone(),
+                });
+                created.push(saved.path);
+            }
+            Err(e) => errors.push(ImportRowError {
+                row: row_number,
+                error: *e,
+            }),
+        }
+    }
+
+    Ok(ImportOutput {
+        result: ImportResult {
+            created,
+            errors,
+            duplicates: vec![],
+        },
+        undo,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::types::{
+        DateCollectionSettings, DatesPairCollectionSettings, NumberSettings, TextCollectionSettings,
+    };
+
+    const SETTINGS: ImportSettings = ImportSettings {
+        collection_separator: ";",
+        date_pair_separator: " - ",
+        filename_template: "{title}",
+        decimal_comma: false,
+    };
+
+    #[test]
+    fn dates() {
+        assert_eq!(normalize_date("2024/03/09"), Some("2024-03-09".to_string()));
+        assert_eq!(normalize_date("09.03.2024"), Some("2024-03-09".to_string()));
+        assert_eq!(
+            normalize_date("2024-03-09 12:00:00"),
+            Some("2024-03-09".to_string())
+        );
+        assert_eq!(normalize_date("March 9"), None);
+    }
+
+    #[test]
+    fn converts_values() {
+        let integer = SchemaAttrType::Number(NumberSettings::default());
+        let float = SchemaAttrType::Number(NumberSettings {
+            decimal_places: Some(1),
+            ..NumberSettings::default()
+        });
+        assert_eq!(
+            convert_value(" 4 ", &integer, &SETTINGS).unwrap(),
+            AttrValue::Integer(Some(4.0))
+        );
+        assert!(convert_value("4.5", &integer, &SETTINGS).is_err());
+        assert!(convert_value("1,234", &float, &SETTINGS).is_err());
+        assert_eq!(
+            convert_value(
+                " 4,5 ",
+                &float,
+                &ImportSettings {
+                    decimal_comma: true,
+                    ..SETTINGS
+                }
+            )
+            .unwrap(),
+            AttrValue::Float(Some(4.5))
+        );
+        assert_eq!(
+            convert_value(
+                "a; b;;",
+                &SchemaAttrType::TextCollection(TextCollectionSettings::default()),
+                &SETTINGS
+            )
+            .unwrap(),
+            AttrValue::StringVec(Some(vec!["a".to_string(), "b".to_string()]))
+        );
+        assert_eq!(
+            convert_value(
+                "",
+                &SchemaAttrType::DateCollection(DateCollectionSettings::default()),
+                &SETTINGS
+            )
+            .unwrap(),
+            AttrValue::StringVec(None)
+        );
+        assert_eq!(
+            convert_value(
+                "2024/01/01 - 2024/01/05; 2025-02-01",
+                &SchemaAttrType::DatesPairCollection(DatesPairCollectionSettings::default()),
+                &SETTINGS
+            )
+            .unwrap(),
+            AttrValue::DatePairVec(Some(vec![
+                DatePair {
+                    started: Some("2024-01-01".to_string()),
+                    finished: Some("2024-01-05".to_string()),
+                },
+                DatePair {
+                    started: Some("2025-02-01".to_string()),
+                    finished: None,
+                },
+            ]))
+        );
+        assert!(convert_value(
+            "soon",
+            &SchemaAttrType::Number(NumberSettings::default()),
+            &SETTINGS
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn ratings() {
+        let stars = NumberSettings {
+            stars_count: Some(10),
+            ..NumberSettings::default()
+        };
+        assert_eq!(rescale_rating(4.0, 5.0, &stars), 8.0);
+
+        let decimal = NumberSettings {
+            max: Some(5.0),
+            decimal_places: Some(1),
+            ..NumberSettings::default()
+        };
+        assert_eq!(rescale_rating(7.0, 10.0, &decimal), 3.5);
+        assert_eq!(rescale_rating(3.0, 5.0, &NumberSettings::default()), 3.0);
+    }
+
+    #[test]
+    fn file_names() {
+        let row = HashMap::from([
+            ("title".to_string(), "AC/DC: Live?".to_string()),
+            ("year".to_string(), "".to_string()),
+        ]);
+        assert_eq!(render_file_name("{title} ({year})", &row), "AC-DC- Live");
+        assert_eq!(render_file_name("{missing}", &row), "Untitled");
+
+        // Values are not templates, their braces and brackets are kept
+        let row = HashMap::from([
+            ("title".to_string(), "Set {a} theory []".to_string()),
+            ("author".to_string(), "{title}".to_string()),
+            ("year".to_string(), "1999".to_string()),
+        ]);
+        assert_eq!(
+            render_file_name("{title} [{year}] {author}", &row),
+            "Set {a} theory [] [1999] {title}"
+        );
+
+        // Names that are not templates keep their brackets
+        assert_eq!(
+            sanitize_file_name("Books {owned} [2024] () in review"),
+            "Books {owned} [2024] () in review"
+        );
+    }
+}
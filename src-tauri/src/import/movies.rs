@@ -0,0 +1,489 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::query::get_files_by_path;
+use crate::core::core_state::AppContext;
+use crate::import::csv::{read_csv_file, CsvTable};
+use crate::import::{
+    attr_value_text, import_rows, match_key, normalize_date, rescale_rating, ImportOutput,
+    ImportRecord, ImportRow, ImportSettings,
+};
+use crate::schema::types::{
+    find_schema_item, is_collection, is_date, is_dates, is_number, is_text, is_text_or_collection,
+    Schema, SchemaAttrType,
+};
+use crate::utils::errorhandling::ErrFR;
+
+const SEPARATOR: &str = ";";
+const PAIR_SEPARATOR: &str = "..";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, TS)]
+#[ts(export)]
+pub enum MovieSource {
+    /* diary.csv, ratings.csv or watched.csv */
+    Letterboxd,
+    /* ratings.csv from "Your ratings" page */
+    Imdb,
+}
+
+impl MovieSource {
+    fn rating_max(&self) -> f64 {
+        match self {
+            MovieSource::Letterboxd => 5.0,
+            MovieSource::Imdb => 10.0,
+        }
+    }
+
+    fn detect(headers: &[String]) -> Option<Self> {
+        let has = |name: &str| headers.iter().any(|h| h == name);
+
+        match (has("Letterboxd URI"), has("Const") && has("Your Rating")) {
+            (true, _) => Some(MovieSource::Letterboxd),
+            (_, true) => Some(MovieSource::Imdb),
+            _ => None,
+        }
+    }
+}
+
+/* Schema item names for movie fields, fields without item are not imported */
+#[derive(Serialize, Deserialize, Clone, Debug, Default, TS)]
+#[ts(export)]
+pub struct MovieMapping {
+    pub title: Option<String>,
+    pub year: Option<String>,
+    /* Release date, only IMDb has it */
+    pub premiere: Option<String>,
+    pub director: Option<String>,
+    pub rating: Option<String>,
+    /* DateCollection or DatesPairCollection, every rewatch is a separate date */
+    pub watched: Option<String>,
+    /* Tags from Letterboxd and genres from IMDb */
+    pub tags: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct MoviePreviewEntry {
+    /* First row of the movie in source file */
+    pub row: u32,
+    /* Values that will be written, keyed by schema item names */
+    pub values: ImportRow,
+    /* There is a record with the same title and year already */
+    pub duplicate: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct MovieImportPreview {
+    pub source: MovieSource,
+    pub mapping: MovieMapping,
+    pub entries: Vec<MoviePreviewEntry>,
+}
+
+/* All rows about one movie, diary has a row per watch */
+#[derive(Debug, Default)]
+struct MovieEntry {
+    row: u32,
+    title: String,
+    year: String,
+    premiere: Option<String>,
+    director: Option<String>,
+    rating: Option<f64>,
+    watched: Vec<String>,
+    tags: Vec<String>,
+}
+
+fn movie_key(title: &str, year: &str) -> String {
+    format!("{}|{}", match_key(title), year)
+}
+
+impl MovieEntry {
+    /* Rows of the same movie in export */
+    fn key(&self) -> String {
+        movie_key(&self.title, &self.year)
+    }
+
+    /*
+        Key for matching records in folder, built from the same mapped fields as existing_keys:
+        year is compared only when schema has year or premiere mapped
+    */
+    fn existing_key(&self, mapping: &MovieMapping) -> String {
+        match mapping.year.is_some() || mapping.premiere.is_some() {
+            true => movie_key(&self.title, &self.year),
+            false => movie_key(&self.title, ""),
+        }
+    }
+}
+
+pub fn guess_movie_mapping(schema: &Schema) -> MovieMapping {
+    MovieMapping {
+        title: find_schema_item(schema, &["title", "name"], is_text),
+        year: find_schema_item(schema, &["year"], is_number),
+        premiere: find_schema_item(schema, &["premiere", "released", "releaseDate"], is_date),
+        director: find_schema_item(schema, &["director", "directors"], is_text_or_collection),
+        rating: find_schema_item(schema, &["myRating", "rating", "score"], is_number),
+        watched: find_schema_item(schema, &["watched", "seen", "watchDates"], is_dates),
+        tags: find_schema_item(schema, &["tags", "genres"], is_collection),
+    }
+}
+
+fn cell<'a>(table: &CsvTable, row: &'a [String], column: &str) -> &'a str {
+    table
+        .headers
+        .iter()
+        .position(|h| h == column)
+        .and_then(|i| row.get(i))
+        .map(|v| v.trim())
+        .unwrap_or("")
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn read_row(table: &CsvTable, row: &[String], source: MovieSource) -> MovieEntry {
+    let get = |column: &str| cell(table, row, column);
+    let some = |v: &str| (!v.is_empty()).then(|| v.to_string());
+
+    match source {
+        MovieSource::Letterboxd => MovieEntry {
+            title: get("Name").to_string(),
+            year: get("Year").to_string(),
+            rating: get("Rating").parse().ok(),
+            // ratings.csv and watched.csv only have date of logging, it's not a watch date
+            watched: normalize_date(get("Watched Date")).into_iter().collect(),
+            tags: split_list(get("Tags")).collect(),
+            ..MovieEntry::default()
+        },
+        MovieSource::Imdb => MovieEntry {
+            title: get("Title").to_string(),
+            year: get("Year").to_string(),
+            premiere: normalize_date(get("Release Date")),
+            director: some(get("Directors")),
+            rating: get("Your Rating").parse().ok(),
+            // Closest thing to watch date IMDb has
+            watched: normalize_date(get("Date Rated")).into_iter().collect(),
+            tags: split_list(get("Genres")).collect(),
+            ..MovieEntry::default()
+        },
+    }
+}
+
+/* Groups rows by movie, later rows win for rating since diary is sorted by date */
+fn read_entries(table: &CsvTable, source: MovieSource) -> Vec<MovieEntry> {
+    let mut entries: Vec<MovieEntry> = vec![];
+    // Position in entries by key, keeps first appearance order
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for (index, row) in table.rows.iter().enumerate() {
+        let mut entry = read_row(table, row, source);
+        entry.row = index as u32 + 1;
+
+        if entry.title.is_empty() {
+            continue;
+        }
+
+        match positions.get(&entry.key()).map(|i| &mut entries[*i]) {
+            Some(existing) => {
+                existing.rating = entry.rating.or(existing.rating);
+                for date in entry.watched {
+                    if !existing.watched.contains(&date) {
+                        existing.watched.push(date);
+                    }
+                }
+                for tag in entry.tags {
+                    if !existing.tags.contains(&tag) {
+                        existing.tags.push(tag);
+                    }
+                }
+            }
+            None => {
+                positions.insert(entry.key(), entries.len());
+                entries.push(entry);
+            }
+        }
+    }
+
+    for entry in entries.iter_mut() {
+        entry.watched.sort();
+    }
+
+    entries
+}
+
+fn item_type<'a>(schema: &'a Schema, attribute: &Option<String>) -> Option<&'a SchemaAttrType> {
+    let attribute = attribute.as_ref()?;
+    schema
+        .items
+        .iter()
+        .find(|i| &i.name == attribute)
+        .map(|i| &i.value)
+}
+
+fn to_import_row(
+    entry: &MovieEntry,
+    mapping: &MovieMapping,
+    schema: &Schema,
+    source: MovieSource,
+) -> ImportRow {
+    let mut values = ImportRow::new();
+    let mut set = |attribute: &Option<String>, value: String| {
+        if let (Some(attribute), false) = (attribute, value.is_empty()) {
+            values.insert(attribute.clone(), value);
+        }
+    };
+
+    set(&mapping.title, entry.title.clone());
+    set(&mapping.year, entry.year.clone());
+    set(
+        &mapping.premiere,
+        entry.premiere.clone().unwrap_or_default(),
+    );
+    set(&mapping.tags, entry.tags.join(SEPARATOR));
+    set(
+        &mapping.director,
+        entry
+            .director
+            .as_deref()
+            .map(|d| split_list(d).collect::<Vec<_>>().join(SEPARATOR))
+            .unwrap_or_default(),
+    );
+
+    let watched = match item_type(schema, &mapping.watched) {
+        Some(SchemaAttrType::DatesPairCollection(_)) => entry
+            .watched
+            .iter()
+            .map(|d| format!("{}{}{}", d, PAIR_SEPARATOR, d))
+            .collect::<Vec<_>>()
+            .join(SEPARATOR),
+        _ => entry.watched.join(SEPARATOR),
+    };
+    set(&mapping.watched, watched);
+
+    if let (Some(SchemaAttrType::Number(settings)), Some(rating)) =
+        (item_type(schema, &mapping.rating), entry.rating)
+    {
+        if rating > 0.0 {
+            set(
+                &mapping.rating,
+                rescale_rating(rating, source.rating_max(), settings).to_string(),
+            );
+        }
+    }
+
+    values
+}
+
+async fn existing_keys(
+    ctx: &AppContext,
+    folder_relative: &Path,
+    mapping: &MovieMapping,
+) -> Result<HashSet<String>, Box<ErrFR>> {
+    let mut keys = HashSet::new();
+
+    for record in get_files_by_path(ctx, folder_relative).await?.records {
+        let get = |attribute: &Option<String>| {
+            attribute
+                .as_ref()
+                .and_then(|a| attr_value_text(record.attrs.get(a)))
+        };
+
+        let Some(title) = get(&mapping.title) else {
+            continue;
+        };
+        let year = get(&mapping.year)
+            .or(get(&mapping.premiere).and_then(|p| p.get(..4).map(|y| y.to_string())))
+            .unwrap_or_default();
+
+        keys.insert(movie_key(&title, &year));
+    }
+
+    Ok(keys)
+}
+
+struct PreparedImport {
+    schema: Schema,
+    source: MovieSource,
+    mapping: MovieMapping,
+    entries: Vec<(MovieEntry, bool)>,
+}
+
+async fn prepare(
+    ctx: &AppContext,
+    path: &Path,
+    folder_relative: &Path,
+    mapping: Option<MovieMapping>,
+) -> Result<PreparedImport, Box<ErrFR>> {
+    let schema = ctx
+        .schemas_cache
+        .get_schema(folder_relative)
+        .await
+        .ok_or(Box::new(
+            ErrFR::new("Target folder has no schema").raw(folder_relative.to_string_lossy()),
+        ))?
+        .schema;
+
+    let table = read_csv_file(path)?;
+    let source = MovieSource::detect(&table.headers).ok_or(Box::new(
+        ErrFR::new("Unknown file format").info("Expected Letterboxd or IMDb export"),
+    ))?;
+
+    let mapping = mapping.unwrap_or_else(|| guess_movie_mapping(&schema));
+    let existing = existing_keys(ctx, folder_relative, &mapping).await?;
+
+    let entries = read_entries(&table, source)
+        .into_iter()
+        .map(|e| {
+            let duplicate = existing.contains(&e.existing_key(&mapping));
+            (e, duplicate)
+        })
+        .collect();
+
+    Ok(PreparedImport {
+        schema,
+        source,
+        mapping,
+        entries,
+    })
+}
+
+/* Nothing is written, mapping is guessed from schema when not passed */
+pub async fn preview_movie_import(
+    ctx: &AppContext,
+    path: &Path,
+    folder_relative: &Path,
+    mapping: Option<MovieMapping>,
+) -> Result<MovieImportPreview, Box<ErrFR>> {
+    let prepared = prepare(ctx, path, folder_relative, mapping).await?;
+
+    let entries = prepared
+        .entries
+        .iter()
+        .map(|(e, duplicate)| MoviePreviewEntry {
+            row: e.row,
+            values: to_import_row(e, &prepared.mapping, &prepared.schema, prepared.source),
+            duplicate: *duplicate,
+        })
+        .collect();
+
+    Ok(MovieImportPreview {
+        source: prepared.source,
+        mapping: prepared.mapping,
+        entries,
+    })
+}
+
+pub async fn import_movies(
+    ctx: &AppContext,
+    path: &Path,
+    folder_relative: &Path,
+    mapping: MovieMapping,
+) -> Result<ImportOutput, Box<ErrFR>> {
+    let prepared = prepare(ctx, path, folder_relative, Some(mapping)).await?;
+
+    let mut records: Vec<ImportRecord> = vec![];
+    let mut duplicates: Vec<u32> = vec![];
+
+    for (entry, duplicate) in prepared.entries.iter() {
+        if *duplicate {
+            duplicates.push(entry.row);
+            continue;
+        }
+        records.push(ImportRecord {
+            row: entry.row,
+            values: to_import_row(entry, &prepared.mapping, &prepared.schema, prepared.source),
+            markdown: None,
+        });
+    }
+
+    let filename_template = match &prepared.mapping.title {
+        Some(title) => format!("{{{}}}", title),
+        None => "Untitled".to_string(),
+    };
+
+    let mut output = import_rows(
+        ctx,
+        folder_relative,
+        &prepared.schema,
+        records,
+        &ImportSettings {
+            collection_separator: SEPARATOR,
+            date_pair_separator: PAIR_SEPARATOR,
+            filename_template: &filename_template,
+            decimal_comma: false,
+        },
+    )
+    .await?;
+
+    output.result.duplicates = duplicates;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::csv::parse_csv;
+
+    #[test]
+    fn groups_letterboxd_rewatches() {
+        let table = parse_csv(
+            "Date,Name,Year,Letterboxd URI,Rating,Rewatch,Tags,Watched Date\n\
+             2023-01-02,Heat,1995,https://boxd.it/a,4,,\"crime, la\",2023-01-01\n\
+             2024-05-02,Heat,1995,https://boxd.it/b,4.5,Yes,crime,2024-05-01\n\
+             2024-06-01,Alien,1979,https://boxd.it/c,,,,2024-06-01\n",
+        )
+        .unwrap();
+
+        let source = MovieSource::detect(&table.headers).unwrap();
+        assert_eq!(source, MovieSource::Letterboxd);
+
+        let entries = read_entries(&table, source);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].watched, vec!["2023-01-01", "2024-05-01"]);
+        assert_eq!(entries[0].rating, Some(4.5));
+        assert_eq!(entries[0].tags, vec!["crime", "la"]);
+        assert_eq!(entries[1].rating, None);
+    }
+
+    #[test]
+    fn reads_imdb_ratings() {
+        let table = parse_csv(
+            "Const,Your Rating,Date Rated,Title,Original Title,URL,Title Type,IMDb Rating,Runtime (mins),Year,Genres,Num Votes,Release Date,Directors\n\
+             tt0078748,8,2024-06-02,Alien,Alien,https://www.imdb.com/title/tt0078748,Movie,8.5,117,1979,\"Horror, Sci-Fi\",900000,1979-05-25,Ridley Scott\n",
+        )
+        .unwrap();
+
+        let source = MovieSource::detect(&table.headers).unwrap();
+        assert_eq!(source, MovieSource::Imdb);
+
+        let entry = &read_entries(&table, source)[0];
+        assert_eq!(entry.premiere.as_deref(), Some("1979-05-25"));
+        assert_eq!(entry.director.as_deref(), Some("Ridley Scott"));
+        assert_eq!(entry.watched, vec!["2024-06-02"]);
+        assert_eq!(entry.tags, vec!["Horror", "Sci-Fi"]);
+    }
+
+    #[test]
+    fn existing_key_uses_year_only_when_mapped() {
+        let entry = MovieEntry {
+            title: "Alien".to_string(),
+            year: "1979".to_string(),
+            ..MovieEntry::default()
+        };
+        let mut mapping = MovieMapping {
+            title: Some("title".to_string()),
+            ..MovieMapping::default()
+        };
+        // Records in folder have no year to compare with
+        assert_eq!(entry.existing_key(&mapping), movie_key("Alien", ""));
+
+        mapping.premiere = Some("premiere".to_string());
+        assert_eq!(entry.existing_key(&mapping), movie_key("Alien", "1979"));
+    }
+}
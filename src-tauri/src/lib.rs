@@ -1,29 +1,78 @@
+mod backup;
 mod cache;
 mod core;
 mod emitter;
+mod export;
 mod files;
+mod import;
 mod schema;
+mod stats;
 #[cfg(test)]
 mod tests;
 mod utils;
 mod watcher;
 
 use core::core_state::CoreStateManager;
+use core::journal::{snapshot_file, JournalAction, JournalStatus};
 use std::{collections::HashMap, path::PathBuf};
 
+use backup::archive::{
+    create_backup, restore_backup, BackupOptions, BackupRestoreResult, BackupResult,
+};
+use backup::schedule::{
+    get_backup_schedule_from_storage, list_scheduled_backups, set_backup_schedule_to_storage,
+    start_backup_scheduler, BackupSchedule, ScheduledBackup,
+};
 use cache::query::{
     get_all_folders, get_all_folders_by_schema, get_all_tags, get_files_by_path,
-    FolderListGetResult, RecordFromDb, RecordListGetResult,
+    get_unmanaged_files_by_path, FolderListGetResult, RecordFromDb, RecordListGetResult,
+    UnmanagedRecord,
+};
+use emitter::{emit_event_to_frontend, IPCEmitEvent};
+use export::citation::{
+    export_citations, load_citation_mapping, save_citation_mapping, CitationExportResult,
+    CitationFormat, CitationMapping,
 };
+use export::csv::{export_csv, CsvExportOptions, CsvExportResult};
+use export::ical::{export_ical, IcalExportOptions, IcalExportResult};
+use export::json::{export_dump, DumpExportOptions, DumpExportResult};
+use export::site::{export_site, SiteExportOptions, SiteExportResult};
+use files::assets::{
+    get_asset_report, import_asset, trash_orphaned_assets, AssetCleanupResult, AssetImportResult,
+    AssetReport, AssetSource,
+};
+use files::batch::{batch_edit, BatchEditResult, BatchOperation, BatchTarget};
+use files::health::{
+    apply_health_fixes, check_vault_health, HealthFix, HealthFixResult, HealthReport,
+};
+use files::markdown::{decode_percent, render_record_markdown, RenderedMarkdown};
 use files::read_save::{
-    read_file_by_path, save_file, FileReadMode, RecordReadResult, RecordSaveResult,
+    read_file_by_path, save_file, update_attributes, FileReadMode, RecordReadResult,
+    RecordSaveResult,
+};
+use files::thumbnails::ThumbnailSize;
+use files::trash::{delete_to_trash, list_trashed_records, restore_from_trash, TrashedRecord};
+use files::unmanaged::{
+    create_schema_for_unmanaged, move_unmanaged_to_folder, read_unmanaged_file,
+    UnmanagedMoveResult, UnmanagedReadResult,
 };
-use schema::schema_cache::SchemaResult;
-use schema::types::Schema;
+use import::calibre::{guess_calibre_mapping, import_calibre, CalibreImportResult, CalibreMapping};
+use import::csv::{import_csv, preview_csv, CsvImportOptions, CsvPreview};
+use import::dump::import_dump;
+use import::goodreads::{guess_goodreads_mapping, import_goodreads, GoodreadsMapping};
+use import::kindle::{
+    import_kindle, preview_kindle_import, KindleBookMatch, KindleBookTarget, KindleImportResult,
+};
+use import::movies::{import_movies, preview_movie_import, MovieImportPreview, MovieMapping};
+use import::ImportResult;
+use schema::schema_cache::{locate_schema_and_folder, SchemaResult};
+use schema::types::{AttrValue, Schema};
 use serde::Serialize;
+use stats::aggregate::{aggregate, AggregateOptions, AggregateResult};
+use stats::review::{year_review, YearReview, YearReviewOptions};
 use tauri::test::{mock_builder, MockRuntime};
 use tauri::{AppHandle, Manager};
-use tokio::fs::create_dir;
+use tokio::fs::{create_dir, rename};
 use tokio::runtime::Runtime;
 use ts_rs::TS;
 use utils::errorhandling::ErrFR;
@@ -55,9 +104,49 @@ type IPCGetSchemas = Result<HashMap<String, Schema>, Box<ErrFR>>;
 type IPCLoadSchema = Result<Schema, Box<ErrFR>>;
 type IPCSaveSchema = Result<Schema, Box<ErrFR>>;
 type IPCSaveFile = Result<RecordSaveResult, Box<ErrFR>>;
+type IPCUpdateAttributes = Result<RecordSaveResult, Box<ErrFR>>;
+type IPCBatchEdit = Result<BatchEditResult, Box<ErrFR>>;
 type IPCResolveSchemaPath = Result<Option<SchemaResult>, Box<ErrFR>>;
-type IPCDeleteFile = Result<(), Box<ErrFR>>;
+type IPCDeleteFile = Result<Option<TrashedRecord>, Box<ErrFR>>;
+type IPCListTrash = Result<Vec<TrashedRecord>, Box<ErrFR>>;
+type IPCRestoreFromTrash = Result<PathBuf, Box<ErrFR>>;
 type IPCCreateFolderForDefaultSchema = Result<PathBuf, Box<ErrFR>>;
+type IPCRenamePath = Result<PathBuf, Box<ErrFR>>;
+type IPCJournal = Result<JournalStatus, Box<ErrFR>>;
+type IPCImportImage = Result<AssetImportResult, Box<ErrFR>>;
+type IPCGetThumbnail = Result<PathBuf, Box<ErrFR>>;
+type IPCGetAssetReport = Result<AssetReport, Box<ErrFR>>;
+type IPCTrashOrphanedAssets = Result<AssetCleanupResult, Box<ErrFR>>;
+type IPCCheckVaultHealth = Result<HealthReport, Box<ErrFR>>;
+type IPCApplyHealthFixes = Result<HealthFixResult, Box<ErrFR>>;
+type IPCGetUnmanagedFiles = Result<Vec<UnmanagedRecord>, Box<ErrFR>>;
+type IPCReadUnmanagedFile = Result<UnmanagedReadResult, Box<ErrFR>>;
+type IPCCreateSchemaForUnmanaged = Result<Schema, Box<ErrFR>>;
+type IPCMoveUnmanagedToFolder = Result<UnmanagedMoveResult, Box<ErrFR>>;
+type IPCExportCsv = Result<CsvExportResult, Box<ErrFR>>;
+type IPCPreviewCsv = Result<CsvPreview, Box<ErrFR>>;
+type IPCImportCsv = Result<ImportResult, Box<ErrFR>>;
+type IPCGuessGoodreadsMapping = Result<GoodreadsMapping, Box<ErrFR>>;
+type IPCImportGoodreads = Result<ImportResult, Box<ErrFR>>;
+type IPCPreviewMovieImport = Result<MovieImportPreview, Box<ErrFR>>;
+type IPCImportMovies = Result<ImportResult, Box<ErrFR>>;
+type IPCPreviewKindleImport = Result<Vec<KindleBookMatch>, Box<ErrFR>>;
+type IPCImportKindle = Result<KindleImportResult, Box<ErrFR>>;
+type IPCGuessCalibreMapping = Result<CalibreMapping, Box<ErrFR>>;
+type IPCImportCalibre = Result<CalibreImportResult, Box<ErrFR>>;
+type IPCExportDump = Result<DumpExportResult, Box<ErrFR>>;
+type IPCImportDump = Result<ImportResult, Box<ErrFR>>;
+type IPCExportSite = Result<SiteExportResult, Box<ErrFR>>;
+type IPCRenderMarkdown = Result<RenderedMarkdown, Box<ErrFR>>;
+type IPCCreateBackup = Result<BackupResult, Box<ErrFR>>;
+type IPCRestoreBackup = Result<BackupRestoreResult, Box<ErrFR>>;
+type IPCBackupSchedule = Result<BackupSchedule, Box<ErrFR>>;
+type IPCListBackups = Result<Vec<ScheduledBackup>, Box<ErrFR>>;
+type IPCExportIcal = Result<IcalExportResult, Box<ErrFR>>;
+type IPCCitationMapping = Result<CitationMapping, Box<ErrFR>>;
+type IPCExportCitations = Result<CitationExportResult, Box<ErrFR>>;
+type IPCAggregate = Result<AggregateResult, Box<ErrFR>>;
+type IPCYearReview = Result<YearReview, Box<ErrFR>>;
 #[derive(TS)]
 #[ts(export)]
 #[allow(dead_code)]
@@ -76,9 +165,54 @@ struct IPCResponces {
     c_load_schema: IPCLoadSchema,
     c_save_schema: IPCSaveSchema,
     c_save_file: IPCSaveFile,
+    c_update_attributes: IPCUpdateAttributes,
+    c_batch_edit: IPCBatchEdit,
     c_resolve_schema_path: IPCResolveSchemaPath,
     c_delete_to_trash: IPCDeleteFile,
+    c_list_trash: IPCListTrash,
+    c_restore_from_trash: IPCRestoreFromTrash,
     c_create_folder_for_default_schema: IPCCreateFolderForDefaultSchema,
+    c_rename_path: IPCRenamePath,
+    c_get_journal: IPCJournal,
+    c_undo: IPCJournal,
+    c_redo: IPCJournal,
+    c_import_image: IPCImportImage,
+    c_import_image_bytes: IPCImportImage,
+    c_get_thumbnail: IPCGetThumbnail,
+    c_get_asset_report: IPCGetAssetReport,
+    c_trash_orphaned_assets: IPCTrashOrphanedAssets,
+    c_check_vault_health: IPCCheckVaultHealth,
+    c_apply_health_fixes: IPCApplyHealthFixes,
+    c_get_unmanaged_files: IPCGetUnmanagedFiles,
+    c_read_unmanaged_file: IPCReadUnmanagedFile,
+    c_create_schema_for_unmanaged: IPCCreateSchemaForUnmanaged,
+    c_move_unmanaged_to_folder: IPCMoveUnmanagedToFolder,
+    c_export_csv: IPCExportCsv,
+    c_preview_csv: IPCPreviewCsv,
+    c_import_csv: IPCImportCsv,
+    c_guess_goodreads_mapping: IPCGuessGoodreadsMapping,
+    c_import_goodreads: IPCImportGoodreads,
+    c_preview_movie_import: IPCPreviewMovieImport,
+    c_import_movies: IPCImportMovies,
+    c_preview_kindle_import: IPCPreviewKindleImport,
+    c_import_kindle: IPCImportKindle,
+    c_guess_calibre_mapping: IPCGuessCalibreMapping,
+    c_import_calibre: IPCImportCalibre,
+    c_export_dump: IPCExportDump,
+    c_import_dump: IPCImportDump,
+    c_export_site: IPCExportSite,
+    c_render_markdown: IPCRenderMarkdown,
+    c_create_backup: IPCCreateBackup,
+    c_restore_backup: IPCRestoreBackup,
+    c_get_backup_schedule: IPCBackupSchedule,
+    c_set_backup_schedule: IPCBackupSchedule,
+    c_list_backups: IPCListBackups,
+    c_export_ical: IPCExportIcal,
+    c_get_citation_mapping: IPCCitationMapping,
+    c_save_citation_mapping: IPCCitationMapping,
+    c_export_citations: IPCExportCitations,
+    c_aggregate: IPCAggregate,
+    c_year_review: IPCYearReview,
 }
 
 #[tauri::command]
@@ -213,10 +347,19 @@ async fn c_save_schema<T: tauri::Runtime>(
     let core = app.state::<CoreStateManager>();
     let ctx = &core.context;
     let normalized_path = normalize_path_to_os(&path);
-    core.context
+
+    let (schema_path, _) =
+        locate_schema_and_folder(&ctx.relative_path_to_absolute(&normalized_path).await?)?;
+    let snapshot = snapshot_file(ctx, &ctx.absolute_path_to_relative(&schema_path).await?).await;
+
+    let res = core
+        .context
         .schemas_cache
         .save_schema(ctx, &normalized_path, schema)
-        .await
+        .await?;
+
+    ctx.journal.record(ctx, "Edit schema", vec![snapshot]).await;
+    Ok(res)
 }
 
 #[tauri::command]
@@ -228,7 +371,74 @@ async fn c_save_file<T: tauri::Runtime>(
 ) -> IPCSaveFile {
     log::info!("c_save_file invoked");
     let core = app.state::<CoreStateManager>();
-    save_file(&core.context, record, forced, create_new).await
+    let ctx = &core.context;
+
+    let snapshot = match (&record.path, create_new) {
+        (Some(p), false) => Some(snapshot_file(ctx, &normalize_path_to_os(p)).await),
+        _ => None,
+    };
+
+    let res = save_file(ctx, record, forced, create_new).await?;
+
+    let (title, undo) = match snapshot {
+        Some(s) => ("Edit record", s),
+        None => (
+            "Create record",
+            JournalAction::MoveToTrash {
+                path: res.path.clone(),
+            },
+        ),
+    };
+    ctx.journal.record(ctx, title, vec![undo]).await;
+
+    Ok(res)
+}
+
+#[tauri::command]
+async fn c_update_attributes<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+    patch: HashMap<String, AttrValue>,
+    hash: Option<String>,
+    modified: Option<i64>,
+    forced: bool,
+) -> IPCUpdateAttributes {
+    log::info!("c_update_attributes invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+    let normalized_path = normalize_path_to_os(&path);
+
+    let snapshot = snapshot_file(ctx, &normalized_path).await;
+    let res = update_attributes(ctx, &normalized_path, patch, hash, modified, forced).await?;
+
+    ctx.journal
+        .record(ctx, "Edit attributes", vec![snapshot])
+        .await;
+    Ok(res)
+}
+
+#[tauri::command]
+async fn c_batch_edit<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    target: BatchTarget,
+    attribute: String,
+    operation: BatchOperation,
+    dry_run: bool,
+) -> IPCBatchEdit {
+    log::info!("c_batch_edit invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+    let output = batch_edit(ctx, target, &attribute, &operation, dry_run).await?;
+
+    ctx.journal
+        .record(ctx, &format!("Batch edit {}", attribute), output.undo)
+        .await;
+
+    if !output.events.is_empty() {
+        emit_event_to_frontend(&app, IPCEmitEvent::FileUpdateBatch(output.events)).await;
+    }
+
+    Ok(output.result)
 }
 
 #[tauri::command]
@@ -249,11 +459,42 @@ async fn c_resolve_schema_path<T: tauri::Runtime>(
 async fn c_delete_to_trash<T: tauri::Runtime>(app: AppHandle<T>, path: String) -> IPCDeleteFile {
     log::info!("c_delete_to_trash invoked");
     let core = app.state::<CoreStateManager>();
-    let absolute_path = core
-        .context
-        .relative_path_to_absolute(&PathBuf::from(path))
-        .await?;
-    trash::delete(&absolute_path).map_err(|e| Box::new(ErrFR::new("Failed to delete file").raw(e)))
+    let ctx = &core.context;
+    let res = delete_to_trash(ctx, &PathBuf::from(path)).await?;
+
+    if let Some(r) = &res {
+        let undo = JournalAction::RestoreFromTrash { id: r.id.clone() };
+        ctx.journal.record(ctx, "Delete", vec![undo]).await;
+    }
+
+    Ok(res)
+}
+
+#[tauri::command]
+async fn c_list_trash<T: tauri::Runtime>(app: AppHandle<T>) -> IPCListTrash {
+    log::info!("c_list_trash invoked");
+    let core = app.state::<CoreStateManager>();
+    list_trashed_records(&core.context).await
+}
+
+#[tauri::command]
+async fn c_restore_from_trash<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    id: String,
+) -> IPCRestoreFromTrash {
+    log::info!("c_restore_from_trash invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+    let res = restore_from_trash(ctx, &id).await?;
+
+    let undo = JournalAction::MoveToTrash {
+        path: res.to_string_lossy().to_string(),
+    };
+    ctx.journal
+        .record(ctx, "Restore from trash", vec![undo])
+        .await;
+
+    Ok(res)
 }
 
 #[tauri::command]
@@ -278,9 +519,667 @@ async fn c_create_folder_for_default_schema<T: tauri::Runtime>(
         .context
         .absolute_path_to_relative(&not_existing)
         .await?;
+
+    let undo = JournalAction::RemoveFolder {
+        path: relative_path.to_string_lossy().to_string(),
+    };
+    core.context
+        .journal
+        .record(&core.context, "Create folder", vec![undo])
+        .await;
+
     Ok(relative_path)
 }
 
+/* Renames or moves file or folder, both paths are relative to root path */
+#[tauri::command]
+async fn c_rename_path<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    from: String,
+    to: String,
+) -> IPCRenamePath {
+    log::info!("c_rename_path invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let from_absolute = ctx
+        .relative_path_to_absolute(&normalize_path_to_os(&from))
+        .await?;
+    let to_absolute = ctx
+        .relative_path_to_absolute(&normalize_path_to_os(&to))
+        .await?;
+
+    if to_absolute.exists() {
+        return Err(Box::new(
+            ErrFR::new("Unable to rename, path is already taken").info(&to),
+        ));
+    }
+
+    rename(&from_absolute, &to_absolute)
+        .await
+        .map_err(|e| Box::new(ErrFR::new("Failed to rename").raw(e)))?;
+
+    let from_relative = ctx.absolute_path_to_relative(&from_absolute).await?;
+    let to_relative = ctx.absolute_path_to_relative(&to_absolute).await?;
+
+    let undo = JournalAction::Rename {
+        from: to_relative.to_string_lossy().to_string(),
+        to: from_relative.to_string_lossy().to_string(),
+    };
+    ctx.journal.record(ctx, "Rename", vec![undo]).await;
+
+    Ok(to_relative)
+}
+
+#[tauri::command]
+async fn c_get_journal<T: tauri::Runtime>(app: AppHandle<T>) -> IPCJournal {
+    log::info!("c_get_journal invoked");
+    let core = app.state::<CoreStateManager>();
+    core.context.journal.status(&core.context).await
+}
+
+#[tauri::command]
+async fn c_undo<T: tauri::Runtime>(app: AppHandle<T>) -> IPCJournal {
+    log::info!("c_undo invoked");
+    let core = app.state::<CoreStateManager>();
+    core.context.journal.undo(&core.context).await
+}
+
+#[tauri::command]
+async fn c_redo<T: tauri::Runtime>(app: AppHandle<T>) -> IPCJournal {
+    log::info!("c_redo invoked");
+    let core = app.state::<CoreStateManager>();
+    core.context.journal.redo(&core.context).await
+}
+
+/* Copies image into assets folder, name is used as a base for file name */
+#[tauri::command]
+async fn c_import_image<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    source: AssetSource,
+    name: String,
+) -> IPCImportImage {
+    log::info!("c_import_image invoked");
+    let core = app.state::<CoreStateManager>();
+    import_asset(&core.context, source, &name).await
+}
+
+/* Same as c_import_image, but image is the raw request body and name is a percent encoded header */
+#[tauri::command]
+async fn c_import_image_bytes<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    request: tauri::ipc::Request<'_>,
+) -> IPCImportImage {
+    log::info!("c_import_image_bytes invoked");
+    let core = app.state::<CoreStateManager>();
+
+    let tauri::ipc::InvokeBody::Raw(bytes) = request.body() else {
+        return Err(Box::new(
+            ErrFR::new("Unable to read image").info("Request body is not binary"),
+        ));
+    };
+    let name = request
+        .headers()
+        .get("asom-image-name")
+        .and_then(|v| v.to_str().ok())
+        .map(decode_percent)
+        .unwrap_or_default();
+
+    import_asset(&core.context, AssetSource::Bytes(bytes.clone()), &name).await
+}
+
+/* Returns absolute path to thumbnail in app cache, generating it when needed */
+#[tauri::command]
+async fn c_get_thumbnail<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+    size: ThumbnailSize,
+) -> IPCGetThumbnail {
+    log::info!("c_get_thumbnail invoked");
+    let core = app.state::<CoreStateManager>();
+    core.context
+        .thumbnails
+        .get_thumbnail(&core.context, &path, size)
+        .await
+}
+
+#[tauri::command]
+async fn c_get_asset_report<T: tauri::Runtime>(app: AppHandle<T>) -> IPCGetAssetReport {
+    log::info!("c_get_asset_report invoked");
+    let core = app.state::<CoreStateManager>();
+    get_asset_report(&core.context).await
+}
+
+/* Names are file names in assets folder, as returned in orphaned list of asset report */
+#[tauri::command]
+async fn c_trash_orphaned_assets<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    names: Vec<String>,
+) -> IPCTrashOrphanedAssets {
+    log::info!("c_trash_orphaned_assets invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+    let res = trash_orphaned_assets(ctx, names).await?;
+
+    let undo = res
+        .trashed
+        .iter()
+        .map(|r| JournalAction::RestoreFromTrash { id: r.id.clone() })
+        .collect();
+    ctx.journal.record(ctx, "Remove unused images", undo).await;
+
+    Ok(res)
+}
+
+#[tauri::command]
+async fn c_check_vault_health<T: tauri::Runtime>(app: AppHandle<T>) -> IPCCheckVaultHealth {
+    log::info!("c_check_vault_health invoked");
+    let core = app.state::<CoreStateManager>();
+    check_vault_health(&core.context).await
+}
+
+/* Fixes come from health report, all of them are applied as a single undo step */
+#[tauri::command]
+async fn c_apply_health_fixes<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    fixes: Vec<HealthFix>,
+) -> IPCApplyHealthFixes {
+    log::info!("c_apply_health_fixes invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+    let output = apply_health_fixes(ctx, fixes).await?;
+
+    ctx.journal
+        .record(ctx, "Fix vault issues", output.undo)
+        .await;
+
+    Ok(output.result)
+}
+
+/* Markdown files that are not covered by any schema, path is a folder relative to root path */
+#[tauri::command]
+async fn c_get_unmanaged_files<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+) -> IPCGetUnmanagedFiles {
+    log::info!("c_get_unmanaged_files invoked");
+    let core = app.state::<CoreStateManager>();
+    get_unmanaged_files_by_path(&core.context, &normalize_path_to_os(&path)).await
+}
+
+#[tauri::command]
+async fn c_read_unmanaged_file<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+) -> IPCReadUnmanagedFile {
+    log::info!("c_read_unmanaged_file invoked");
+    let core = app.state::<CoreStateManager>();
+    read_unmanaged_file(&core.context, &normalize_path_to_os(&path)).await
+}
+
+/* Schema items are guessed from frontmatter of unmanaged files in the folder */
+#[tauri::command]
+async fn c_create_schema_for_unmanaged<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    folder: String,
+    name: Option<String>,
+) -> IPCCreateSchemaForUnmanaged {
+    log::info!("c_create_schema_for_unmanaged invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+    let normalized_path = normalize_path_to_os(&folder);
+
+    let (schema_path, _) =
+        locate_schema_and_folder(&ctx.relative_path_to_absolute(&normalized_path).await?)?;
+    let snapshot = snapshot_file(ctx, &ctx.absolute_path_to_relative(&schema_path).await?).await;
+
+    let res = create_schema_for_unmanaged(ctx, &normalized_path, name).await?;
+
+    ctx.journal
+        .record(ctx, "Create schema", vec![snapshot])
+        .await;
+    Ok(res)
+}
+
+#[tauri::command]
+async fn c_move_unmanaged_to_folder<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    paths: Vec<String>,
+    folder: String,
+) -> IPCMoveUnmanagedToFolder {
+    log::info!("c_move_unmanaged_to_folder invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+    let paths = paths
+        .iter()
+        .map(|p| normalize_path_to_os(p).to_string_lossy().to_string())
+        .collect();
+
+    let output = move_unmanaged_to_folder(ctx, paths, &normalize_path_to_os(&folder)).await?;
+
+    ctx.journal.record(ctx, "Move files", output.undo).await;
+    Ok(output.result)
+}
+
+/* Output is an absolute path picked by user in save dialog */
+#[tauri::command]
+async fn c_export_csv<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    target: BatchTarget,
+    output: String,
+    options: CsvExportOptions,
+) -> IPCExportCsv {
+    log::info!("c_export_csv invoked");
+    let core = app.state::<CoreStateManager>();
+    export_csv(&core.context, target, &PathBuf::from(output), &options).await
+}
+
+/* Path is an absolute path to csv file picked by user */
+#[tauri::command]
+async fn c_preview_csv(path: String) -> IPCPreviewCsv {
+    log::info!("c_preview_csv invoked");
+    preview_csv(&PathBuf::from(path), 5)
+}
+
+/* Creates a record per row in folder, every created file is a single undo step */
+#[tauri::command]
+async fn c_import_csv<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+    folder: String,
+    options: CsvImportOptions,
+) -> IPCImportCsv {
+    log::info!("c_import_csv invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let output = import_csv(
+        ctx,
+        &PathBuf::from(path),
+        &normalize_path_to_os(&folder),
+        &options,
+    )
+    .await?;
+
+    ctx.journal.record(ctx, "Import CSV", output.undo).await;
+    Ok(output.result)
+}
+
+#[tauri::command]
+async fn c_guess_goodreads_mapping<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    folder: String,
+) -> IPCGuessGoodreadsMapping {
+    log::info!("c_guess_goodreads_mapping invoked");
+    let core = app.state::<CoreStateManager>();
+    let schema = core
+        .context
+        .schemas_cache
+        .get_schema_safe(&normalize_path_to_os(&folder))
+        .await?;
+    Ok(guess_goodreads_mapping(&schema.schema))
+}
+
+/* Path is an absolute path to goodreads_library_export.csv */
+#[tauri::command]
+async fn c_import_goodreads<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+    folder: String,
+    mapping: GoodreadsMapping,
+) -> IPCImportGoodreads {
+    log::info!("c_import_goodreads invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let output = import_goodreads(
+        ctx,
+        &PathBuf::from(path),
+        &normalize_path_to_os(&folder),
+        &mapping,
+    )
+    .await?;
+
+    ctx.journal
+        .record(ctx, "Import from Goodreads", output.undo)
+        .await;
+    Ok(output.result)
+}
+
+/* Letterboxd or IMDb export, source is detected by headers. Mapping is guessed when not passed */
+#[tauri::command]
+async fn c_preview_movie_import<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+    folder: String,
+    mapping: Option<MovieMapping>,
+) -> IPCPreviewMovieImport {
+    log::info!("c_preview_movie_import invoked");
+    let core = app.state::<CoreStateManager>();
+    preview_movie_import(
+        &core.context,
+        &PathBuf::from(path),
+        &normalize_path_to_os(&folder),
+        mapping,
+    )
+    .await
+}
+
+#[tauri::command]
+async fn c_import_movies<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+    folder: String,
+    mapping: MovieMapping,
+) -> IPCImportMovies {
+    log::info!("c_import_movies invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let output = import_movies(
+        ctx,
+        &PathBuf::from(path),
+        &normalize_path_to_os(&folder),
+        mapping,
+    )
+    .await?;
+
+    ctx.journal.record(ctx, "Import movies", output.undo).await;
+    Ok(output.result)
+}
+
+/* Path is an absolute path to "My Clippings.txt", books are matched against records in folder */
+#[tauri::command]
+async fn c_preview_kindle_import<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+    folder: String,
+) -> IPCPreviewKindleImport {
+    log::info!("c_preview_kindle_import invoked");
+    let core = app.state::<CoreStateManager>();
+    preview_kindle_import(
+        &core.context,
+        &PathBuf::from(path),
+        &normalize_path_to_os(&folder),
+    )
+    .await
+}
+
+#[tauri::command]
+async fn c_import_kindle<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+    targets: Vec<KindleBookTarget>,
+) -> IPCImportKindle {
+    log::info!("c_import_kindle invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let targets = targets
+        .into_iter()
+        .map(|t| KindleBookTarget {
+            record: normalize_path_to_os(&t.record)
+                .to_string_lossy()
+                .to_string(),
+            ..t
+        })
+        .collect();
+
+    let output = import_kindle(ctx, &PathBuf::from(path), targets).await?;
+
+    ctx.journal
+        .record(ctx, "Import Kindle highlights", output.undo)
+        .await;
+    Ok(output.result)
+}
+
+#[tauri::command]
+async fn c_guess_calibre_mapping<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    folder: String,
+) -> IPCGuessCalibreMapping {
+    log::info!("c_guess_calibre_mapping invoked");
+    let core = app.state::<CoreStateManager>();
+    let schema = core
+        .context
+        .schemas_cache
+        .get_schema_safe(&normalize_path_to_os(&folder))
+        .await?;
+    Ok(guess_calibre_mapping(&schema.schema))
+}
+
+/* Library is an absolute path to Calibre library folder or its metadata.db */
+#[tauri::command]
+async fn c_import_calibre<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    library: String,
+    folder: String,
+    mapping: CalibreMapping,
+) -> IPCImportCalibre {
+    log::info!("c_import_calibre invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let output = import_calibre(
+        ctx,
+        &PathBuf::from(library),
+        &normalize_path_to_os(&folder),
+        &mapping,
+    )
+    .await?;
+
+    ctx.journal
+        .record(ctx, "Import from Calibre", output.undo)
+        .await;
+    Ok(output.result)
+}
+
+/* Folder with all records that share its schema goes into JSON or NDJSON file at output */
+#[tauri::command]
+async fn c_export_dump<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    folder: String,
+    output: String,
+    options: DumpExportOptions,
+) -> IPCExportDump {
+    log::info!("c_export_dump invoked");
+    let core = app.state::<CoreStateManager>();
+    export_dump(
+        &core.context,
+        &normalize_path_to_os(&folder),
+        &PathBuf::from(output),
+        &options,
+    )
+    .await
+}
+
+/* Path is an absolute path to file made by c_export_dump */
+#[tauri::command]
+async fn c_import_dump<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+    folder: String,
+) -> IPCImportDump {
+    log::info!("c_import_dump invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let output = import_dump(ctx, &PathBuf::from(path), &normalize_path_to_os(&folder)).await?;
+
+    ctx.journal.record(ctx, "Import records", output.undo).await;
+    Ok(output.result)
+}
+
+/* Output is an absolute path to folder where index.html will be */
+#[tauri::command]
+async fn c_export_site<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    folder: String,
+    output: String,
+    options: SiteExportOptions,
+) -> IPCExportSite {
+    log::info!("c_export_site invoked");
+    let core = app.state::<CoreStateManager>();
+    export_site(
+        &core.context,
+        &normalize_path_to_os(&folder),
+        &PathBuf::from(output),
+        &options,
+    )
+    .await
+}
+
+#[tauri::command]
+async fn c_render_markdown<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    path: String,
+    markdown: Option<String>,
+) -> IPCRenderMarkdown {
+    log::info!("c_render_markdown invoked");
+    let core = app.state::<CoreStateManager>();
+    render_record_markdown(&core.context, &normalize_path_to_os(&path), markdown).await
+}
+
+#[tauri::command]
+async fn c_create_backup<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    output: String,
+    options: BackupOptions,
+) -> IPCCreateBackup {
+    log::info!("c_create_backup invoked");
+    let core = app.state::<CoreStateManager>();
+    create_backup(&core.context, &PathBuf::from(output), &options).await
+}
+
+#[tauri::command]
+async fn c_restore_backup<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    archive: String,
+    target: String,
+    switch_root: bool,
+) -> IPCRestoreBackup {
+    log::info!("c_restore_backup invoked");
+    let core = app.state::<CoreStateManager>();
+    let mut result = restore_backup(&PathBuf::from(archive), &PathBuf::from(target)).await?;
+
+    // Vault with broken schemas is left for user to inspect before opening it
+    if switch_root && result.errors.is_none() {
+        core.set_root_path_and_reinit(&app, result.path.clone())
+            .await?;
+        result.root_switched = true;
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+async fn c_get_backup_schedule<T: tauri::Runtime>(app: AppHandle<T>) -> IPCBackupSchedule {
+    log::info!("c_get_backup_schedule invoked");
+    get_backup_schedule_from_storage(&app)
+}
+
+#[tauri::command]
+async fn c_set_backup_schedule<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    schedule: BackupSchedule,
+) -> IPCBackupSchedule {
+    log::info!("c_set_backup_schedule invoked");
+    let core = app.state::<CoreStateManager>();
+    set_backup_schedule_to_storage(&app, &core.context, schedule).await
+}
+
+/* Restoring one of them is done with c_restore_backup */
+#[tauri::command]
+async fn c_list_backups<T: tauri::Runtime>(app: AppHandle<T>) -> IPCListBackups {
+    log::info!("c_list_backups invoked");
+    let core = app.state::<CoreStateManager>();
+    let schedule = get_backup_schedule_from_storage(&app)?;
+    list_scheduled_backups(&core.context, &schedule).await
+}
+
+#[tauri::command]
+async fn c_export_ical<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    output: String,
+    options: IcalExportOptions,
+) -> IPCExportIcal {
+    log::info!("c_export_ical invoked");
+    let core = app.state::<CoreStateManager>();
+    export_ical(&core.context, &PathBuf::from(output), &options).await
+}
+
+#[tauri::command]
+async fn c_get_citation_mapping<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    folder: String,
+) -> IPCCitationMapping {
+    log::info!("c_get_citation_mapping invoked");
+    let core = app.state::<CoreStateManager>();
+    load_citation_mapping(&core.context, &normalize_path_to_os(&folder)).await
+}
+
+#[tauri::command]
+async fn c_save_citation_mapping<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    folder: String,
+    mapping: CitationMapping,
+) -> IPCCitationMapping {
+    log::info!("c_save_citation_mapping invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+    let output = save_citation_mapping(ctx, &normalize_path_to_os(&folder), mapping).await?;
+    ctx.journal
+        .record(ctx, "Edit citation mapping", output.undo)
+        .await;
+    Ok(output.mapping)
+}
+
+#[tauri::command]
+async fn c_export_citations<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    folder: String,
+    output: String,
+    format: CitationFormat,
+) -> IPCExportCitations {
+    log::info!("c_export_citations invoked");
+    let core = app.state::<CoreStateManager>();
+    export_citations(
+        &core.context,
+        &normalize_path_to_os(&folder),
+        &PathBuf::from(output),
+        format,
+    )
+    .await
+}
+
+/* Counts and number summaries of cached records of a schema folder, grouped by attribute */
+#[tauri::command]
+async fn c_aggregate<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    options: AggregateOptions,
+) -> IPCAggregate {
+    log::info!("c_aggregate invoked");
+    let core = app.state::<CoreStateManager>();
+    aggregate(&core.context, &options).await
+}
+
+/* Saving the report is journaled, so an overwritten report of the same year can be restored */
+#[tauri::command]
+async fn c_year_review<T: tauri::Runtime>(
+    app: AppHandle<T>,
+    options: YearReviewOptions,
+) -> IPCYearReview {
+    log::info!("c_year_review invoked");
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+    let output = year_review(ctx, &options).await?;
+    if !output.undo.is_empty() {
+        ctx.journal
+            .record(ctx, "Save year in review", output.undo)
+            .await;
+    }
+    Ok(output.review)
+}
+
 pub fn create_app<T: tauri::Runtime>(builder: tauri::Builder<T>) -> tauri::App<T> {
     builder
         .plugin(tauri_plugin_sql::Builder::new().build())
@@ -299,11 +1198,56 @@ pub fn create_app<T: tauri::Runtime>(builder: tauri::Builder<T>) -> tauri::App<T
             c_get_all_folders_by_schema,
             c_read_file_by_path,
             c_save_file,
+            c_update_attributes,
+            c_batch_edit,
             c_get_schemas_usable,
             c_get_schemas_all,
             c_resolve_schema_path,
             c_delete_to_trash,
-            c_create_folder_for_default_schema
+            c_list_trash,
+            c_restore_from_trash,
+            c_create_folder_for_default_schema,
+            c_rename_path,
+            c_get_journal,
+            c_undo,
+            c_redo,
+            c_import_image,
+            c_import_image_bytes,
+            c_get_thumbnail,
+            c_get_asset_report,
+            c_trash_orphaned_assets,
+            c_check_vault_health,
+            c_apply_health_fixes,
+            c_get_unmanaged_files,
+            c_read_unmanaged_file,
+            c_create_schema_for_unmanaged,
+            c_move_unmanaged_to_folder,
+            c_export_csv,
+            c_preview_csv,
+            c_import_csv,
+            c_guess_goodreads_mapping,
+            c_import_goodreads,
+            c_preview_movie_import,
+            c_import_movies,
+            c_preview_kindle_import,
+            c_import_kindle,
+            c_guess_calibre_mapping,
+            c_import_calibre,
+            c_export_dump,
+            c_import_dump,
+            c_export_site,
+            c_render_markdown,
+            c_create_backup,
+            c_restore_backup,
+            c_get_backup_schedule,
+            c_set_backup_schedule,
+            c_list_backups,
+            c_export_ical,
+            c_get_citation_mapping,
+            c_save_citation_mapping,
+            c_export_citations,
+            c_aggregate,
+            c_year_review
         ])
         .setup(|app| {
             log::info!("Setting up app");
@@ -337,14 +1281,22 @@ pub fn create_app<T: tauri::Runtime>(builder: tauri::Builder<T>) -> tauri::App<T
             let rt = Runtime::new().unwrap();
             rt.block_on(async {
                 let cache_dir = app.path().cache_dir().unwrap();
+                if let Ok(app_cache_dir) = app.path().app_cache_dir() {
+                    state.context.thumbnails.init(&app_cache_dir).await;
+                }
                 let _ = state
                     .context
                     .database_conn
                     .init(InitMode::InFolder(cache_dir))
                     .await;
+
+                if let Ok(data_dir) = app.path().app_data_dir() {
+                    state.context.journal.init(data_dir).await;
+                }
             });
 
             app.manage(state);
+            start_backup_scheduler(app.handle().clone());
             Ok(())
         })
         .build(tauri::generate_context!())
@@ -32,11 +32,11 @@ pub struct SchemaResult {
     pub location: SchemaLocation,
 }
 
-const INTERNAL_FOLDER_NAME: &str = ".asom";
-const SCHEMA_FILE_NAME: &str = "schema.yaml";
+pub const INTERNAL_FOLDER_NAME: &str = ".asom";
+pub const SCHEMA_FILE_NAME: &str = "schema.yaml";
 
 /** Takes either schema owner folder path, internal config path(folder/.asom) or schema file path and return both paths */
-fn locate_schema_and_folder(path_absolute: &Path) -> Result<(PathBuf, PathBuf), Box<ErrFR>> {
+pub fn locate_schema_and_folder(path_absolute: &Path) -> Result<(PathBuf, PathBuf), Box<ErrFR>> {
     let is_dir_safe_for_deleted = match path_absolute.exists() {
         true => path_absolute.is_dir(),
         false => path_absolute.extension().is_none(),
@@ -151,6 +151,7 @@ pub struct TextSettings {
     pub settings_type: SettingsTypeText,
 
     pub display_name: Option<String>,
+    pub is_private: Option<bool>,
 
     pub size: Option<InputSize>,
     pub font: Option<TextFont>,
@@ -162,6 +163,7 @@ impl Default for TextSettings {
         TextSettings {
             settings_type: SettingsTypeText::Text,
             display_name: None,
+            is_private: None,
             size: None,
             font: None,
             weight: None,
@@ -182,6 +184,7 @@ pub struct NumberSettings {
     pub settings_type: SettingsTypeNumber,
 
     pub display_name: Option<String>,
+    pub is_private: Option<bool>,
 
     pub size: Option<InputSize>,
     pub min: Option<f64>,
@@ -197,6 +200,7 @@ impl Default for NumberSettings {
         NumberSettings {
             settings_type: SettingsTypeNumber::Number,
             display_name: None,
+            is_private: None,
             size: None,
             min: None,
             max: None,
@@ -218,6 +222,7 @@ pub struct TextCollectionSettings {
     #[serde(default)]
     pub settings_type: SettingsTypeTextCollection,
     pub display_name: Option<String>,
+    pub is_private: Option<bool>,
     pub size: Option<InputSize>,
     pub font: Option<TextFont>,
     pub weight: Option<TextWeight>,
@@ -228,6 +233,7 @@ impl Default for TextCollectionSettings {
         TextCollectionSettings {
             settings_type: SettingsTypeTextCollection::TextCollection,
             display_name: None,
+            is_private: None,
             size: None,
             font: None,
             weight: None,
@@ -247,6 +253,7 @@ pub struct ImageSettings {
     #[serde(default)]
     pub settings_type: SettingsTypeImage,
     pub display_name: Option<String>,
+    pub is_private: Option<bool>,
     pub aspect_ratio: Option<String>,
 }
 impl Default for ImageSettings {
@@ -254,6 +261,7 @@ impl Default for ImageSettings {
         ImageSettings {
             settings_type: SettingsTypeImage::Image,
             display_name: None,
+            is_private: None,
             aspect_ratio: None,
         }
     }
@@ -269,6 +277,7 @@ impl Default for ImageSettings {
 pub struct DateSettings {
     pub settings_type: SettingsTypeDate,
     pub display_name: Option<String>,
+    pub is_private: Option<bool>,
 }
 
 impl Default for DateSettings {
@@ -276,6 +285,7 @@ impl Default for DateSettings {
         DateSettings {
             settings_type: SettingsTypeDate::Date,
             display_name: None,
+            is_private: None,
         }
     }
 }
@@ -289,6 +299,7 @@ impl Default for DateSettings {
 pub struct DateCollectionSettings {
     pub settings_type: SettingsTypeDateCollection,
     pub display_name: Option<String>,
+    pub is_private: Option<bool>,
 }
 
 impl Default for DateCollectionSettings {
@@ -296,6 +307,7 @@ impl Default for DateCollectionSettings {
         DateCollectionSettings {
             settings_type: SettingsTypeDateCollection::DateCollection,
             display_name: None,
+            is_private: None,
         }
     }
 }
@@ -308,6 +320,7 @@ impl Default for DateCollectionSettings {
 pub struct DatesPairCollectionSettings {
     pub settings_type: SettingsTypeDatesPairCollection,
     pub display_name: Option<String>,
+    pub is_private: Option<bool>,
 }
 
 impl Default for DatesPairCollectionSettings {
@@ -315,6 +328,7 @@ impl Default for DatesPairCollectionSettings {
         DatesPairCollectionSettings {
             settings_type: SettingsTypeDatesPairCollection::DatesPairCollection,
             display_name: None,
+            is_private: None,
         }
     }
 }
@@ -356,6 +370,40 @@ pub struct SchemaItem {
     pub value: SchemaAttrType,
 }
 
+impl SchemaItem {
+    /* Display name when it's set, otherwise attribute name as in frontmatter */
+    pub fn label(&self) -> &str {
+        let display_name = match &self.value {
+            SchemaAttrType::Text(s) => &s.display_name,
+            SchemaAttrType::TextCollection(s) => &s.display_name,
+            SchemaAttrType::Number(s) => &s.display_name,
+            SchemaAttrType::Date(s) => &s.display_name,
+            SchemaAttrType::DateCollection(s) => &s.display_name,
+            SchemaAttrType::DatesPairCollection(s) => &s.display_name,
+            SchemaAttrType::Image(s) => &s.display_name,
+        };
+
+        match display_name.as_deref() {
+            Some(v) if !v.trim().is_empty() => v,
+            _ => &self.name,
+        }
+    }
+
+    /* Private attributes are left out of anything that is published, like site export */
+    pub fn is_private(&self) -> bool {
+        let is_private = match &self.value {
+            SchemaAttrType::Text(s) => s.is_private,
+            SchemaAttrType::TextCollection(s) => s.is_private,
+            SchemaAttrType::Number(s) => s.is_private,
+            SchemaAttrType::Date(s) => s.is_private,
+            SchemaAttrType::DateCollection(s) => s.is_private,
+            SchemaAttrType::DatesPairCollection(s) => s.is_private,
+            SchemaAttrType::Image(s) => s.is_private,
+        };
+        is_private.unwrap_or(false)
+    }
+}
+
 pub type SchemaItems = Vec<SchemaItem>;
 
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
@@ -396,6 +444,56 @@ impl Schema {
     }
 }
 
+/* First schema item with one of names (case insensitive) that satisfies type check, for guessing mappings */
+pub fn find_schema_item(
+    schema: &Schema,
+    names: &[&str],
+    type_check: fn(&SchemaAttrType) -> bool,
+) -> Option<String> {
+    names.iter().find_map(|name| {
+        schema
+            .items
+            .iter()
+            .find(|i| i.name.eq_ignore_ascii_case(name) && type_check(&i.value))
+            .map(|i| i.name.clone())
+    })
+}
+
+/* Type checks for find_schema_item */
+pub fn is_text(t: &SchemaAttrType) -> bool {
+    matches!(t, SchemaAttrType::Text(_))
+}
+
+pub fn is_text_or_collection(t: &SchemaAttrType) -> bool {
+    matches!(
+        t,
+        SchemaAttrType::Text(_) | SchemaAttrType::TextCollection(_)
+    )
+}
+
+pub fn is_collection(t: &SchemaAttrType) -> bool {
+    matches!(t, SchemaAttrType::TextCollection(_))
+}
+
+pub fn is_number(t: &SchemaAttrType) -> bool {
+    matches!(t, SchemaAttrType::Number(_))
+}
+
+pub fn is_date(t: &SchemaAttrType) -> bool {
+    matches!(t, SchemaAttrType::Date(_))
+}
+
+pub fn is_dates(t: &SchemaAttrType) -> bool {
+    matches!(
+        t,
+        SchemaAttrType::DateCollection(_) | SchemaAttrType::DatesPairCollection(_)
+    )
+}
+
+pub fn is_dates_pair(t: &SchemaAttrType) -> bool {
+    matches!(t, SchemaAttrType::DatesPairCollection(_))
+}
+
 pub const SCHEMA_VERSION: &str = "1.0";
 
 #[derive(Serialize, TS, Clone, Debug, Deserialize)]
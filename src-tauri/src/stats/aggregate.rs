@@ -0,0 +1,407 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::query::{get_files_by_path, RecordFromDb};
+use crate::core::core_state::AppContext;
+use crate::schema::types::{
+    is_date, is_dates, is_dates_pair, is_number, AttrValue, Schema, SchemaAttrType,
+};
+use crate::utils::errorhandling::ErrFR;
+use crate::utils::helpers::normalize_path_to_os;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+#[serde(tag = "type", content = "attribute")]
+pub enum GroupBy {
+    /* Every record in one group */
+    All,
+    /* Each item of a text collection is a separate group */
+    Value(String),
+    /* Year of a date, of each date in a collection or of each finished date of pairs */
+    Year(String),
+    /* Same as year, keys are YYYY-MM */
+    Month(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct AggregateOptions {
+    /* Schema folder relative to root path, subfolders with their own schema are not included */
+    pub folder: String,
+    pub group_by: GroupBy,
+    /* Number attributes to summarize in every group */
+    pub numbers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+pub struct NumberSummary {
+    pub attribute: String,
+    /* Records of the group that have a value */
+    pub count: u32,
+    pub sum: f64,
+    pub avg: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+pub struct AggregateGroup {
+    /* None groups records without a value */
+    pub key: Option<String>,
+    pub count: u32,
+    pub numbers: Vec<NumberSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct AggregateResult {
+    /* Records in folder, same record can be counted in several groups */
+    pub records: u32,
+    pub groups: Vec<AggregateGroup>,
+}
+
+pub fn number_value(value: Option<&AttrValue>) -> Option<f64> {
+    match value {
+        Some(AttrValue::Integer(Some(v))) | Some(AttrValue::Float(Some(v))) if v.is_finite() => {
+            Some(*v)
+        }
+        _ => None,
+    }
+}
+
+/* Texts, collection items, numbers and finished dates of pairs as strings */
+fn attr_strings(value: Option<&AttrValue>) -> Vec<String> {
+    let values: Vec<String> = match value {
+        Some(AttrValue::String(Some(v))) => vec![v.clone()],
+        Some(AttrValue::StringVec(Some(v))) => v.clone(),
+        Some(AttrValue::DatePairVec(Some(pairs))) => {
+            pairs.iter().filter_map(|p| p.finished.clone()).collect()
+        }
+        Some(AttrValue::Integer(_)) | Some(AttrValue::Float(_)) => number_value(value)
+            .map(|v| v.to_string())
+            .into_iter()
+            .collect(),
+        _ => vec![],
+    };
+
+    values
+        .into_iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+pub fn parse_day(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/* Record is counted once in each of its groups, a book finished twice in a year is one book of that year */
+fn group_keys(record: &RecordFromDb, group_by: &GroupBy) -> BTreeSet<Option<String>> {
+    let keys: BTreeSet<Option<String>> = match group_by {
+        GroupBy::All => return BTreeSet::from([None]),
+        GroupBy::Value(attr) => attr_strings(record.attrs.get(attr))
+            .into_iter()
+            .map(Some)
+            .collect(),
+        GroupBy::Year(attr) => attr_strings(record.attrs.get(attr))
+            .iter()
+            .filter_map(|v| parse_day(v))
+            .map(|d| Some(d.format("%Y").to_string()))
+            .collect(),
+        GroupBy::Month(attr) => attr_strings(record.attrs.get(attr))
+            .iter()
+            .filter_map(|v| parse_day(v))
+            .map(|d| Some(d.format("%Y-%m").to_string()))
+            .collect(),
+    };
+
+    match keys.is_empty() {
+        true => BTreeSet::from([None]),
+        false => keys,
+    }
+}
+
+/*
+    Numeric keys like years of publication are compared as numbers and go before text keys,
+    records without value go last
+*/
+fn compare_keys(a: &Option<String>, b: &Option<String>) -> Ordering {
+    let number = |v: &str| v.parse::<f64>().ok();
+    match (a, b) {
+        (Some(a), Some(b)) => match (number(a), number(b)) {
+            (Some(x), Some(y)) => x.total_cmp(&y).then_with(|| a.cmp(b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => a.cmp(b),
+        },
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+pub fn summarize_numbers(attribute: &str, values: &[f64]) -> NumberSummary {
+    let sum: f64 = values.iter().sum();
+    NumberSummary {
+        attribute: attribute.to_string(),
+        count: values.len() as u32,
+        sum,
+        avg: match values.is_empty() {
+            true => None,
+            false => Some(sum / values.len() as f64),
+        },
+        min: values.iter().copied().reduce(f64::min),
+        max: values.iter().copied().reduce(f64::max),
+    }
+}
+
+pub fn aggregate_records(
+    records: &[RecordFromDb],
+    group_by: &GroupBy,
+    numbers: &[String],
+) -> Vec<AggregateGroup> {
+    let mut groups: HashMap<Option<String>, (u32, Vec<Vec<f64>>)> = HashMap::new();
+
+    for record in records {
+        let values: Vec<Option<f64>> = numbers
+            .iter()
+            .map(|n| number_value(record.attrs.get(n)))
+            .collect();
+
+        for key in group_keys(record, group_by) {
+            let (count, group_values) = groups
+                .entry(key)
+                .or_insert_with(|| (0, vec![vec![]; numbers.len()]));
+            *count += 1;
+            for (i, v) in values.iter().enumerate() {
+                if let Some(v) = v {
+                    group_values[i].push(*v);
+                }
+            }
+        }
+    }
+
+    let mut result: Vec<AggregateGroup> = groups
+        .into_iter()
+        .map(|(key, (count, values))| AggregateGroup {
+            key,
+            count,
+            numbers: numbers
+                .iter()
+                .zip(values.iter())
+                .map(|(n, v)| summarize_numbers(n, v))
+                .collect(),
+        })
+        .collect();
+
+    result.sort_by(|a, b| compare_keys(&a.key, &b.key));
+    result
+}
+
+pub fn check_attribute(
+    schema: &Schema,
+    name: &str,
+    type_check: fn(&SchemaAttrType) -> bool,
+    hint: &str,
+) -> Result<(), Box<ErrFR>> {
+    match schema.items.iter().find(|i| i.name == name) {
+        Some(item) if type_check(&item.value) => Ok(()),
+        Some(_) => Err(Box::new(
+            ErrFR::new("Attribute can't be used here")
+                .info(hint)
+                .raw(name),
+        )),
+        None => Err(Box::new(
+            ErrFR::new("Attribute is not in schema")
+                .info(&schema.name)
+                .raw(name),
+        )),
+    }
+}
+
+fn is_any_date(t: &SchemaAttrType) -> bool {
+    is_date(t) || is_dates(t)
+}
+
+fn is_groupable_value(t: &SchemaAttrType) -> bool {
+    !is_dates_pair(t)
+}
+
+fn check_options(schema: &Schema, options: &AggregateOptions) -> Result<(), Box<ErrFR>> {
+    match &options.group_by {
+        GroupBy::All => Ok(()),
+        GroupBy::Value(attr) => check_attribute(
+            schema,
+            attr,
+            is_groupable_value,
+            "Group dates by year or month",
+        ),
+        GroupBy::Year(attr) | GroupBy::Month(attr) => check_attribute(
+            schema,
+            attr,
+            is_any_date,
+            "Only dates can be grouped by year or month",
+        ),
+    }?;
+
+    for number in options.numbers.iter() {
+        check_attribute(schema, number, is_number, "Only numbers can be summarized")?;
+    }
+    Ok(())
+}
+
+/* Cached records of the schema folder, files are not read */
+pub async fn get_schema_records(
+    ctx: &AppContext,
+    folder_relative: &Path,
+) -> Result<(Schema, Vec<RecordFromDb>), Box<ErrFR>> {
+    let list = get_files_by_path(ctx, folder_relative).await?;
+    let schema_owner = list.schema.location.schema_owner_folder;
+    let lock = ctx.schemas_cache.get_read_lock().await;
+
+    // Path prefix also matches sibling folders and subfolders with their own schema
+    let records = list
+        .records
+        .into_iter()
+        .filter(|r| {
+            r.path.as_ref().is_some_and(|p| {
+                Path::new(p).starts_with(folder_relative)
+                    && ctx
+                        .schemas_cache
+                        .get_schema_by_lock(&lock, Path::new(p))
+                        .is_some_and(|s| s.location.schema_owner_folder == schema_owner)
+            })
+        })
+        .collect();
+
+    Ok((list.schema.schema, records))
+}
+
+pub async fn aggregate(
+    ctx: &AppContext,
+    options: &AggregateOptions,
+) -> Result<AggregateResult, Box<ErrFR>> {
+    let (schema, records) = get_schema_records(ctx, &normalize_path_to_os(&options.folder)).await?;
+    check_options(&schema, options)?;
+
+    Ok(AggregateResult {
+        records: records.len() as u32,
+        groups: aggregate_records(&records, &options.group_by, &options.numbers),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::types::DatePair;
+
+    use super::*;
+
+    fn record(attrs: Vec<(&str, AttrValue)>) -> RecordFromDb {
+        RecordFromDb {
+            attrs: attrs.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn finished(dates: &[&str]) -> AttrValue {
+        AttrValue::DatePairVec(Some(
+            dates
+                .iter()
+                .map(|d| DatePair {
+                    started: None,
+                    finished: Some(d.to_string()),
+                })
+                .collect(),
+        ))
+    }
+
+    fn records() -> Vec<RecordFromDb> {
+        vec![
+            record(vec![
+                ("author", AttrValue::String(Some("Le Guin".to_string()))),
+                ("rating", AttrValue::Float(Some(5.0))),
+                ("pages", AttrValue::Integer(Some(300.0))),
+                (
+                    "read",
+                    finished(&["2024-05-01", "2025-01-10", "2025-12-30"]),
+                ),
+            ]),
+            record(vec![
+                ("author", AttrValue::String(Some("Le Guin".to_string()))),
+                ("rating", AttrValue::Float(Some(3.0))),
+                ("read", finished(&["2025-02-01"])),
+            ]),
+            record(vec![
+                ("author", AttrValue::String(Some(" ".to_string()))),
+                ("pages", AttrValue::Integer(Some(100.0))),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn group_by_year_of_finished() {
+        let groups = aggregate_records(
+            &records(),
+            &GroupBy::Year("read".to_string()),
+            &["pages".to_string()],
+        );
+        let keys: Vec<Option<&str>> = groups.iter().map(|g| g.key.as_deref()).collect();
+        assert_eq!(keys, vec![Some("2024"), Some("2025"), None]);
+
+        // Book finished twice in 2025 is counted once
+        assert_eq!(groups[1].count, 2);
+        assert_eq!(groups[1].numbers[0], summarize_numbers("pages", &[300.0]));
+    }
+
+    #[test]
+    fn group_by_value() {
+        let groups = aggregate_records(
+            &records(),
+            &GroupBy::Value("author".to_string()),
+            &["rating".to_string()],
+        );
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].key.as_deref(), Some("Le Guin"));
+        assert_eq!(groups[0].numbers[0].avg, Some(4.0));
+        assert_eq!(groups[0].numbers[0].min, Some(3.0));
+        assert_eq!(groups[0].numbers[0].max, Some(5.0));
+        assert_eq!(groups[1].key, None);
+        assert_eq!(groups[1].numbers[0].count, 0);
+        assert_eq!(groups[1].numbers[0].avg, None);
+
+        let months = aggregate_records(&records(), &GroupBy::Month("read".to_string()), &[]);
+        assert_eq!(months[0].key.as_deref(), Some("2024-05"));
+        assert_eq!(months.len(), 5);
+    }
+
+    #[test]
+    fn numeric_keys_order() {
+        let mut keys = vec![
+            Some("10".to_string()),
+            None,
+            Some("9".to_string()),
+            Some("abc".to_string()),
+            Some("1a".to_string()),
+            Some("2".to_string()),
+        ];
+        keys.sort_by(compare_keys);
+        assert_eq!(
+            keys,
+            vec![
+                Some("2".to_string()),
+                Some("9".to_string()),
+                Some("10".to_string()),
+                Some("1a".to_string()),
+                Some("abc".to_string()),
+                None
+            ]
+        );
+    }
+}
@@ -0,0 +1,863 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::cache::query::RecordFromDb;
+use crate::core::core_state::AppContext;
+use crate::core::journal::{snapshot_file, JournalAction};
+use crate::export::{escape_html, record_name};
+use crate::files::markdown::{encode_uri_component, render_markdown, MarkdownRenderOptions};
+use crate::schema::types::{
+    find_schema_item, is_collection, is_dates_pair, is_number, is_text, is_text_or_collection,
+    AttrValue, Schema, SchemaAttrType,
+};
+use crate::stats::aggregate::{
+    aggregate_records, check_attribute, get_schema_records, number_value, parse_day, GroupBy,
+};
+use crate::utils::errorhandling::ErrFR;
+use crate::utils::helpers::{normalize_path_to_os, sanitize_file_name, split_names};
+
+const TOP_COUNT: usize = 5;
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/* Attribute names the review is built from, missing ones leave their sections empty */
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+pub struct ReviewAttributes {
+    /* Dates pair collection, finished dates decide which records belong to the year */
+    pub dates: Option<String>,
+    pub title: Option<String>,
+    pub rating: Option<String>,
+    /* Number like pages or runtime, for totals and longest and shortest items */
+    pub length: Option<String>,
+    pub authors: Option<String>,
+    pub tags: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, TS)]
+#[ts(export)]
+pub enum ReviewReportFormat {
+    Markdown,
+    Html,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct ReviewReportOptions {
+    pub format: ReviewReportFormat,
+    /* Folder relative to root path, markdown can't be saved into a folder with schema */
+    pub folder: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[ts(export)]
+pub struct YearReviewOptions {
+    /* Schema folder relative to root path */
+    pub folder: String,
+    pub year: i32,
+    /* Guessed from schema when not passed */
+    pub attributes: Option<ReviewAttributes>,
+    /* Report is only saved when passed */
+    pub report: Option<ReviewReportOptions>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+pub struct ReviewItem {
+    /* Relative path to root path */
+    pub path: String,
+    pub name: String,
+    /* Rating, length or number of finishes, depending on the list */
+    pub value: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+pub struct FrequentValue {
+    pub value: String,
+    pub count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+pub struct MonthSummary {
+    /* 1 to 12 */
+    pub month: u32,
+    /* Records finished in that month */
+    pub count: u32,
+    pub length: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[ts(export)]
+pub struct YearReview {
+    pub year: i32,
+    pub schema_name: String,
+    pub attributes: ReviewAttributes,
+    /* Records finished at least once in the year */
+    pub finished: u32,
+    /* Every finish in the year, re-reads included */
+    pub finishes: u32,
+    /* Pairs started in the year */
+    pub started: u32,
+    pub total_length: Option<f64>,
+    pub average_rating: Option<f64>,
+    pub months: Vec<MonthSummary>,
+    pub top_rated: Vec<ReviewItem>,
+    pub authors: Vec<FrequentValue>,
+    pub tags: Vec<FrequentValue>,
+    pub longest: Option<ReviewItem>,
+    pub shortest: Option<ReviewItem>,
+    /* Records finished in the year that were finished before, value is number of finishes so far */
+    pub rereads: Vec<ReviewItem>,
+    /* Saved report relative to root path */
+    pub report: Option<String>,
+}
+
+pub struct YearReviewOutput {
+    pub review: YearReview,
+    pub undo: Vec<JournalAction>,
+}
+
+pub fn guess_review_attributes(schema: &Schema) -> ReviewAttributes {
+    ReviewAttributes {
+        dates: find_schema_item(
+            schema,
+            &["read", "watched", "played", "finished", "dates"],
+            is_dates_pair,
+        )
+        .or(schema
+            .items
+            .iter()
+            .find(|i| is_dates_pair(&i.value))
+            .map(|i| i.name.clone())),
+        title: find_schema_item(schema, &["title", "name"], is_text),
+        rating: find_schema_item(schema, &["myRating", "rating", "score", "stars"], is_number),
+        length: find_schema_item(
+            schema,
+            &[
+                "pages",
+                "pageCount",
+                "length",
+                "runtime",
+                "duration",
+                "minutes",
+            ],
+            is_number,
+        ),
+        authors: find_schema_item(
+            schema,
+            &[
+                "authors",
+                "author",
+                "directors",
+                "director",
+                "creator",
+                "artist",
+            ],
+            is_text_or_collection,
+        ),
+        tags: find_schema_item(schema, &["tags", "genres", "genre"], is_collection),
+    }
+}
+
+fn check_attributes(schema: &Schema, attributes: &ReviewAttributes) -> Result<(), Box<ErrFR>> {
+    let Some(dates) = attributes.dates.as_deref() else {
+        return Err(Box::new(
+            ErrFR::new("Schema has no dates to build review from")
+                .info("Add a collection of start and finish dates to schema"),
+        ));
+    };
+    check_attribute(
+        schema,
+        dates,
+        is_dates_pair,
+        "Choose a collection of date pairs",
+    )?;
+
+    let checks = [
+        (
+            &attributes.title,
+            is_text as fn(&SchemaAttrType) -> bool,
+            "Title must be a text",
+        ),
+        (&attributes.rating, is_number, "Rating must be a number"),
+        (&attributes.length, is_number, "Length must be a number"),
+        (
+            &attributes.authors,
+            is_text_or_collection,
+            "Authors must be a text",
+        ),
+        (
+            &attributes.tags,
+            is_collection,
+            "Tags must be a text collection",
+        ),
+    ];
+    for (attr, type_check, hint) in checks {
+        if let Some(attr) = attr {
+            check_attribute(schema, attr, type_check, hint)?;
+        }
+    }
+    Ok(())
+}
+
+fn finished_dates(record: &RecordFromDb, attr: &str) -> Vec<NaiveDate> {
+    match record.attrs.get(attr) {
+        Some(AttrValue::DatePairVec(Some(pairs))) => pairs
+            .iter()
+            .filter_map(|p| parse_day(p.finished.as_deref()?))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn started_dates(record: &RecordFromDb, attr: &str) -> Vec<NaiveDate> {
+    match record.attrs.get(attr) {
+        Some(AttrValue::DatePairVec(Some(pairs))) => pairs
+            .iter()
+            .filter_map(|p| parse_day(p.started.as_deref()?))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn review_item(record: &RecordFromDb, title: Option<&str>, value: Option<f64>) -> ReviewItem {
+    let name = match title.and_then(|t| record.attrs.get(t)) {
+        Some(AttrValue::String(Some(v))) if !v.trim().is_empty() => v.trim().to_string(),
+        _ => record_name(record),
+    };
+    ReviewItem {
+        path: record.path.clone().unwrap_or_default(),
+        name,
+        value,
+    }
+}
+
+/* Text values are split like names, importers write several authors into one text attribute */
+fn frequent_values(records: &[RecordFromDb], attr: Option<&str>) -> Vec<FrequentValue> {
+    let Some(attr) = attr else {
+        return vec![];
+    };
+
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for record in records.iter() {
+        let values: BTreeSet<String> = match record.attrs.get(attr) {
+            Some(AttrValue::String(Some(v))) => split_names(v).into_iter().collect(),
+            Some(AttrValue::StringVec(Some(v))) => v
+                .iter()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+            _ => BTreeSet::new(),
+        };
+        for value in values {
+            *counts.entry(value).or_default() += 1;
+        }
+    }
+
+    let mut values: Vec<FrequentValue> = counts
+        .into_iter()
+        .map(|(value, count)| FrequentValue { value, count })
+        .collect();
+    values.sort_by(|a, b| b.count.cmp(&a.count).then(a.value.cmp(&b.value)));
+    values.truncate(TOP_COUNT);
+    values
+}
+
+fn month_summaries(
+    records: &[RecordFromDb],
+    attributes: &ReviewAttributes,
+    year: i32,
+) -> Vec<MonthSummary> {
+    let Some(dates) = attributes.dates.as_deref() else {
+        return vec![];
+    };
+    let numbers: Vec<String> = attributes.length.iter().cloned().collect();
+    let groups = aggregate_records(records, &GroupBy::Month(dates.to_string()), &numbers);
+
+    (1..=12)
+        .map(|month| {
+            let key = format!("{:04}-{:02}", year, month);
+            let group = groups
+                .iter()
+                .find(|g| g.key.as_deref() == Some(key.as_str()));
+            MonthSummary {
+                month,
+                count: group.map(|g| g.count).unwrap_or_default(),
+                length: group
+                    .and_then(|g| g.numbers.first())
+                    .map(|n| n.sum)
+                    .unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/* Records belong to the year when they were finished in it at least once */
+pub fn review_records(
+    schema: &Schema,
+    records: &[RecordFromDb],
+    attributes: &ReviewAttributes,
+    year: i32,
+) -> YearReview {
+    let dates = attributes.dates.as_deref().unwrap_or_default();
+    let title = attributes.title.as_deref();
+    let number = |record: &RecordFromDb, attr: &Option<String>| {
+        attr.as_ref()
+            .and_then(|a| number_value(record.attrs.get(a)))
+    };
+
+    let year_records: Vec<RecordFromDb> = records
+        .iter()
+        .filter(|r| finished_dates(r, dates).iter().any(|d| d.year() == year))
+        .cloned()
+        .collect();
+
+    let finishes = year_records
+        .iter()
+        .map(|r| {
+            finished_dates(r, dates)
+                .iter()
+                .filter(|d| d.year() == year)
+                .count() as u32
+        })
+        .sum();
+    let started = records
+        .iter()
+        .map(|r| {
+            started_dates(r, dates)
+                .iter()
+                .filter(|d| d.year() == year)
+                .count() as u32
+        })
+        .sum();
+
+    let lengths: Vec<(&RecordFromDb, f64)> = year_records
+        .iter()
+        .filter_map(|r| Some((r, number(r, &attributes.length)?)))
+        .collect();
+    let ratings: Vec<(&RecordFromDb, f64)> = year_records
+        .iter()
+        .filter_map(|r| Some((r, number(r, &attributes.rating)?)))
+        .collect();
+
+    let mut top_rated: Vec<ReviewItem> = ratings
+        .iter()
+        .map(|(r, v)| review_item(r, title, Some(*v)))
+        .collect();
+    top_rated.sort_by(|a, b| {
+        b.value
+            .partial_cmp(&a.value)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.name.cmp(&b.name))
+    });
+    top_rated.truncate(TOP_COUNT);
+
+    let mut rereads: Vec<ReviewItem> = year_records
+        .iter()
+        .filter_map(|r| {
+            let finished = finished_dates(r, dates);
+            let last = finished.iter().filter(|d| d.year() == year).max()?;
+            let so_far = finished.iter().filter(|d| *d <= last).count();
+            match so_far > 1 {
+                true => Some(review_item(r, title, Some(so_far as f64))),
+                false => None,
+            }
+        })
+        .collect();
+    rereads.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let by_length = |a: &&(&RecordFromDb, f64), b: &&(&RecordFromDb, f64)| {
+        a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)
+    };
+
+    YearReview {
+        year,
+        schema_name: schema.name.clone(),
+        attributes: attributes.clone(),
+        finished: year_records.len() as u32,
+        finishes,
+        started,
+        total_length: attributes
+            .length
+            .as_ref()
+            .map(|_| lengths.iter().map(|(_, v)| v).sum()),
+        average_rating: match ratings.is_empty() {
+            true => None,
+            false => Some(ratings.iter().map(|(_, v)| v).sum::<f64>() / ratings.len() as f64),
+        },
+        months: month_summaries(&year_records, attributes, year),
+        top_rated,
+        authors: frequent_values(&year_records, attributes.authors.as_deref()),
+        tags: frequent_values(&year_records, attributes.tags.as_deref()),
+        longest: lengths
+            .iter()
+            .max_by(by_length)
+            .map(|(r, v)| review_item(r, title, Some(*v))),
+        shortest: lengths
+            .iter()
+            .min_by(by_length)
+            .map(|(r, v)| review_item(r, title, Some(*v))),
+        rereads,
+        report: None,
+    }
+}
+
+fn escape_markdown(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '!' | '~'
+        ) {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+fn format_value(value: f64) -> String {
+    match value.fract() == 0.0 {
+        true => format!("{}", value),
+        false => format!("{:.1}", value),
+    }
+}
+
+fn attr_label(schema: &Schema, attr: &Option<String>, default: &str) -> String {
+    attr.as_ref()
+        .and_then(|a| schema.items.iter().find(|i| &i.name == a))
+        .map(|i| i.label().to_string())
+        .unwrap_or_else(|| default.to_string())
+}
+
+fn push_items(lines: &mut Vec<String>, heading: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    lines.push(format!("## {}", escape_markdown(heading)));
+    lines.push(String::new());
+    lines.extend(items.iter().cloned());
+    lines.push(String::new());
+}
+
+pub fn render_review_markdown(schema: &Schema, review: &YearReview) -> String {
+    let attributes = &review.attributes;
+    let length_label = attr_label(schema, &attributes.length, "Length");
+
+    let mut lines: Vec<String> = vec![
+        format!(
+            "# {}: {} in review",
+            escape_markdown(&review.schema_name),
+            review.year
+        ),
+        String::new(),
+        format!("- Finished: {}", review.finished),
+        format!("- Times finished, re-reads included: {}", review.finishes),
+        format!("- Started: {}", review.started),
+    ];
+    if let Some(total) = review.total_length {
+        lines.push(format!(
+            "- {}: {}",
+            escape_markdown(&length_label),
+            format_value(total)
+        ));
+    }
+    if let Some(avg) = review.average_rating {
+        lines.push(format!("- Average rating: {}", format_value(avg)));
+    }
+    lines.push(String::new());
+
+    if review.finished > 0 {
+        lines.push("## By month".to_string());
+        lines.push(String::new());
+        match review.total_length {
+            Some(_) => {
+                lines.push(format!(
+                    "| Month | Finished | {} |",
+                    escape_markdown(&length_label)
+                ));
+                lines.push("| --- | ---: | ---: |".to_string());
+            }
+            None => {
+                lines.push("| Month | Finished |".to_string());
+                lines.push("| --- | ---: |".to_string());
+            }
+        }
+        for m in review.months.iter() {
+            let name = MONTHS[(m.month as usize).saturating_sub(1) % 12];
+            lines.push(match review.total_length {
+                Some(_) => format!("| {} | {} | {} |", name, m.count, format_value(m.length)),
+                None => format!("| {} | {} |", name, m.count),
+            });
+        }
+        lines.push(String::new());
+    }
+
+    let numbered = |items: &[ReviewItem]| -> Vec<String> {
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                format!(
+                    "{}. {}: {}",
+                    i + 1,
+                    escape_markdown(&item.name),
+                    item.value.map(format_value).unwrap_or_default()
+                )
+            })
+            .collect()
+    };
+    let frequent = |values: &[FrequentValue]| -> Vec<String> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| format!("{}. {}: {}", i + 1, escape_markdown(&v.value), v.count))
+            .collect()
+    };
+
+    push_items(&mut lines, "Top rated", &numbered(&review.top_rated));
+    push_items(
+        &mut lines,
+        &attr_label(schema, &attributes.authors, "Authors"),
+        &frequent(&review.authors),
+    );
+    push_items(
+        &mut lines,
+        &attr_label(schema, &attributes.tags, "Tags"),
+        &frequent(&review.tags),
+    );
+
+    let extremes: Vec<String> = [("Longest", &review.longest), ("Shortest", &review.shortest)]
+        .iter()
+        .filter_map(|(label, item)| {
+            let item = item.as_ref()?;
+            Some(format!(
+                "- {}: {} ({})",
+                label,
+                escape_markdown(&item.name),
+                item.value.map(format_value).unwrap_or_default()
+            ))
+        })
+        .collect();
+    push_items(&mut lines, "Longest and shortest", &extremes);
+
+    let rereads: Vec<String> = review
+        .rereads
+        .iter()
+        .map(|item| {
+            format!(
+                "- {}: finished {} times",
+                escape_markdown(&item.name),
+                item.value.map(format_value).unwrap_or_default()
+            )
+        })
+        .collect();
+    push_items(&mut lines, "Re-reads", &rereads);
+
+    lines.join("\n").trim_end().to_string() + "\n"
+}
+
+fn render_review_html(title: &str, markdown: &str) -> String {
+    let body = render_markdown(
+        markdown,
+        &MarkdownRenderOptions {
+            record_folder: Path::new(""),
+            asset_url: &encode_uri_component,
+        },
+    );
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{}</title>
+<style>
+body {{ font-family: system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; line-height: 1.5; }}
+table {{ border-collapse: collapse; }}
+th, td {{ padding: 0.25rem 0.75rem; border-bottom: 1px solid #ddd; }}
+</style>
+</head>
+<body>
+{}
+</body>
+</html>
+"#,
+        escape_html(title),
+        body.html
+    )
+}
+
+/* Existing report of the same year is overwritten, undo brings it back */
+async fn save_report(
+    ctx: &AppContext,
+    schema: &Schema,
+    review: &YearReview,
+    options: &ReviewReportOptions,
+) -> Result<(PathBuf, JournalAction), Box<ErrFR>> {
+    let folder = normalize_path_to_os(&options.folder);
+    let folder_absolute = ctx.relative_path_to_absolute(&folder).await?;
+    if !folder_absolute.is_dir() {
+        return Err(Box::new(
+            ErrFR::new("Folder for report does not exist").raw(folder.to_string_lossy()),
+        ));
+    }
+
+    // Markdown file in a schema folder would be read as a record
+    if options.format == ReviewReportFormat::Markdown
+        && ctx.schemas_cache.get_schema(&folder).await.is_some()
+    {
+        return Err(Box::new(
+            ErrFR::new("Markdown report can't be saved into a folder with schema")
+                .info("Choose a folder without schema or save report as HTML")
+                .raw(folder.to_string_lossy()),
+        ));
+    }
+
+    let title = format!("{} {} in review", review.schema_name, review.year);
+    let markdown = render_review_markdown(schema, review);
+    let (content, extension) = match options.format {
+        ReviewReportFormat::Markdown => (markdown, "md"),
+        ReviewReportFormat::Html => (render_review_html(&title, &markdown), "html"),
+    };
+
+    let path_relative = folder.join(format!("{}.{}", sanitize_file_name(&title), extension));
+    let path = ctx.relative_path_to_absolute(&path_relative).await?;
+    let undo = snapshot_file(ctx, &path_relative).await;
+
+    fs::write(&path, content).map_err(|e| {
+        Box::new(
+            ErrFR::new("Error writing to disk")
+                .info(&path.to_string_lossy())
+                .raw(e),
+        )
+    })?;
+
+    Ok((path_relative, undo))
+}
+
+pub async fn year_review(
+    ctx: &AppContext,
+    options: &YearReviewOptions,
+) -> Result<YearReviewOutput, Box<ErrFR>> {
+    let (schema, records) = get_schema_records(ctx, &normalize_path_to_os(&options.folder)).await?;
+    let attributes = options
+        .attributes
+        .clone()
+        .unwrap_or_else(|| guess_review_attributes(&schema));
+    check_attributes(&schema, &attributes)?;
+
+    let mut review = review_records(&schema, &records, &attributes, options.year);
+    let mut undo: Vec<JournalAction> = vec![];
+
+    if let Some(report) = options.report.as_ref() {
+        let (path, action) = save_report(ctx, &schema, &review, report).await?;
+        review.report = Some(path.to_string_lossy().to_string());
+        undo.push(action);
+    }
+
+    Ok(YearReviewOutput { review, undo })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::types::{
+        DatePair, DatesPairCollectionSettings, NumberSettings, SchemaItem, TextCollectionSettings,
+        TextSettings,
+    };
+
+    use super::*;
+
+    fn schema() -> Schema {
+        let item = |name: &str, value: SchemaAttrType| SchemaItem {
+            name: name.to_string(),
+            value,
+        };
+        Schema {
+            fill_api_search_from: None,
+            fill_from_filename: None,
+            name: "Books".to_string(),
+            items: vec![
+                item("title", SchemaAttrType::Text(TextSettings::default())),
+                item("author", SchemaAttrType::Text(TextSettings::default())),
+                item("pages", SchemaAttrType::Number(NumberSettings::default())),
+                item(
+                    "myRating",
+                    SchemaAttrType::Number(NumberSettings::default()),
+                ),
+                item(
+                    "read",
+                    SchemaAttrType::DatesPairCollection(DatesPairCollectionSettings::default()),
+                ),
+                item(
+                    "tags",
+                    SchemaAttrType::TextCollection(TextCollectionSettings::default()),
+                ),
+            ],
+            version: "1.0".to_string(),
+        }
+    }
+
+    fn record(
+        path: &str,
+        author: &str,
+        pages: f64,
+        rating: f64,
+        read: &[(&str, &str)],
+    ) -> RecordFromDb {
+        let pairs = read
+            .iter()
+            .map(|(s, f)| DatePair {
+                started: Some(s.to_string()).filter(|s| !s.is_empty()),
+                finished: Some(f.to_string()).filter(|f| !f.is_empty()),
+            })
+            .collect();
+        RecordFromDb {
+            path: Some(path.to_string()),
+            attrs: [
+                ("author", AttrValue::String(Some(author.to_string()))),
+                ("pages", AttrValue::Integer(Some(pages))),
+                ("myRating", AttrValue::Float(Some(rating))),
+                ("read", AttrValue::DatePairVec(Some(pairs))),
+                (
+                    "tags",
+                    AttrValue::StringVec(Some(vec!["sf".to_string(), author.to_string()])),
+                ),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn records() -> Vec<RecordFromDb> {
+        vec![
+            record(
+                "books/Dune.md",
+                "Herbert",
+                600.0,
+                4.0,
+                &[("2023-01-01", "2023-02-01"), ("2025-03-01", "2025-03-20")],
+            ),
+            record(
+                "books/Solaris.md",
+                "Lem",
+                200.0,
+                5.0,
+                &[("2025-01-05", "2025-01-15"), ("2025-11-01", "2025-11-10")],
+            ),
+            record("books/Eden.md", "Lem", 300.0, 3.0, &[("2025-12-20", "")]),
+            record(
+                "books/Old.md",
+                "Asimov",
+                250.0,
+                5.0,
+                &[("2024-01-01", "2024-01-09")],
+            ),
+        ]
+    }
+
+    #[test]
+    fn review_of_year() {
+        let schema = schema();
+        let attributes = guess_review_attributes(&schema);
+        assert_eq!(attributes.length.as_deref(), Some("pages"));
+        assert_eq!(attributes.rating.as_deref(), Some("myRating"));
+
+        let review = review_records(&schema, &records(), &attributes, 2025);
+        assert_eq!(review.finished, 2);
+        assert_eq!(review.finishes, 3);
+        assert_eq!(review.started, 4);
+        assert_eq!(review.total_length, Some(800.0));
+        assert_eq!(review.average_rating, Some(4.5));
+
+        assert_eq!(review.months.len(), 12);
+        assert_eq!(review.months[0].count, 1);
+        assert_eq!(review.months[2].length, 600.0);
+        assert_eq!(review.months[11].count, 0);
+
+        assert_eq!(review.top_rated[0].name, "Solaris");
+        assert_eq!(review.longest.as_ref().unwrap().name, "Dune");
+        assert_eq!(review.shortest.as_ref().unwrap().name, "Solaris");
+        assert_eq!(
+            review.tags[0],
+            FrequentValue {
+                value: "sf".to_string(),
+                count: 2
+            }
+        );
+
+        let rereads: Vec<(&str, Option<f64>)> = review
+            .rereads
+            .iter()
+            .map(|r| (r.name.as_str(), r.value))
+            .collect();
+        assert_eq!(rereads, vec![("Dune", Some(2.0)), ("Solaris", Some(2.0))]);
+    }
+
+    #[test]
+    fn text_authors_are_split() {
+        let mut records = records();
+        records[0].attrs.insert(
+            "author".to_string(),
+            AttrValue::String(Some("Lem; Herbert".to_string())),
+        );
+
+        let authors: Vec<(String, u32)> = frequent_values(&records, Some("author"))
+            .into_iter()
+            .map(|v| (v.value, v.count))
+            .collect();
+        assert_eq!(
+            authors,
+            vec![
+                ("Lem".to_string(), 3),
+                ("Asimov".to_string(), 1),
+                ("Herbert".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_report() {
+        let schema = schema();
+        let attributes = guess_review_attributes(&schema);
+        let mut records = records();
+        records[0].attrs.insert(
+            "title".to_string(),
+            AttrValue::String(Some("Dune | *Part One*".to_string())),
+        );
+
+        let review = review_records(&schema, &records, &attributes, 2025);
+        let markdown = render_review_markdown(&schema, &review);
+
+        assert!(markdown.starts_with("# Books: 2025 in review\n"));
+        assert!(markdown.contains("| March | 1 | 600 |"));
+        assert!(markdown.contains("1. Solaris: 5\n"));
+        assert!(markdown.contains("- Longest: Dune \\| \\*Part One\\* (600)"));
+        assert!(markdown.contains("## Re-reads"));
+
+        let html = render_review_html("Books 2025 in review", &markdown);
+        assert!(html.contains("<td>March</td>"));
+    }
+}
@@ -1,12 +1,54 @@
-use std::{path::Path, time::Duration};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use tauri::Manager;
+use tauri::{Listener, Manager};
 
 use crate::{
-    cache::query::get_files_by_path,
+    backup::archive::{create_backup, partial_archive_path, restore_backup, BackupOptions},
+    cache::{
+        cache_thing::{cache_file, cache_unmanaged_file},
+        query::{get_files_by_path, get_unmanaged_files_by_path, RecordFromDb},
+    },
     core::core_state::CoreStateManager,
+    export::{
+        citation::{
+            export_citations, load_citation_mapping, save_citation_mapping, CitationFormat,
+        },
+        csv::{export_csv, CsvExportOptions},
+        ical::{export_ical, IcalExportOptions, MissingFinishedRule, MissingStartedRule},
+        json::{export_dump, DumpExportOptions, DumpFormat},
+        site::{export_site, SiteExportOptions, SiteIndexLayout},
+    },
+    files::{
+        assets::{
+            get_asset_report, import_asset, trash_orphaned_assets, AssetSource, ASSETS_FOLDER_NAME,
+        },
+        batch::{BatchOperation, BatchTarget},
+        health::{apply_health_fixes, check_vault_health},
+        read_save::{read_file_by_path, save_file, update_attributes, FileReadMode},
+        thumbnails::{ThumbnailCache, ThumbnailSize},
+        trash::{delete_many_to_trash, delete_to_trash, list_trashed_records, restore_from_trash},
+        unmanaged::{move_unmanaged_to_folder, read_unmanaged_file},
+    },
+    import::{
+        calibre::{guess_calibre_mapping, import_calibre},
+        csv::{import_csv, CsvColumnMapping, CsvImportOptions},
+        dump::import_dump,
+        goodreads::{guess_goodreads_mapping, import_goodreads},
+        movies::{import_movies, preview_movie_import},
+    },
+    schema::types::{AttrValue, ImageSettings, SchemaAttrType, SchemaItem},
+    stats::{
+        aggregate::{aggregate, AggregateOptions, GroupBy},
+        review::{year_review, ReviewReportFormat, ReviewReportOptions, YearReviewOptions},
+    },
     tests::test_utils::{
-        app_creator, prepare_test_case, wait_for_condition_async, DEFAULT_RETRY_COUNT,
+        app_creator, cleanup_test_case, prepare_test_case, wait_for_condition_async,
+        DEFAULT_RETRY_COUNT,
     },
 };
 
@@ -97,3 +139,1348 @@ async fn test_new_schema_file_appears() {
         "After creating new schema file, relevant files were parsed"
     );
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_save_conflict_uses_content_hash() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+
+    let (test_dir, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+    let file_path = Path::new("books").join("How to Read a Book.md");
+
+    let read = read_file_by_path(&core.context, &file_path, FileReadMode::FullFile)
+        .await
+        .unwrap();
+    assert!(read.record.hash.is_some(), "Read record has no hash");
+
+    // Same content with a different mtime is not a conflict
+    let mut record = read.record.clone();
+    record.modified = Some(0);
+    let saved = save_file(&core.context, record, false, false).await;
+    assert!(saved.is_ok(), "Save with matching hash failed");
+
+    // Hash from before the save is stale now
+    let mut record = read.record.clone();
+    record.markdown = Some("changed".to_string());
+    let conflict = save_file(&core.context, record, false, false).await;
+    assert!(conflict.is_err(), "Save with stale hash did not conflict");
+
+    let mut record = read.record;
+    record.hash = Some(saved.unwrap().hash);
+    let resaved = save_file(&core.context, record, false, false).await;
+    assert!(resaved.is_ok(), "Save with hash from last save failed");
+
+    cleanup_test_case(test_dir).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_update_attributes_keeps_body() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+
+    let (test_dir, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+    let file_path = Path::new("books").join("How to Read a Book.md");
+    let file_path_absolute = test_dir.join(&file_path);
+
+    let content_before = std::fs::read_to_string(&file_path_absolute).unwrap();
+    let body_before = content_before.split_once("\n---\n").unwrap().1.to_string();
+
+    let read = read_file_by_path(&core.context, &file_path, FileReadMode::OnlyMeta)
+        .await
+        .unwrap();
+
+    let patch = HashMap::from([("myRating".to_string(), AttrValue::Float(Some(3.5)))]);
+
+    let res = update_attributes(
+        &core.context,
+        &file_path,
+        patch.clone(),
+        None,
+        read.record.modified,
+        false,
+    )
+    .await;
+    assert!(res.is_ok(), "Update failed {:?}", res.err());
+
+    let content_after = std::fs::read_to_string(&file_path_absolute).unwrap();
+    let body_after = content_after.split_once("\n---\n").unwrap().1.to_string();
+    assert_eq!(body_before, body_after, "Markdown body changed");
+
+    let read_after = read_file_by_path(&core.context, &file_path, FileReadMode::OnlyMeta)
+        .await
+        .unwrap();
+    assert_eq!(
+        read_after.record.attrs["myRating"],
+        AttrValue::Float(Some(3.5))
+    );
+    assert_eq!(
+        read_after.record.attrs["author"],
+        read.record.attrs["author"]
+    );
+
+    // Stale modified time is a conflict
+    let stale = update_attributes(&core.context, &file_path, patch, None, Some(0), false).await;
+    assert!(
+        stale.is_err(),
+        "Update with stale modified time did not conflict"
+    );
+
+    cleanup_test_case(test_dir).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_import_image_deduplicates() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let png: Vec<u8> = vec![
+        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44,
+        0x52,
+    ];
+
+    let first = import_asset(&core.context, AssetSource::Bytes(png.clone()), "Some Book")
+        .await
+        .unwrap();
+    assert!(!first.existing);
+    assert!(first.path.starts_with("some-book-"));
+    assert!(first.path.ends_with(".png"));
+    assert!(path.join(ASSETS_FOLDER_NAME).join(&first.path).exists());
+
+    let second = import_asset(&core.context, AssetSource::Bytes(png), "Other Book")
+        .await
+        .unwrap();
+    assert!(second.existing);
+    assert_eq!(first.path, second.path);
+
+    let not_image = import_asset(
+        &core.context,
+        AssetSource::Bytes(b"plain text".to_vec()),
+        "Text",
+    )
+    .await;
+    assert!(not_image.is_err());
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_asset_report() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let mut sch = ctx
+        .schemas_cache
+        .get_schema_safe(Path::new("books"))
+        .await
+        .unwrap();
+    sch.schema.items.push(SchemaItem {
+        name: "cover".to_string(),
+        value: SchemaAttrType::Image(ImageSettings::default()),
+    });
+    ctx.schemas_cache
+        .save_schema(ctx, Path::new("books"), sch.schema)
+        .await
+        .unwrap();
+
+    let record_path = Path::new("books").join("How to Read a Book.md");
+    update_attributes(
+        ctx,
+        &record_path,
+        HashMap::from([(
+            "cover".to_string(),
+            AttrValue::String(Some("missing.png".to_string())),
+        )]),
+        None,
+        None,
+        true,
+    )
+    .await
+    .unwrap();
+    cache_file(ctx, &path.join(&record_path)).await.unwrap();
+
+    std::fs::create_dir_all(path.join(ASSETS_FOLDER_NAME)).unwrap();
+    std::fs::write(path.join(ASSETS_FOLDER_NAME).join("unused.png"), [0u8; 4]).unwrap();
+    std::fs::write(path.join(ASSETS_FOLDER_NAME).join("in body.png"), [0u8; 4]).unwrap();
+
+    // Only linked from markdown body, which is not in cache
+    let notes = path.join("books").join("How to Take Smart Notes.md");
+    let content = std::fs::read_to_string(&notes).unwrap();
+    std::fs::write(
+        &notes,
+        format!(
+            "{}\n![](../{}/in%20body.png)\n",
+            content, ASSETS_FOLDER_NAME
+        ),
+    )
+    .unwrap();
+
+    {
+        let _indexing = ctx.root_path_cached.write().await;
+        assert!(get_asset_report(ctx).await.is_err());
+    }
+
+    let report = get_asset_report(ctx).await.unwrap();
+    assert_eq!(report.orphaned, vec!["unused.png".to_string()]);
+    assert_eq!(report.missing.len(), 1);
+    assert_eq!(report.missing[0].attribute, "cover");
+    assert_eq!(report.missing[0].value, "missing.png");
+
+    let cleanup = trash_orphaned_assets(ctx, vec!["unused.png".into(), "nope.png".into()])
+        .await
+        .unwrap();
+    assert_eq!(cleanup.errors.unwrap().sub_errors.len(), 1);
+    assert!(!path.join(ASSETS_FOLDER_NAME).join("unused.png").exists());
+
+    cleanup_test_case(path).await;
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trash_list_and_restore() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let record = Path::new("books").join("How to Read a Book.md");
+    let content = std::fs::read_to_string(path.join(&record)).unwrap();
+
+    let trashed = delete_to_trash(ctx, &record).await.unwrap().unwrap();
+    assert_eq!(Path::new(&trashed.path), record);
+    assert!(!path.join(&record).exists());
+
+    let listed = list_trashed_records(ctx).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, trashed.id);
+
+    let restored = restore_from_trash(ctx, &trashed.id).await.unwrap();
+    assert_eq!(restored, record);
+    assert_eq!(
+        std::fs::read_to_string(path.join(&record)).unwrap(),
+        content
+    );
+    assert!(list_trashed_records(ctx).await.unwrap().is_empty());
+    assert!(restore_from_trash(ctx, &trashed.id).await.is_err());
+
+    cleanup_test_case(path).await;
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trash_many() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let paths = vec![
+        Path::new("books").join("How to Read a Book.md"),
+        Path::new("books").join("Missing.md"),
+        Path::new("books").join("How to Take Smart Notes.md"),
+    ];
+
+    let results = delete_many_to_trash(ctx, &paths).await;
+    assert_eq!(results.len(), 3);
+    assert!(results[1].is_err());
+
+    let first = results[0].as_ref().unwrap().as_ref().unwrap();
+    let last = results[2].as_ref().unwrap().as_ref().unwrap();
+    assert_eq!(Path::new(&first.path), paths[0]);
+    assert_eq!(Path::new(&last.path), paths[2]);
+    assert_ne!(first.id, last.id);
+    assert_eq!(list_trashed_records(ctx).await.unwrap().len(), 2);
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unmanaged_files() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Nested).await;
+
+    let unmanaged = get_unmanaged_files_by_path(ctx, Path::new("noschema"))
+        .await
+        .unwrap();
+    assert_eq!(unmanaged.len(), 1);
+    assert_eq!(unmanaged[0].keys[0], "watched");
+    assert!(unmanaged[0].keys.contains(&"title".to_string()));
+
+    // Sibling folder with the same prefix is not part of the folder
+    let sibling = path.join("noschema-old");
+    std::fs::create_dir(&sibling).unwrap();
+    std::fs::write(sibling.join("Old.md"), "---\nrating: 3\n---\n").unwrap();
+    cache_unmanaged_file(ctx, &sibling.join("Old.md"))
+        .await
+        .unwrap();
+    let unmanaged = get_unmanaged_files_by_path(ctx, Path::new("noschema"))
+        .await
+        .unwrap();
+    assert_eq!(unmanaged.len(), 1);
+    assert!(!unmanaged[0].keys.contains(&"rating".to_string()));
+    assert_eq!(
+        get_unmanaged_files_by_path(ctx, Path::new(""))
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let record_path = Path::new("noschema").join("nestno").join("Cool Movie.md");
+    let read = read_unmanaged_file(ctx, &record_path).await.unwrap();
+    assert!(read.parsing_error.is_none());
+    assert!(read.front_matter.contains("title: Cool Movie"));
+
+    assert!(
+        read_unmanaged_file(ctx, &Path::new("movies").join("Cool Movie.md"))
+            .await
+            .is_err()
+    );
+
+    let moved = move_unmanaged_to_folder(
+        ctx,
+        vec![record_path.to_string_lossy().to_string()],
+        Path::new("movies"),
+    )
+    .await
+    .unwrap();
+    assert!(moved.result.errors.is_none());
+    // Name is taken in target folder
+    assert_ne!(
+        moved.result.moved[0],
+        Path::new("movies")
+            .join("Cool Movie.md")
+            .to_string_lossy()
+            .to_string()
+    );
+    assert!(path.join(&moved.result.moved[0]).exists());
+    assert_eq!(moved.undo.len(), 1);
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_import_csv() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let csv_path = path.join("import.csv");
+    std::fs::write(
+        &csv_path,
+        "Title,Published,Rating\n\
+         How to Read a Book,1940,4.5\n\
+         Dune,not a year,5\n\
+         Solaris,1961,\n",
+    )
+    .unwrap();
+
+    let mapping = [
+        ("Title", "title"),
+        ("Published", "year"),
+        ("Rating", "myRating"),
+    ]
+    .iter()
+    .map(|(column, attribute)| CsvColumnMapping {
+        column: column.to_string(),
+        attribute: attribute.to_string(),
+    })
+    .collect();
+
+    let output = import_csv(
+        ctx,
+        &csv_path,
+        Path::new("books"),
+        &CsvImportOptions {
+            mapping,
+            filename_template: "{title}".to_string(),
+            collection_separator: ";".to_string(),
+            date_pair_separator: " - ".to_string(),
+            decimal_comma: false,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(output.result.errors.len(), 1);
+    assert_eq!(output.result.errors[0].row, 2);
+    assert_eq!(output.result.created.len(), 2);
+    assert_eq!(output.undo.len(), 2);
+    // Existing record is not overwritten
+    assert_ne!(
+        output.result.created[0],
+        Path::new("books")
+            .join("How to Read a Book.md")
+            .to_string_lossy()
+            .to_string()
+    );
+
+    let solaris = read_file_by_path(
+        ctx,
+        &Path::new("books").join("Solaris.md"),
+        FileReadMode::OnlyMeta,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        solaris.record.attrs.get("year"),
+        Some(&AttrValue::Integer(Some(1961.0)))
+    );
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_import_goodreads() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let csv_path = path.join("goodreads_library_export.csv");
+    std::fs::write(
+        &csv_path,
+        "Book Id,Title,Author,ISBN,ISBN13,My Rating,Year Published,Original Publication Year,Date Read,Date Added,Bookshelves,Exclusive Shelf,My Review\n\
+         1,How to Read a Book: The Classic Guide to Intelligent Reading,Mortimer J. Adler,,,5,1972,1940,,2020/01/01,,read,\n\
+         2,\"Dune (Dune, #1)\",Frank Herbert,\"=\"\"0441013597\"\"\",,4,2005,1965,2024/03/01,2024/01/15,classics,read,Great\n\
+         3,Dune,Frank Herbert,,,0,2005,1965,,2024/01/15,,to-read,\n",
+    )
+    .unwrap();
+
+    let schema = ctx
+        .schemas_cache
+        .get_schema_safe(Path::new("books"))
+        .await
+        .unwrap();
+    let mapping = guess_goodreads_mapping(&schema.schema);
+    assert_eq!(mapping.isbn.as_deref(), Some("ISBN13"));
+    assert_eq!(mapping.shelves.as_deref(), Some("tags"));
+
+    let output = import_goodreads(ctx, &csv_path, Path::new("books"), &mapping)
+        .await
+        .unwrap();
+
+    assert!(output.result.errors.is_empty());
+    assert_eq!(output.result.duplicates, vec![1, 3]);
+    assert_eq!(output.result.created.len(), 1);
+
+    let dune = read_file_by_path(
+        ctx,
+        Path::new(&output.result.created[0]),
+        FileReadMode::FullFile,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        dune.record.attrs.get("ISBN13"),
+        Some(&AttrValue::String(Some("0441013597".to_string())))
+    );
+    assert_eq!(
+        dune.record.attrs.get("tags"),
+        Some(&AttrValue::StringVec(Some(vec![
+            "read".to_string(),
+            "classics".to_string()
+        ])))
+    );
+    assert!(dune.record.markdown.unwrap().contains("Great"));
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_import_movies() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Nested).await;
+
+    let csv_path = path.join("diary.csv");
+    std::fs::write(
+        &csv_path,
+        "Date,Name,Year,Letterboxd URI,Rating,Rewatch,Tags,Watched Date\n\
+         2023-01-02,Heat,1995,https://boxd.it/a,4,,crime,2023-01-01\n\
+         2024-05-02,Heat,1995,https://boxd.it/b,4.5,Yes,,2024-05-01\n",
+    )
+    .unwrap();
+
+    let preview = preview_movie_import(ctx, &csv_path, Path::new("movies"), None)
+        .await
+        .unwrap();
+    assert_eq!(preview.entries.len(), 1);
+    assert_eq!(preview.mapping.watched.as_deref(), Some("watched"));
+    // Schema has no decimal places
+    assert_eq!(preview.entries[0].values["myRating"], "5");
+    assert!(!path.join("movies").join("Heat.md").exists());
+
+    let output = import_movies(ctx, &csv_path, Path::new("movies"), preview.mapping)
+        .await
+        .unwrap();
+    assert!(output.result.errors.is_empty());
+
+    let heat = read_file_by_path(
+        ctx,
+        &Path::new("movies").join("Heat.md"),
+        FileReadMode::OnlyMeta,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        heat.record.attrs.get("watched"),
+        Some(&AttrValue::StringVec(Some(vec![
+            "2023-01-01".to_string(),
+            "2024-05-01".to_string()
+        ])))
+    );
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_import_calibre() {
+    use sqlx::Connection;
+
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let library = tempfile::tempdir().unwrap();
+    let options = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(library.path().join("metadata.db"))
+        .create_if_missing(true);
+    let mut conn = sqlx::sqlite::SqliteConnection::connect_with(&options)
+        .await
+        .unwrap();
+    sqlx::raw_sql(
+        "CREATE TABLE books (id INTEGER PRIMARY KEY, title TEXT, uuid TEXT, path TEXT, has_cover BOOL, pubdate TIMESTAMP, series_index REAL);
+         CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT);
+         CREATE TABLE books_authors_link (id INTEGER PRIMARY KEY, book INTEGER, author INTEGER);
+         CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT);
+         CREATE TABLE books_tags_link (id INTEGER PRIMARY KEY, book INTEGER, tag INTEGER);
+         CREATE TABLE series (id INTEGER PRIMARY KEY, name TEXT);
+         CREATE TABLE books_series_link (id INTEGER PRIMARY KEY, book INTEGER, series INTEGER);
+         CREATE TABLE publishers (id INTEGER PRIMARY KEY, name TEXT);
+         CREATE TABLE books_publishers_link (id INTEGER PRIMARY KEY, book INTEGER, publisher INTEGER);
+         CREATE TABLE ratings (id INTEGER PRIMARY KEY, rating INTEGER);
+         CREATE TABLE books_ratings_link (id INTEGER PRIMARY KEY, book INTEGER, rating INTEGER);
+         CREATE TABLE identifiers (id INTEGER PRIMARY KEY, book INTEGER, type TEXT, val TEXT);
+         INSERT INTO books VALUES
+            (1, 'How to Read a Book: The Classic Guide to Intelligent Reading', 'u1', 'Mortimer J. Adler/How to Read a Book (1)', 0, '0101-01-01 00:00:00+00:00', 1.0),
+            (2, 'Dune', 'u2', 'Frank Herbert/Dune (2)', 1, '1965-08-01 00:00:00+00:00', 1.0);
+         INSERT INTO authors VALUES (1, 'Mortimer J. Adler'), (2, 'Frank Herbert');
+         INSERT INTO books_authors_link VALUES (1, 1, 1), (2, 2, 2);
+         INSERT INTO tags VALUES (1, 'classics'), (2, 'sci-fi');
+         INSERT INTO books_tags_link VALUES (1, 1, 1), (2, 2, 2), (3, 2, 1);
+         INSERT INTO ratings VALUES (1, 10), (2, 8);
+         INSERT INTO books_ratings_link VALUES (1, 1, 1), (2, 2, 2);
+         INSERT INTO identifiers VALUES (1, 2, 'isbn', '9780441013593');",
+    )
+    .execute(&mut conn)
+    .await
+    .unwrap();
+    conn.close().await.unwrap();
+
+    let cover_folder = library.path().join("Frank Herbert").join("Dune (2)");
+    std::fs::create_dir_all(&cover_folder).unwrap();
+    std::fs::write(
+        cover_folder.join("cover.jpg"),
+        [
+            0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48,
+            0x44, 0x52,
+        ],
+    )
+    .unwrap();
+
+    let schema = ctx
+        .schemas_cache
+        .get_schema_safe(Path::new("books"))
+        .await
+        .unwrap();
+    let mapping = guess_calibre_mapping(&schema.schema);
+    assert_eq!(mapping.cover.as_deref(), Some("cover"));
+    assert_eq!(mapping.pubdate.as_deref(), Some("year"));
+
+    let first = import_calibre(ctx, library.path(), Path::new("books"), &mapping)
+        .await
+        .unwrap();
+    assert!(first.result.errors.is_empty());
+    assert_eq!(first.result.created.len(), 1);
+    // Only tags differ from the existing record
+    assert_eq!(first.result.updated.len(), 1);
+
+    let dune = read_file_by_path(
+        ctx,
+        Path::new(&first.result.created[0]),
+        FileReadMode::OnlyMeta,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        dune.record.attrs.get("ISBN13"),
+        Some(&AttrValue::String(Some("9780441013593".to_string())))
+    );
+    assert_eq!(
+        dune.record.attrs.get("myRating"),
+        Some(&AttrValue::Float(Some(4.0)))
+    );
+    let Some(AttrValue::String(Some(cover))) = dune.record.attrs.get("cover") else {
+        panic!("cover was not imported");
+    };
+    assert!(path.join(ASSETS_FOLDER_NAME).join(cover).exists());
+
+    let second = import_calibre(ctx, library.path(), Path::new("books"), &mapping)
+        .await
+        .unwrap();
+    assert!(second.result.created.is_empty());
+    assert!(second.result.updated.is_empty());
+    assert_eq!(second.result.unchanged.len(), 2);
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dump_round_trip() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let dump_path = path.join("books.ndjson");
+    let exported = export_dump(
+        ctx,
+        Path::new("books"),
+        &dump_path,
+        &DumpExportOptions {
+            format: DumpFormat::Ndjson,
+            include_markdown: true,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(exported.records, 2);
+    assert!(exported.errors.is_none());
+
+    std::fs::create_dir(path.join("restored")).unwrap();
+    let output = import_dump(ctx, &dump_path, Path::new("restored"))
+        .await
+        .unwrap();
+    assert!(output.result.errors.is_empty());
+    assert_eq!(output.result.created.len(), 2);
+    assert!(ctx
+        .schemas_cache
+        .get_schema(Path::new("restored"))
+        .await
+        .is_some());
+
+    for name in ["How to Read a Book.md", "How to Take Smart Notes.md"] {
+        let original =
+            read_file_by_path(ctx, &Path::new("books").join(name), FileReadMode::FullFile)
+                .await
+                .unwrap();
+        let restored = read_file_by_path(
+            ctx,
+            &Path::new("restored").join(name),
+            FileReadMode::FullFile,
+        )
+        .await
+        .unwrap();
+        assert_eq!(original.record.attrs, restored.record.attrs);
+        assert_eq!(original.record.markdown, restored.record.markdown);
+    }
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_export_site() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let mut schema = ctx
+        .schemas_cache
+        .get_schema_safe(Path::new("books"))
+        .await
+        .unwrap()
+        .schema;
+    for item in schema.items.iter_mut() {
+        if let SchemaAttrType::Text(settings) = &mut item.value {
+            if item.name == "ISBN13" {
+                settings.is_private = Some(true);
+            }
+        }
+    }
+    ctx.schemas_cache
+        .save_schema(ctx, Path::new("books"), schema)
+        .await
+        .unwrap();
+
+    let output = tempfile::tempdir().unwrap();
+    let result = export_site(
+        ctx,
+        Path::new("books"),
+        output.path(),
+        &SiteExportOptions {
+            title: Some("What I've read".to_string()),
+            layout: SiteIndexLayout::Table,
+            include_markdown: true,
+            exclude_private: true,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.pages, 2);
+    assert!(result.errors.is_none());
+
+    let index = std::fs::read_to_string(output.path().join("index.html")).unwrap();
+    assert!(index.contains("What I&#39;ve read"));
+    assert!(index.contains("records/how-to-read-a-book.html"));
+    assert!(!index.contains("ISBN13"));
+
+    let record = std::fs::read_to_string(
+        output
+            .path()
+            .join("records")
+            .join("how-to-read-a-book.html"),
+    )
+    .unwrap();
+    assert!(record.contains("<p>Lorem ipsum"));
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_backup_round_trip() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+    let root = ctx.root_path_as_buf().await.unwrap();
+    std::fs::write(root.join(".gitignore"), "drafts/\n").unwrap();
+    std::fs::create_dir(root.join("drafts")).unwrap();
+    std::fs::write(root.join("drafts").join("Draft.md"), "draft").unwrap();
+    std::fs::create_dir(root.join(".trash")).unwrap();
+    std::fs::write(root.join(".trash").join("Old.md"), "old").unwrap();
+
+    let output = tempfile::tempdir().unwrap();
+    let archive = output.path().join("vault.zip");
+    let options = BackupOptions {
+        include_configs: true,
+        include_assets: true,
+        exclude_ignored: true,
+    };
+
+    // Archive inside of the vault would contain itself
+    assert!(create_backup(ctx, &root.join("vault.zip"), &options)
+        .await
+        .is_err());
+
+    let result = create_backup(ctx, &archive, &options).await.unwrap();
+    assert_eq!(result.files, 4);
+    assert!(!partial_archive_path(&archive).exists());
+
+    let target = output.path().join("restored");
+    let restored = restore_backup(&archive, &target).await.unwrap();
+    assert_eq!(restored.files, 4);
+    assert_eq!(restored.schemas, 1);
+    assert!(restored.errors.is_none());
+    assert!(target.join("books").join("How to Read a Book.md").is_file());
+    assert!(target
+        .join("books")
+        .join(".asom")
+        .join("schema.yaml")
+        .is_file());
+    assert!(target.join(".gitignore").is_file());
+    assert!(!target.join("drafts").exists());
+    assert!(!target.join(".trash").exists());
+
+    // Restore never writes over existing files
+    assert!(restore_backup(&archive, &target).await.is_err());
+
+    std::fs::write(
+        target.join("books").join(".asom").join("schema.yaml"),
+        "items: [",
+    )
+    .unwrap();
+    let broken = output.path().join("broken.zip");
+    core.test_only_set_root_path(target.to_string_lossy().to_string())
+        .await;
+    create_backup(ctx, &broken, &options).await.unwrap();
+    let restored = restore_backup(&broken, &output.path().join("broken"))
+        .await
+        .unwrap();
+    assert!(restored.errors.is_some());
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_export_ical() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let output = tempfile::tempdir().unwrap();
+    let ics = output.path().join("history.ics");
+    let mut options = IcalExportOptions {
+        folders: vec!["books".to_string()],
+        from: None,
+        to: None,
+        missing_finished: MissingFinishedRule::Skip,
+        missing_started: MissingStartedRule::Skip,
+    };
+
+    let result = export_ical(ctx, &ics, &options).await.unwrap();
+    assert_eq!(result.events, 1);
+    assert!(result.errors.is_none());
+
+    let content = std::fs::read_to_string(&ics).unwrap();
+    assert!(content.contains("SUMMARY:How to Read a Book\r\n"));
+    assert!(content.contains("DTSTART;VALUE=DATE:20250203\r\n"));
+    assert!(content.contains("DTEND;VALUE=DATE:20250307\r\n"));
+
+    options.from = Some("2025-04-01".to_string());
+    let result = export_ical(ctx, &ics, &options).await.unwrap();
+    assert_eq!(result.events, 0);
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_export_citations() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let mut mapping = load_citation_mapping(ctx, Path::new("books"))
+        .await
+        .unwrap();
+    assert_eq!(mapping.author.as_deref(), Some("author"));
+    assert_eq!(mapping.year.as_deref(), Some("year"));
+
+    // Saved mapping wins over the guess
+    mapping.title = None;
+    save_citation_mapping(ctx, Path::new("books"), mapping.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        load_citation_mapping(ctx, Path::new("books"))
+            .await
+            .unwrap(),
+        mapping
+    );
+
+    let output = tempfile::tempdir().unwrap();
+    let bib = output.path().join("books.bib");
+    let result = export_citations(ctx, Path::new("books"), &bib, CitationFormat::Bibtex)
+        .await
+        .unwrap();
+    assert_eq!(result.entries, 2);
+
+    let content = std::fs::read_to_string(&bib).unwrap();
+    assert!(content.contains("@book{adler1942how,"));
+    assert!(content.contains("  title = {How to Read a Book},"));
+    assert!(content.contains("  author = {Ahrens, Sönke},"));
+
+    let json = output.path().join("books.json");
+    export_citations(ctx, Path::new("books"), &json, CitationFormat::CslJson)
+        .await
+        .unwrap();
+    let items: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+    assert_eq!(items[1]["author"][0]["family"], "Ahrens");
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_aggregate() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let mut options = AggregateOptions {
+        folder: "books".to_string(),
+        group_by: GroupBy::Year("read".to_string()),
+        numbers: vec!["myRating".to_string()],
+    };
+    let result = aggregate(ctx, &options).await.unwrap();
+    assert_eq!(result.records, 2);
+    assert_eq!(result.groups.len(), 2);
+    assert_eq!(result.groups[0].key.as_deref(), Some("2025"));
+    assert_eq!(result.groups[0].numbers[0].avg, Some(5.0));
+    assert_eq!(result.groups[1].key, None);
+
+    options.group_by = GroupBy::Value("tags".to_string());
+    let result = aggregate(ctx, &options).await.unwrap();
+    let keys: Vec<Option<&str>> = result.groups.iter().map(|g| g.key.as_deref()).collect();
+    assert_eq!(keys, vec![Some("asd"), Some("tag1"), Some("tag2"), None]);
+
+    options.group_by = GroupBy::Month("author".to_string());
+    assert!(aggregate(ctx, &options).await.is_err());
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_year_review() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let mut options = YearReviewOptions {
+        folder: "books".to_string(),
+        year: 2025,
+        attributes: None,
+        report: None,
+    };
+    let review = year_review(ctx, &options).await.unwrap().review;
+    assert_eq!(review.attributes.dates.as_deref(), Some("read"));
+    assert_eq!(review.finished, 1);
+    assert_eq!(review.months[2].count, 1);
+    assert_eq!(review.average_rating, Some(5.0));
+    assert_eq!(review.total_length, None);
+    assert_eq!(review.tags.len(), 3);
+
+    // Markdown report in schema folder would become a record
+    options.report = Some(ReviewReportOptions {
+        format: ReviewReportFormat::Markdown,
+        folder: "books".to_string(),
+    });
+    assert!(year_review(ctx, &options).await.is_err());
+
+    options.report = Some(ReviewReportOptions {
+        format: ReviewReportFormat::Markdown,
+        folder: "".to_string(),
+    });
+    let output = year_review(ctx, &options).await.unwrap();
+    assert_eq!(output.undo.len(), 1);
+    let report = path.join(output.review.report.unwrap());
+    let content = std::fs::read_to_string(&report).unwrap();
+    assert!(content.starts_with("# books: 2025 in review\n"));
+
+    options.report = Some(ReviewReportOptions {
+        format: ReviewReportFormat::Html,
+        folder: "books".to_string(),
+    });
+    let output = year_review(ctx, &options).await.unwrap();
+    assert!(output.review.report.unwrap().ends_with(".html"));
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_batch_edit_emits_one_event() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let batches = Arc::new(Mutex::new(vec![]));
+    let updates = Arc::new(Mutex::new(0));
+    {
+        let batches = batches.clone();
+        app.listen_any("FileUpdateBatch", move |e| {
+            batches.lock().unwrap().push(e.payload().to_string())
+        });
+        let updates = updates.clone();
+        app.listen_any("FileUpdate", move |_| *updates.lock().unwrap() += 1);
+    }
+
+    let target = || BatchTarget::Query {
+        folder: "books".to_string(),
+        attribute: "author".to_string(),
+        value: "Adler, Mortimer J.".to_string(),
+    };
+    let operation = BatchOperation::AddToCollection("batch".to_string());
+
+    let dry = crate::c_batch_edit(
+        app.clone(),
+        target(),
+        "tags".to_string(),
+        operation.clone(),
+        true,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        dry.changed,
+        vec![Path::new("books")
+            .join("How to Read a Book.md")
+            .to_string_lossy()]
+    );
+
+    let result = crate::c_batch_edit(app.clone(), target(), "tags".to_string(), operation, false)
+        .await
+        .unwrap();
+    assert_eq!(result.changed.len(), 1);
+    assert!(result.errors.is_none());
+
+    let record = read_file_by_path(ctx, Path::new(&result.changed[0]), FileReadMode::OnlyMeta)
+        .await
+        .unwrap()
+        .record;
+    assert!(matches!(
+        record.attrs.get("tags"),
+        Some(AttrValue::StringVec(Some(tags))) if tags.contains(&"batch".to_string())
+    ));
+
+    let emitted = || async { batches.lock().unwrap().len() == 1 };
+    assert!(wait_for_condition_async(emitted, DEFAULT_RETRY_COUNT).await);
+    assert!(batches.lock().unwrap()[0].contains("How to Read a Book.md"));
+
+    // Watcher sees the cached hash and does not send its own event per file
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(batches.lock().unwrap().len(), 1);
+    assert_eq!(*updates.lock().unwrap(), 0);
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_undo_redo_edit_and_rename() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let record_path = Path::new("books").join("How to Read a Book.md");
+    let original = std::fs::read_to_string(path.join(&record_path)).unwrap();
+
+    let read = read_file_by_path(ctx, &record_path, FileReadMode::FullFile)
+        .await
+        .unwrap();
+    for body in ["first", "second"] {
+        let mut record = read.record.clone();
+        record.markdown = Some(body.to_string());
+        crate::c_save_file(app.clone(), record, true, false)
+            .await
+            .unwrap();
+    }
+
+    // Consecutive saves of the same file are one step back
+    let status = crate::c_get_journal(app.clone()).await.unwrap();
+    assert_eq!(status.undo.len(), 1);
+
+    crate::c_undo(app.clone()).await.unwrap();
+    assert_eq!(
+        std::fs::read_to_string(path.join(&record_path)).unwrap(),
+        original
+    );
+
+    let status = crate::c_redo(app.clone()).await.unwrap();
+    assert!(std::fs::read_to_string(path.join(&record_path))
+        .unwrap()
+        .ends_with("second"));
+    assert_eq!(status.undo.len(), 1);
+    assert!(status.redo.is_empty());
+
+    let renamed = Path::new("books").join("Renamed.md");
+    crate::c_rename_path(
+        app.clone(),
+        record_path.to_string_lossy().to_string(),
+        renamed.to_string_lossy().to_string(),
+    )
+    .await
+    .unwrap();
+    assert!(path.join(&renamed).exists());
+
+    crate::c_undo(app.clone()).await.unwrap();
+    assert!(path.join(&record_path).exists());
+    assert!(!path.join(&renamed).exists());
+
+    crate::c_redo(app.clone()).await.unwrap();
+    assert!(!path.join(&record_path).exists());
+    assert!(path.join(&renamed).exists());
+
+    // Failed step stays in history and can be retried
+    std::fs::write(path.join(&record_path), "blocking").unwrap();
+    assert!(crate::c_undo(app.clone()).await.is_err());
+    let status = crate::c_get_journal(app.clone()).await.unwrap();
+    assert_eq!(status.undo.len(), 2);
+    assert!(status.redo.is_empty());
+
+    std::fs::remove_file(path.join(&record_path)).unwrap();
+    let status = crate::c_undo(app.clone()).await.unwrap();
+    assert!(path.join(&record_path).exists());
+    assert_eq!(status.redo.len(), 1);
+
+    cleanup_test_case(path).await;
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_undo_redo_trash() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let record_path = Path::new("books").join("How to Read a Book.md");
+    let original = std::fs::read_to_string(path.join(&record_path)).unwrap();
+
+    crate::c_delete_to_trash(app.clone(), record_path.to_string_lossy().to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!path.join(&record_path).exists());
+
+    crate::c_undo(app.clone()).await.unwrap();
+    assert_eq!(
+        std::fs::read_to_string(path.join(&record_path)).unwrap(),
+        original
+    );
+    assert!(list_trashed_records(ctx).await.unwrap().is_empty());
+
+    crate::c_redo(app.clone()).await.unwrap();
+    assert!(!path.join(&record_path).exists());
+    assert_eq!(list_trashed_records(ctx).await.unwrap().len(), 1);
+
+    crate::c_undo(app.clone()).await.unwrap();
+    assert!(path.join(&record_path).exists());
+
+    // Undoing a create keeps the record in trash, it may already have content typed into it
+    let created = RecordFromDb {
+        path: Some(
+            Path::new("books")
+                .join("New.md")
+                .to_string_lossy()
+                .to_string(),
+        ),
+        markdown: Some("draft".to_string()),
+        ..RecordFromDb::default()
+    };
+    let saved = crate::c_save_file(app.clone(), created, false, true)
+        .await
+        .unwrap();
+    crate::c_undo(app.clone()).await.unwrap();
+    assert!(!path.join(&saved.path).exists());
+    assert!(list_trashed_records(ctx)
+        .await
+        .unwrap()
+        .iter()
+        .any(|r| r.path == saved.path));
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_thumbnail_replaced_when_source_changes() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let encode = |width: u32| {
+        let img = image::RgbImage::from_pixel(width, 100, image::Rgb([200, 10, 10]));
+        let mut result = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgb8(img)
+            .write_to(&mut result, image::ImageFormat::Png)
+            .unwrap();
+        result.into_inner()
+    };
+
+    let assets = path.join(ASSETS_FOLDER_NAME);
+    std::fs::create_dir_all(&assets).unwrap();
+    std::fs::write(assets.join("cover.png"), encode(300)).unwrap();
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let thumbnails = ThumbnailCache::new();
+    thumbnails.init(cache_dir.path()).await;
+
+    let first = thumbnails
+        .get_thumbnail(ctx, "cover.png", ThumbnailSize::Small)
+        .await
+        .unwrap();
+    assert!(first.exists());
+    assert_eq!(
+        thumbnails
+            .get_thumbnail(ctx, "cover.png", ThumbnailSize::Small)
+            .await
+            .unwrap(),
+        first
+    );
+
+    std::fs::write(assets.join("cover.png"), encode(200)).unwrap();
+
+    let second = thumbnails
+        .get_thumbnail(ctx, "cover.png", ThumbnailSize::Small)
+        .await
+        .unwrap();
+    assert_ne!(first, second);
+    assert!(!first.exists());
+    let thumb = image::open(&second).unwrap();
+    assert_eq!((thumb.width(), thumb.height()), (128, 64));
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_apply_health_fixes() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Basic).await;
+
+    let book = Path::new("books").join("How to Read a Book.md");
+    let notes = Path::new("books").join("How to Take Smart Notes.md");
+
+    let content = std::fs::read_to_string(path.join(&book)).unwrap();
+    let broken = content
+        .replace("year: 1942", "year: '1942'")
+        .replace("started: '2025-02-03'", "started: '2025-04-01'");
+    std::fs::write(path.join(&book), &broken).unwrap();
+
+    let content = std::fs::read_to_string(path.join(&notes)).unwrap();
+    std::fs::write(
+        path.join(&notes),
+        content.replace("year: 2017", "year: '2017.5'"),
+    )
+    .unwrap();
+
+    let report = check_vault_health(ctx).await.unwrap();
+    let issues: Vec<_> = report.groups.iter().flat_map(|g| &g.issues).collect();
+
+    // Integer attribute can't keep the fraction, so there is nothing to offer
+    let fraction = issues
+        .iter()
+        .find(|i| Path::new(&i.path) == notes && i.attribute.as_deref() == Some("year"))
+        .unwrap();
+    assert!(fraction.fix.is_none());
+
+    let fixes: Vec<_> = issues.iter().filter_map(|i| i.fix.clone()).collect();
+    assert_eq!(fixes.len(), 2);
+
+    let output = apply_health_fixes(ctx, fixes).await.unwrap();
+    assert!(output.result.errors.is_none());
+    assert_eq!(
+        output.result.fixed,
+        vec![book.to_string_lossy().to_string()]
+    );
+    // Both fixes changed the same file, one snapshot is enough to undo them
+    assert_eq!(output.undo.len(), 1);
+
+    let read = read_file_by_path(ctx, &book, FileReadMode::OnlyMeta)
+        .await
+        .unwrap();
+    assert_eq!(read.record.attrs["year"], AttrValue::Integer(Some(1942.0)));
+    let Some(AttrValue::DatePairVec(Some(pairs))) = read.record.attrs.get("read") else {
+        panic!("read is not a date pair collection");
+    };
+    assert_eq!(pairs[0].started.as_deref(), Some("2025-03-06"));
+    assert_eq!(pairs[0].finished.as_deref(), Some("2025-04-01"));
+
+    let report = check_vault_health(ctx).await.unwrap();
+    assert!(report
+        .groups
+        .iter()
+        .flat_map(|g| &g.issues)
+        .all(|i| Path::new(&i.path) != book || i.fix.is_none()));
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unmanaged_file_events() {
+    let app = app_creator().await;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Nested).await;
+
+    let events = Arc::new(Mutex::new(vec![]));
+    for name in [
+        "UnmanagedFileAdd",
+        "UnmanagedFileUpdate",
+        "UnmanagedFileRemove",
+    ] {
+        let events = events.clone();
+        app.listen_any(name, move |_| events.lock().unwrap().push(name));
+    }
+
+    let has_event = |name: &'static str| {
+        let events = events.clone();
+        move || {
+            let found = events.lock().unwrap().contains(&name);
+            async move { found }
+        }
+    };
+
+    let note = path.join("noschema").join("Watched.md");
+
+    std::fs::write(&note, "---\ntitle: Watched\n---\n").unwrap();
+    assert!(wait_for_condition_async(has_event("UnmanagedFileAdd"), DEFAULT_RETRY_COUNT).await);
+
+    std::fs::write(&note, "---\ntitle: Watched\nyear: 2024\n---\n").unwrap();
+    assert!(wait_for_condition_async(has_event("UnmanagedFileUpdate"), DEFAULT_RETRY_COUNT).await);
+
+    std::fs::remove_file(&note).unwrap();
+    assert!(wait_for_condition_async(has_event("UnmanagedFileRemove"), DEFAULT_RETRY_COUNT).await);
+
+    cleanup_test_case(path).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_export_csv_folder_is_collection() {
+    let app = app_creator().await;
+    let core = app.state::<CoreStateManager>();
+    let ctx = &core.context;
+
+    let (path, _) = prepare_test_case(&app, TestCaseName::Nested).await;
+
+    let broken = path
+        .join("books")
+        .join("favorites")
+        .join("How to Read a Book.md");
+    std::fs::write(&broken, "---\ntitle: [unclosed\n---\nbody").unwrap();
+
+    let output = path.join("export.csv");
+    let result = export_csv(
+        ctx,
+        BatchTarget::Folder("books".to_string()),
+        &output,
+        &CsvExportOptions::default(),
+    )
+    .await
+    .unwrap();
+
+    // Audiobooks have their own schema, broken record is reported and not exported with empty cells
+    assert_eq!(result.rows, 1);
+    assert_eq!(result.errors.unwrap().sub_errors.len(), 1);
+
+    let content = std::fs::read_to_string(&output).unwrap();
+    assert!(content.contains("How to Take Smart Notes"));
+    assert!(!content.contains("Sample Audiobook"));
+    assert!(!content.contains("favorites"));
+
+    cleanup_test_case(path).await;
+}
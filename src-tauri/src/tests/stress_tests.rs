@@ -47,6 +47,7 @@ async fn test_quick_file_creation() {
                     .to_string(),
             ),
             modified: None,
+            hash: None,
             markdown: Some("".to_string()),
             attrs: HashMap::new(),
         };
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{sync::Once, time::Duration};
 
 use tauri::{test::MockRuntime, AppHandle, Manager};
 
@@ -13,7 +13,24 @@ use uuid::Uuid;
 
 use fs_extra::dir::{self, CopyOptions};
 
+/*
+ * Files deleted by tests should not end up in the trash of the user running them.
+ * Freedesktop trash lives in XDG_DATA_HOME, pointing it inside working dir also keeps it on the same mount as test cases
+ */
+fn isolate_system_trash() {
+    static ISOLATE: Once = Once::new();
+    ISOLATE.call_once(|| {
+        let data_home = current_dir()
+            .unwrap()
+            .join(get_working_path())
+            .join("xdg_data");
+        std::fs::create_dir_all(&data_home).unwrap();
+        std::env::set_var("XDG_DATA_HOME", data_home);
+    });
+}
+
 pub async fn app_creator() -> AppHandle<MockRuntime> {
+    isolate_system_trash();
     let app = create_mock_app();
 
     let mut state = CoreStateManager::new(app.handle().clone());
@@ -62,7 +62,6 @@ impl ErrFR {
         self.sub_errors.push(thing);
         self
     }
-    #[allow(dead_code)]
     pub fn subs(mut self, full_array_to_set: Vec<ErrFR>) -> Self {
         self.sub_errors = full_array_to_set;
         self
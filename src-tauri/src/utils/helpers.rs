@@ -6,6 +6,8 @@ use ts_rs::TS;
 
 use crate::files::read_save::RecordReadResult;
 
+const MAX_FILE_NAME_LENGTH: usize = 120;
+
 #[derive(TS, Clone, Serialize)]
 #[ts(export)]
 pub struct BreadcrumbItem {
@@ -60,3 +62,41 @@ pub fn normalize_path_to_os(path: &str) -> PathBuf {
 
     PathBuf::from(&normalized)
 }
+
+/// Several people in one text attribute, "Adler, Mortimer J.; Van Doren, Charles".
+/// Importers join with it and exporters split on it, commas are part of names
+pub const NAMES_SEPARATOR: &str = "; ";
+
+pub fn split_names(value: &str) -> Vec<String> {
+    value
+        .split(NAMES_SEPARATOR.trim())
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .collect()
+}
+
+/// Replaces characters that are not allowed in file names on any platform, brackets are kept
+pub fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+
+    let trimmed: String = cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(['.', ' ', '-'])
+        .chars()
+        .take(MAX_FILE_NAME_LENGTH)
+        .collect();
+
+    match trimmed.trim().is_empty() {
+        true => "Untitled".to_string(),
+        false => trimmed.trim().to_string(),
+    }
+}
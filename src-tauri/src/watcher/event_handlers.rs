@@ -2,15 +2,18 @@ use notify::event::{CreateKind, ModifyKind, RemoveKind, RenameMode};
 use notify::Event;
 use notify::EventKind;
 use std::ffi::OsStr;
+use std::fs;
 use std::path::Path;
 
 use crate::cache::cache_thing::{
-    cache_file, cache_files_folders_schemas, remove_file_from_cache,
-    remove_files_in_folder_from_cache, remove_folder_from_cache,
+    cache_file, cache_file_from_bytes, cache_files_folders_schemas, cache_unmanaged_file,
+    is_in_hidden_folder, remove_file_from_cache, remove_files_in_folder_from_cache,
+    remove_folder_from_cache, remove_unmanaged_files_from_cache,
 };
+use crate::cache::query::get_cached_file_hash;
 use crate::core::core_state::AppContext;
 use crate::emitter::{FileEventDataExisting, FileEventDataRemoved, FolderEventData, IPCEmitEvent};
-
+use crate::files::utils::get_content_hash;
 use crate::utils::errorhandling::ErrFR;
 
 fn get_double_parent_path(path_absolute: &Path) -> Result<&Path, Box<ErrFR>> {
@@ -40,11 +43,15 @@ async fn handle_file_remove(
         Some("md") => {
             let path_relative = ctx.absolute_path_to_relative(path_absolute).await?;
 
-            let schema = ctx
-                .schemas_cache
-                .get_schema(&path_relative)
-                .await
-                .ok_or(Box::new(ErrFR::new("Schema not found for file remove")))?;
+            let schema = match ctx.schemas_cache.get_schema(&path_relative).await {
+                Some(v) => v,
+                None => {
+                    remove_unmanaged_files_from_cache(ctx, &path_relative).await?;
+                    return Ok(vec![IPCEmitEvent::UnmanagedFileRemove(
+                        path_relative.to_string_lossy().to_string(),
+                    )]);
+                }
+            };
 
             match remove_file_from_cache(ctx, path_absolute).await {
                 Ok(_) => Ok(vec![IPCEmitEvent::FileRemove(FileEventDataRemoved {
@@ -69,6 +76,14 @@ async fn handle_file_remove(
                 Err(e) => return Err(e),
             }
 
+            // Records of removed schema are unmanaged from now on
+            if path_absolute
+                .file_name()
+                .is_some_and(|n| n == "schema.yaml")
+            {
+                cache_files_folders_schemas(ctx, get_double_parent_path(path_absolute)?).await?;
+            }
+
             Ok(vec![IPCEmitEvent::SchemasUpdated(
                 ctx.schemas_cache.get_schemas_list().await,
             )])
@@ -88,7 +103,13 @@ async fn handle_file_add(
 
             let schema = match ctx.schemas_cache.get_schema(&path_relative).await {
                 Some(v) => v,
-                None => return Ok(vec![]),
+                None => {
+                    if is_in_hidden_folder(ctx, path_absolute).await? {
+                        return Ok(vec![]);
+                    }
+                    let record = cache_unmanaged_file(ctx, path_absolute).await?;
+                    return Ok(vec![IPCEmitEvent::UnmanagedFileAdd(record)]);
+                }
             };
 
             match cache_file(ctx, path_absolute).await {
@@ -141,9 +162,28 @@ async fn handle_file_update(
 
             let schema = match ctx.schemas_cache.get_schema(&path_relative).await {
                 Some(v) => v,
-                None => return Ok(vec![]),
+                None => {
+                    if is_in_hidden_folder(ctx, path_absolute).await? {
+                        return Ok(vec![]);
+                    }
+                    let record = cache_unmanaged_file(ctx, path_absolute).await?;
+                    return Ok(vec![IPCEmitEvent::UnmanagedFileUpdate(record)]);
+                }
             };
-            match cache_file(ctx, path_absolute).await {
+
+            // Sync tools and editors like to touch files without changing them, no need to reparse those.
+            // File is read once, so skip decision and cached hash come from the same content
+            let bytes = fs::read(path_absolute).map_err(|e| {
+                ErrFR::new("Error reading file")
+                    .info(&path_absolute.to_string_lossy())
+                    .raw(e)
+            })?;
+            let cached_hash = get_cached_file_hash(ctx, &path_relative).await?;
+            if cached_hash.is_some_and(|h| h == get_content_hash(&bytes)) {
+                return Ok(vec![]);
+            }
+
+            match cache_file_from_bytes(ctx, path_absolute, &bytes).await {
                 Ok(record) => Ok(vec![IPCEmitEvent::FileUpdate(FileEventDataExisting {
                     record,
                     path: path_relative.to_string_lossy().to_string(),
@@ -191,6 +231,8 @@ async fn handle_folder_remove(
             let path_relative = ctx.absolute_path_to_relative(path_absolute).await?;
             let schema = ctx.schemas_cache.get_schema(&path_relative).await;
 
+            remove_unmanaged_files_from_cache(ctx, &path_relative).await?;
+
             match schema {
                 Some(schema) => {
                     remove_files_in_folder_from_cache(ctx, &path_relative).await?;